@@ -0,0 +1,66 @@
+use clap::{Parser, Subcommand};
+use sqlx::postgres::PgPool;
+
+/// List and reset trips of the circuit breaker pausing finalization.
+#[derive(Parser, Debug)]
+struct Args {
+    /// database url
+    #[arg(short, long)]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List active trips
+    List {
+        /// only trips of this chain
+        #[arg(short, long)]
+        chain_id: Option<u64>,
+    },
+
+    /// Reset a trip resuming finalization paused by it
+    Reset {
+        /// id of the trip
+        #[arg(short, long)]
+        id: u64,
+
+        /// identity of the admin resetting the trip
+        #[arg(short, long)]
+        operator: String,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let pool = PgPool::connect(&args.database_url).await.unwrap();
+
+    match args.command {
+        Command::List { chain_id } => {
+            let trips = storage::circuit_breaker_trips(&pool, chain_id)
+                .await
+                .unwrap();
+
+            for t in trips {
+                println!(
+                    "id {} chain {} token {:?} tripped {}s ago: {}",
+                    t.id, t.chain_id, t.l1_token, t.tripped_secs, t.reason,
+                );
+            }
+        }
+        Command::Reset { id, operator } => {
+            if storage::reset_circuit_breaker_trip(&pool, id, &operator)
+                .await
+                .unwrap()
+            {
+                println!("trip {id} reset by {operator}");
+            } else {
+                println!("trip {id} is not active");
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use clap::{Parser, Subcommand};
+use ethers::types::Address;
+use sqlx::postgres::PgPool;
+
+/// Pause and resume finalization of withdrawals while they keep being indexed.
+#[derive(Parser, Debug)]
+struct Args {
+    /// database url
+    #[arg(short, long)]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List active pauses
+    List {
+        /// only pauses of this chain and of all chains
+        #[arg(short, long)]
+        chain_id: Option<u64>,
+    },
+
+    /// Pause finalization, of all withdrawals of all chains unless narrowed down
+    Pause {
+        /// reason of the pause
+        #[arg(short, long)]
+        reason: String,
+
+        /// identity of the operator pausing finalization
+        #[arg(short, long)]
+        operator: String,
+
+        /// only pause finalization on this chain
+        #[arg(short, long)]
+        chain_id: Option<u64>,
+
+        /// only pause finalization of this token by its L1 or L2 address
+        #[arg(short, long)]
+        token: Option<Address>,
+
+        /// only pause finalization of withdrawals to this L1 recipient
+        #[arg(short = 'l', long)]
+        l1_recipient: Option<Address>,
+    },
+
+    /// Resume finalization paused by a pause
+    Resume {
+        /// id of the pause
+        #[arg(short, long)]
+        id: u64,
+
+        /// identity of the operator resuming finalization
+        #[arg(short, long)]
+        operator: String,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let pool = PgPool::connect(&args.database_url).await.unwrap();
+
+    match args.command {
+        Command::List { chain_id } => {
+            let pauses = storage::active_finalization_pauses(&pool, chain_id)
+                .await
+                .unwrap();
+
+            for p in pauses {
+                println!(
+                    "id {} chain {:?} token {:?} l1 recipient {:?} paused by {} {}s ago: {}",
+                    p.id, p.chain_id, p.token, p.l1_recipient, p.paused_by, p.paused_secs, p.reason,
+                );
+            }
+        }
+        Command::Pause {
+            reason,
+            operator,
+            chain_id,
+            token,
+            l1_recipient,
+        } => {
+            let id = storage::pause_finalization(
+                &pool,
+                chain_id,
+                token,
+                l1_recipient,
+                &reason,
+                &operator,
+            )
+            .await
+            .unwrap();
+
+            println!("finalization paused by {operator}, resume it with pause id {id}");
+        }
+        Command::Resume { id, operator } => {
+            if storage::resume_finalization(&pool, id, &operator)
+                .await
+                .unwrap()
+            {
+                println!("pause {id} resumed by {operator}");
+            } else {
+                println!("pause {id} is not active");
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use clap::{Parser, Subcommand};
+use ethers::types::H256;
+use sqlx::postgres::PgPool;
+use storage::FinalizationRequestOutcome;
+
+/// Request finalization of withdrawals the finalizer does not finalize on its own.
+#[derive(Parser, Debug)]
+struct Args {
+    /// database url
+    #[arg(short, long)]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List requests and their statuses
+    List {
+        /// only requests of this chain
+        #[arg(short, long)]
+        chain_id: Option<u64>,
+
+        /// only the request of this transaction
+        #[arg(short, long)]
+        tx_hash: Option<H256>,
+    },
+
+    /// Request finalization of the withdrawals of a transaction
+    Request {
+        /// chain the transaction has happened on
+        #[arg(short, long)]
+        chain_id: u64,
+
+        /// hash of the L2 transaction
+        #[arg(short, long)]
+        tx_hash: H256,
+
+        /// identity of the operator requesting finalization
+        #[arg(short, long)]
+        operator: String,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let pool = PgPool::connect(&args.database_url).await.unwrap();
+
+    match args.command {
+        Command::List { chain_id, tx_hash } => {
+            let requests = storage::finalization_requests(&pool, chain_id, tx_hash)
+                .await
+                .unwrap();
+
+            for r in requests {
+                println!(
+                    "id {} chain {} tx {:?} requested by {} {}s ago: {}{}",
+                    r.id,
+                    r.chain_id,
+                    r.tx_hash,
+                    r.requested_by,
+                    r.waiting_secs,
+                    r.status,
+                    r.error.map(|e| format!(" ({e})")).unwrap_or_default(),
+                );
+            }
+        }
+        Command::Request {
+            chain_id,
+            tx_hash,
+            operator,
+        } => {
+            // Operators are not rate-limited.
+            match storage::request_finalization(&pool, chain_id, tx_hash, &operator, None)
+                .await
+                .unwrap()
+            {
+                FinalizationRequestOutcome::Added(id) => {
+                    println!("finalization of {tx_hash:?} requested by {operator} with id {id}")
+                }
+                FinalizationRequestOutcome::Duplicate(id) => {
+                    println!("finalization of {tx_hash:?} has already been requested with id {id}")
+                }
+                FinalizationRequestOutcome::RateLimited => {
+                    println!("too many requests by {operator}")
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use clap::{Parser, Subcommand};
+use sqlx::postgres::PgPool;
+
+/// List pending finalization transactions and cancel them.
+#[derive(Parser, Debug)]
+struct Args {
+    /// database url
+    #[arg(short, long)]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List pending finalization transactions
+    List {
+        /// only transactions of this chain
+        #[arg(short, long)]
+        chain_id: Option<u64>,
+    },
+
+    /// Cancel a pending finalization transaction by replacing it with
+    /// a zero-value transfer once it is due to be sent again
+    Cancel {
+        /// id of the pending transaction
+        #[arg(short, long)]
+        id: u64,
+
+        /// identity of the operator cancelling the transaction
+        #[arg(short, long)]
+        operator: String,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let pool = PgPool::connect(&args.database_url).await.unwrap();
+
+    match args.command {
+        Command::List { chain_id } => {
+            let transactions = storage::pending_finalization_transactions(&pool, chain_id)
+                .await
+                .unwrap();
+
+            for t in transactions {
+                println!(
+                    "id {} chain {} from {:?} pending for {}s withdrawals {:?} cancellation requested by {:?}",
+                    t.id,
+                    t.chain_id,
+                    t.account,
+                    t.pending_secs,
+                    t.withdrawal_ids,
+                    t.cancellation_requested_by,
+                );
+            }
+        }
+        Command::Cancel { id, operator } => {
+            if storage::request_finalization_transaction_cancellation(&pool, id, &operator)
+                .await
+                .unwrap()
+            {
+                println!("cancellation of transaction {id} requested by {operator}");
+            } else {
+                println!("transaction {id} is not pending or is already being cancelled");
+            }
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use std::sync::Arc;
+
+use clap::Parser;
+use client::{
+    l1bridge::codegen::IL1Bridge, l1sharedbridge::codegen::IL1SharedBridge,
+    zksync_contract::codegen::IZkSync, FinalizationContracts, WithdrawalParams, ZksyncMiddleware,
+};
+use ethers::{
+    providers::{Http, Middleware, Provider},
+    types::{Address, H256, U256},
+};
+use eyre::{anyhow, Result};
+use serde_json::{json, Value};
+use url::Url;
+
+/// Build calldata finalizing withdrawals of an L2 transaction directly on the L1
+/// contracts of a chain to be sent by users themselves, no database required.
+#[derive(Parser, Debug)]
+struct Args {
+    /// L1 JSON-RPC url
+    #[arg(long)]
+    l1_rpc_url: Url,
+
+    /// L2 JSON-RPC url of the chain the withdrawal has happened on
+    #[arg(long)]
+    l2_rpc_url: Url,
+
+    /// hash of the L2 transaction
+    #[arg(short, long)]
+    tx_hash: H256,
+
+    /// index of the withdrawal in the transaction, all of its withdrawals if not given
+    #[arg(short, long)]
+    index: Option<usize>,
+
+    /// address of the L1 shared bridge, the legacy contracts are used if not given
+    #[arg(long)]
+    l1_shared_bridge: Option<Address>,
+
+    /// address of the diamond proxy of the chain, finalizing ETH withdrawals in the legacy flow,
+    /// the main contract reported by the L2 node if not given
+    #[arg(long)]
+    diamond_proxy: Option<Address>,
+
+    /// address of the L1 ERC20 bridge finalizing token withdrawals in the legacy flow
+    #[arg(long, required_unless_present = "l1_shared_bridge")]
+    l1_erc20_bridge: Option<Address>,
+
+    /// simulate the finalization with `eth_call`
+    #[arg(short, long)]
+    simulate: bool,
+
+    /// account to simulate the finalization from
+    #[arg(short, long)]
+    from: Option<Address>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let client_l1 = Arc::new(Provider::new(Http::new(args.l1_rpc_url)));
+    let client_l2 = Provider::new(Http::new(args.l2_rpc_url));
+
+    let chain_id = client_l2
+        .get_chainid()
+        .await
+        .map_err(|e| anyhow!("{e}"))?
+        .as_u64();
+
+    // Withdrawals are only ready to be finalized once their batches are executed.
+    let diamond_proxy = match args.diamond_proxy {
+        Some(diamond_proxy) => diamond_proxy,
+        None => client_l2
+            .request("zks_getMainContract", ())
+            .await
+            .map_err(|e| anyhow!("{e}"))?,
+    };
+    let zksync_contract = IZkSync::new(diamond_proxy, client_l1.clone());
+
+    let contracts = match (args.l1_shared_bridge, args.l1_erc20_bridge) {
+        (Some(shared_bridge), _) => FinalizationContracts::SharedBridge {
+            shared_bridge: IL1SharedBridge::new(shared_bridge, client_l1),
+            chain_id,
+        },
+        (None, Some(l1_erc20_bridge)) => FinalizationContracts::Legacy {
+            zksync_contract: zksync_contract.clone(),
+            l1_bridge: IL1Bridge::new(l1_erc20_bridge, client_l1),
+        },
+        _ => unreachable!("clap requires the legacy contracts without the shared bridge; qed"),
+    };
+
+    let indices = match args.index {
+        Some(index) => vec![index],
+        None => {
+            let events = client_l2.get_withdrawal_events(args.tx_hash).await?;
+            if events.is_empty() {
+                return Err(anyhow!("no withdrawals in transaction {:?}", args.tx_hash));
+            }
+
+            (0..events.len()).collect()
+        }
+    };
+
+    let mut withdrawals = Vec::with_capacity(indices.len());
+    for index in indices {
+        // The proof is only available once the batch of the withdrawal is committed.
+        let params = client_l2
+            .finalize_withdrawal_params(args.tx_hash, index)
+            .await?;
+
+        withdrawals.push(
+            withdrawal_calldata(
+                &zksync_contract,
+                &contracts,
+                args.tx_hash,
+                index,
+                params,
+                args.simulate,
+                args.from,
+            )
+            .await?,
+        );
+    }
+
+    println!("{}", serde_json::to_string_pretty(&withdrawals)?);
+
+    Ok(())
+}
+
+// Describe how to finalize the withdrawal with the given index in the transaction
+// given its parameters if its batch is committed.
+async fn withdrawal_calldata<M: Middleware>(
+    zksync_contract: &IZkSync<M>,
+    contracts: &FinalizationContracts<M>,
+    tx_hash: H256,
+    index: usize,
+    params: Option<WithdrawalParams>,
+    simulate: bool,
+    from: Option<Address>,
+) -> Result<Value> {
+    let Some(params) = params else {
+        return Ok(json!({
+            "tx_hash": tx_hash,
+            "index": index,
+            "ready": false,
+        }));
+    };
+
+    let total_batches_executed = zksync_contract
+        .get_total_batches_executed()
+        .call()
+        .await
+        .map_err(|e| anyhow!("{e}"))?;
+
+    if U256::from(params.l1_batch_number.as_u64()) > total_batches_executed {
+        return Ok(json!({
+            "tx_hash": tx_hash,
+            "index": index,
+            "ready": false,
+            "l1_batch_number": params.l1_batch_number,
+            "total_batches_executed": total_batches_executed,
+        }));
+    }
+
+    let finalized = contracts
+        .is_withdrawal_finalized(
+            params.l1_batch_number.as_u64().into(),
+            params.l2_message_index.into(),
+            params.sender,
+        )
+        .await?;
+
+    let mut call = contracts.finalize_withdrawal(&params);
+    if let Some(from) = from {
+        call = call.from(from);
+    }
+
+    let simulation = match (simulate, finalized) {
+        (true, false) => Some(match call.call().await {
+            Ok(()) => json!({ "success": true }),
+            Err(e) => json!({ "success": false, "error": e.to_string() }),
+        }),
+        _ => None,
+    };
+
+    Ok(json!({
+        "tx_hash": tx_hash,
+        "index": index,
+        "ready": true,
+        "finalized": finalized,
+        "l1_batch_number": params.l1_batch_number,
+        "l2_message_index": params.l2_message_index,
+        "target": call.tx.to_addr(),
+        "function": call.function.name,
+        "calldata": call.calldata(),
+        "simulation": simulation,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        abi::{self, Token},
+        providers::MockProvider,
+        types::Bytes,
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CHAIN_ID: u64 = 324;
+
+    struct Mock {
+        mock: MockProvider,
+        zksync_contract: IZkSync<Provider<MockProvider>>,
+        contracts: FinalizationContracts<Provider<MockProvider>>,
+        shared_bridge: Address,
+    }
+
+    fn mock() -> Mock {
+        let mock = MockProvider::new();
+        let provider = Arc::new(Provider::new(mock.clone()));
+        let shared_bridge = Address::random();
+
+        Mock {
+            mock,
+            zksync_contract: IZkSync::new(Address::random(), provider.clone()),
+            contracts: FinalizationContracts::SharedBridge {
+                shared_bridge: IL1SharedBridge::new(shared_bridge, provider),
+                chain_id: CHAIN_ID,
+            },
+            shared_bridge,
+        }
+    }
+
+    // Mock the results of the calls in the order they are made.
+    fn push_calls(mock: &MockProvider, results: Vec<Token>) {
+        for result in results.into_iter().rev() {
+            mock.push::<Bytes, _>(Bytes::from(abi::encode(&[result])))
+                .unwrap();
+        }
+    }
+
+    fn params(l1_batch_number: u64) -> WithdrawalParams {
+        WithdrawalParams {
+            chain_id: CHAIN_ID,
+            tx_hash: H256::random(),
+            event_index_in_tx: 0,
+            id: 1,
+            l2_block_number: 1,
+            l1_batch_number: l1_batch_number.into(),
+            l2_message_index: 3,
+            l2_tx_number_in_block: 0,
+            message: Default::default(),
+            sender: Address::random(),
+            proof: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn withdrawals_of_uncommitted_batches_are_not_ready() {
+        let Mock {
+            zksync_contract,
+            contracts,
+            ..
+        } = mock();
+        let tx_hash = H256::random();
+
+        let calldata =
+            withdrawal_calldata(&zksync_contract, &contracts, tx_hash, 1, None, false, None)
+                .await
+                .unwrap();
+
+        assert_eq!(
+            calldata,
+            json!({ "tx_hash": tx_hash, "index": 1, "ready": false })
+        );
+    }
+
+    #[tokio::test]
+    async fn withdrawals_of_unexecuted_batches_are_not_ready() {
+        let Mock {
+            mock,
+            zksync_contract,
+            contracts,
+            ..
+        } = mock();
+        push_calls(&mock, vec![Token::Uint(9.into())]);
+        let tx_hash = H256::random();
+
+        let calldata = withdrawal_calldata(
+            &zksync_contract,
+            &contracts,
+            tx_hash,
+            0,
+            Some(params(10)),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            calldata,
+            json!({
+                "tx_hash": tx_hash,
+                "index": 0,
+                "ready": false,
+                "l1_batch_number": "0xa",
+                "total_batches_executed": "0x9",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn withdrawals_of_executed_batches_are_ready() {
+        let Mock {
+            mock,
+            zksync_contract,
+            contracts,
+            shared_bridge,
+        } = mock();
+        // The batch is executed, the withdrawal is not finalized and the simulation succeeds,
+        // mocked responses are popped in reverse.
+        mock.push::<Bytes, _>(Bytes::new()).unwrap();
+        push_calls(&mock, vec![Token::Uint(10.into()), Token::Bool(false)]);
+        let tx_hash = H256::random();
+        let params = params(10);
+        let calldata = contracts.finalize_withdrawal(&params).calldata().unwrap();
+
+        let json = withdrawal_calldata(
+            &zksync_contract,
+            &contracts,
+            tx_hash,
+            0,
+            Some(params),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            json,
+            json!({
+                "tx_hash": tx_hash,
+                "index": 0,
+                "ready": true,
+                "finalized": false,
+                "l1_batch_number": "0xa",
+                "l2_message_index": 3,
+                "target": shared_bridge,
+                "function": "finalizeWithdrawal",
+                "calldata": calldata,
+                "simulation": { "success": true },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn finalized_withdrawals_are_not_simulated() {
+        let Mock {
+            mock,
+            zksync_contract,
+            contracts,
+            ..
+        } = mock();
+        push_calls(&mock, vec![Token::Uint(11.into()), Token::Bool(true)]);
+
+        let json = withdrawal_calldata(
+            &zksync_contract,
+            &contracts,
+            H256::random(),
+            0,
+            Some(params(10)),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(json["ready"], json!(true));
+        assert_eq!(json["finalized"], json!(true));
+        assert_eq!(json["simulation"], Value::Null);
+    }
+}
@@ -0,0 +1,135 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use ethers::types::{Address, U256};
+use sqlx::postgres::PgPool;
+use storage::TokenPolicy;
+
+/// Manage finalization policies of tokens applied by the finalizer without a restart.
+#[derive(Parser, Debug)]
+struct Args {
+    /// database url
+    #[arg(short, long)]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List policies of tokens of a chain
+    List {
+        /// chain id
+        #[arg(short, long)]
+        chain_id: u64,
+    },
+
+    /// Set a policy of a token, the settings not given are kept
+    Set {
+        /// chain id
+        #[arg(short, long)]
+        chain_id: u64,
+
+        /// L2 address of the token
+        #[arg(short, long)]
+        token: Address,
+
+        /// whether the token is allowed or denied to be finalized
+        #[arg(short, long, value_enum)]
+        allowed: Option<Allowance>,
+
+        /// minimal amount of a finalized withdrawal in the smallest units of the token
+        #[arg(long, value_parser = U256::from_dec_str, conflicts_with = "no_threshold")]
+        threshold: Option<U256>,
+
+        /// remove the threshold
+        #[arg(long)]
+        no_threshold: bool,
+
+        /// withdrawals of tokens with higher priority are finalized first
+        #[arg(short, long, allow_hyphen_values = true)]
+        priority: Option<i32>,
+
+        /// whether finalization of the token is paused
+        #[arg(long)]
+        paused: Option<bool>,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Allowance {
+    Allowed,
+    Denied,
+    Unset,
+}
+
+impl From<Allowance> for Option<bool> {
+    fn from(allowance: Allowance) -> Self {
+        match allowance {
+            Allowance::Allowed => Some(true),
+            Allowance::Denied => Some(false),
+            Allowance::Unset => None,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let pool = PgPool::connect(&args.database_url).await.unwrap();
+
+    match args.command {
+        Command::List { chain_id } => {
+            let policies = storage::token_policies(&pool, chain_id).await.unwrap();
+
+            for p in policies {
+                print_policy(&p);
+            }
+        }
+        Command::Set {
+            chain_id,
+            token,
+            allowed,
+            threshold,
+            no_threshold,
+            priority,
+            paused,
+        } => {
+            let mut policy = storage::token_policies(&pool, chain_id)
+                .await
+                .unwrap()
+                .into_iter()
+                .find(|p| p.l2_token_address == token)
+                .unwrap_or(TokenPolicy {
+                    l2_token_address: token,
+                    ..Default::default()
+                });
+
+            if let Some(allowed) = allowed {
+                policy.allowed = allowed.into();
+            }
+            if threshold.is_some() || no_threshold {
+                policy.threshold = threshold;
+            }
+            if let Some(priority) = priority {
+                policy.priority = priority;
+            }
+            if let Some(paused) = paused {
+                policy.paused = paused;
+            }
+
+            storage::set_token_policy(&pool, chain_id, &policy)
+                .await
+                .unwrap();
+
+            print_policy(&policy);
+        }
+    }
+}
+
+fn print_policy(p: &TokenPolicy) {
+    println!(
+        "token {:?} allowed {:?} threshold {:?} priority {} paused {}",
+        p.l2_token_address, p.allowed, p.threshold, p.priority, p.paused,
+    );
+}
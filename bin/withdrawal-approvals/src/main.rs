@@ -0,0 +1,176 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use client::withdrawal_finalizer::codegen::{
+    FinalizeWithdrawalsCall, FinalizeWithdrawalsSharedBridgeCall,
+    RequestFinalizeWithdrawalSharedBridge,
+};
+use ethers::abi::AbiEncode;
+use sqlx::postgres::PgPool;
+use storage::ApprovalStatus;
+
+/// Manage withdrawals finalized only once approved by an operator.
+#[derive(Parser, Debug)]
+struct Args {
+    /// database url
+    #[arg(short, long)]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List withdrawals by their approval status
+    List {
+        /// only withdrawals of this chain
+        #[arg(short, long)]
+        chain_id: Option<u64>,
+
+        /// approval status
+        #[arg(short, long, value_enum, default_value_t = Status::ApprovalRequired)]
+        status: Status,
+    },
+
+    /// Approve a withdrawal to be finalized automatically
+    Approve {
+        /// id of withdrawal
+        #[arg(short, long)]
+        withdrawal_id: u64,
+
+        /// identity of the operator approving the withdrawal
+        #[arg(short, long)]
+        operator: String,
+    },
+
+    /// Reject a withdrawal so that it is never finalized automatically
+    Reject {
+        /// id of withdrawal
+        #[arg(short, long)]
+        withdrawal_id: u64,
+
+        /// identity of the operator rejecting the withdrawal
+        #[arg(short, long)]
+        operator: String,
+    },
+
+    /// Build calldata of the finalizer contract finalizing a withdrawal
+    /// to be signed by a multisig
+    Calldata {
+        /// id of withdrawal
+        #[arg(short, long)]
+        withdrawal_id: u64,
+
+        /// gas
+        #[arg(short, long)]
+        gas: u64,
+
+        /// the chain of the withdrawal finalizes withdrawals through the shared bridge
+        #[arg(long)]
+        shared_bridge: bool,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Status {
+    ApprovalRequired,
+    Approved,
+    Rejected,
+}
+
+impl From<Status> for ApprovalStatus {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::ApprovalRequired => ApprovalStatus::ApprovalRequired,
+            Status::Approved => ApprovalStatus::Approved,
+            Status::Rejected => ApprovalStatus::Rejected,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let pool = PgPool::connect(&args.database_url).await.unwrap();
+
+    match args.command {
+        Command::List { chain_id, status } => {
+            let approvals = storage::withdrawal_approvals(&pool, chain_id, status.into())
+                .await
+                .unwrap();
+
+            for a in approvals {
+                println!(
+                    "id {} chain {} tx {:?} token {:?} amount {} receiver {:?} operator {} waiting {}s",
+                    a.withdrawal_id,
+                    a.chain_id,
+                    a.tx_hash,
+                    a.token,
+                    a.amount,
+                    a.l1_receiver,
+                    a.operator.as_deref().unwrap_or("-"),
+                    a.waiting_secs,
+                );
+            }
+        }
+        Command::Approve {
+            withdrawal_id,
+            operator,
+        } => decide(&pool, withdrawal_id, true, &operator).await,
+        Command::Reject {
+            withdrawal_id,
+            operator,
+        } => decide(&pool, withdrawal_id, false, &operator).await,
+        Command::Calldata {
+            withdrawal_id,
+            gas,
+            shared_bridge,
+        } => {
+            let request_finalize_withdrawal =
+                storage::get_finalize_withdrawal_params(&pool, withdrawal_id, gas)
+                    .await
+                    .unwrap()
+                    .unwrap();
+
+            let encoded = if shared_bridge {
+                let withdrawal = storage::get_withdrawals(&pool, &[withdrawal_id as i64])
+                    .await
+                    .unwrap()
+                    .pop()
+                    .unwrap();
+
+                FinalizeWithdrawalsSharedBridgeCall {
+                    requests: vec![RequestFinalizeWithdrawalSharedBridge {
+                        chain_id: withdrawal.chain_id.into(),
+                        l_2_batch_number: request_finalize_withdrawal.l_2_block_number,
+                        l_2_message_index: request_finalize_withdrawal.l_2_message_index,
+                        l_2_tx_number_in_batch: request_finalize_withdrawal.l_2_tx_number_in_block,
+                        message: request_finalize_withdrawal.message,
+                        merkle_proof: request_finalize_withdrawal.merkle_proof,
+                        gas: request_finalize_withdrawal.gas,
+                    }],
+                }
+                .encode()
+            } else {
+                FinalizeWithdrawalsCall {
+                    requests: vec![request_finalize_withdrawal],
+                }
+                .encode()
+            };
+
+            println!("hex payload is\n{}", hex::encode(encoded));
+        }
+    }
+}
+
+async fn decide(pool: &PgPool, withdrawal_id: u64, approve: bool, operator: &str) {
+    let decided = storage::decide_withdrawal_approval(pool, withdrawal_id, approve, operator)
+        .await
+        .unwrap();
+
+    match (decided, approve) {
+        (true, true) => println!("withdrawal {withdrawal_id} approved by {operator}"),
+        (true, false) => println!("withdrawal {withdrawal_id} rejected by {operator}"),
+        (false, _) => println!("withdrawal {withdrawal_id} does not await approval"),
+    }
+}
@@ -1,11 +1,45 @@
-use std::str::FromStr;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    num::NonZeroUsize,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
 
+use client::{
+    rate_limit::RateLimits,
+    signer::{AccountSigner, RemoteSigner},
+    BaseToken,
+};
 use envconfig::Envconfig;
-use ethers::types::Address;
-use finalizer::AddrList;
+use ethers::{
+    providers::Http,
+    signers::{LocalWallet, WalletError},
+    types::Address,
+};
+use finalizer::{AddrList, CircuitBreaker, PriceSource};
 use serde::{Deserialize, Serialize};
+use storage::{BigDecimal, TokenThreshold};
 use url::Url;
 
+const DEFAULT_RPC_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// Default finalization deadline in seconds.
+pub const DEFAULT_FINALIZATION_DEADLINE_SECS: u64 = 86400;
+
+/// Default margin of finalization deadlines in seconds.
+pub const DEFAULT_FINALIZATION_DEADLINE_MARGIN_SECS: u64 = 3600;
+
+/// Default minimal ratio of the value of a withdrawal to the fee of finalizing it.
+pub const DEFAULT_MIN_VALUE_TO_FEE_RATIO: f64 = 1.0;
+
+/// Default number of windows the baseline of the circuit breaker is averaged over.
+const DEFAULT_CIRCUIT_BREAKER_BASELINE_WINDOWS: u32 = 7;
+
+/// Default number of finalization requests a client may make through the API within an hour.
+pub const DEFAULT_FINALIZATION_REQUESTS_PER_HOUR: u64 = 10;
+
 /// Withdrawal finalizer configuration.
 ///
 /// Can be read from
@@ -13,13 +47,13 @@ use url::Url;
 /// * TOML config file via [`Self::from_file()`]
 #[derive(Debug, Envconfig)]
 pub struct Config {
-    /// L1 WS url.
+    /// L1 WS urls.
     #[envconfig(from = "ETH_CLIENT_WS_URL")]
-    pub eth_client_ws_url: Url,
+    pub eth_client_ws_url: UrlList,
 
-    /// L1 HTTP url.
+    /// L1 HTTP urls.
     #[envconfig(from = "ETH_CLIENT_HTTP_URL")]
-    pub eth_client_http_url: Url,
+    pub eth_client_http_url: UrlList,
 
     /// Address of the `L1Bridge` contract.
     #[envconfig(from = "CONTRACTS_L1_ERC20_BRIDGE_PROXY_ADDR")]
@@ -33,17 +67,67 @@ pub struct Config {
     #[envconfig(from = "CONTRACTS_DIAMOND_PROXY_ADDR")]
     pub diamond_proxy_addr: Address,
 
-    /// Finalizer contract
+    /// Address of the `L1SharedBridge` contract, finalize through it if set.
+    #[envconfig(from = "CONTRACTS_L1_SHARED_BRIDGE_PROXY_ADDR")]
+    pub l1_shared_bridge_proxy_addr: Option<Address>,
+
+    /// L1 address of the base token of the chain if it is not ETH.
+    #[envconfig(from = "BASE_TOKEN_L1_ADDRESS")]
+    pub base_token_l1_address: Option<Address>,
+
+    /// Decimals of the base token of the chain if it is not ETH.
+    #[envconfig(from = "BASE_TOKEN_DECIMALS")]
+    pub base_token_decimals: Option<u32>,
+
+    /// ZK chains to finalize withdrawals of in addition to the main one
+    /// through the shared bridge.
+    #[envconfig(from = "ADDITIONAL_CHAINS")]
+    pub additional_chains: Option<ChainConfigs>,
+
+    /// Finalizer contract, withdrawals are finalized one by one directly
+    /// on the contracts of the chain without it.
     #[envconfig(from = "CONTRACTS_WITHDRAWAL_FINALIZER_CONTRACT")]
-    pub withdrawal_finalizer_addr: Address,
+    pub withdrawal_finalizer_addr: Option<Address>,
 
-    /// L2 WS Endpoint
+    /// L2 WS Endpoints
     #[envconfig(from = "API_WEB3_JSON_RPC_WS_URL")]
-    pub api_web3_json_rpc_ws_url: Url,
+    pub api_web3_json_rpc_ws_url: UrlList,
 
-    /// L2 HTTP Endpoint
+    /// L2 HTTP Endpoints
     #[envconfig(from = "API_WEB3_JSON_RPC_HTTP_URL")]
-    pub api_web3_json_rpc_http_url: Url,
+    pub api_web3_json_rpc_http_url: UrlList,
+
+    /// Number of L1 HTTP endpoints that have to agree on withdrawal finalization status.
+    #[envconfig(from = "ETH_CLIENT_QUORUM")]
+    pub eth_client_quorum: Option<usize>,
+
+    /// Number of L2 HTTP endpoints that have to agree on withdrawal proofs.
+    #[envconfig(from = "API_WEB3_JSON_RPC_QUORUM")]
+    pub api_web3_json_rpc_quorum: Option<usize>,
+
+    /// Maximum number of requests per second to each of L1 HTTP endpoints.
+    #[envconfig(from = "ETH_CLIENT_REQUESTS_PER_SECOND")]
+    pub eth_client_requests_per_second: Option<u32>,
+
+    /// Maximum number of in-flight requests to each of L1 HTTP endpoints.
+    #[envconfig(from = "ETH_CLIENT_MAX_IN_FLIGHT_REQUESTS")]
+    pub eth_client_max_in_flight_requests: Option<usize>,
+
+    /// Maximum number of requests per second to each of L2 HTTP endpoints.
+    #[envconfig(from = "API_WEB3_JSON_RPC_REQUESTS_PER_SECOND")]
+    pub api_web3_json_rpc_requests_per_second: Option<u32>,
+
+    /// Maximum number of in-flight requests to each of L2 HTTP endpoints.
+    #[envconfig(from = "API_WEB3_JSON_RPC_MAX_IN_FLIGHT_REQUESTS")]
+    pub api_web3_json_rpc_max_in_flight_requests: Option<usize>,
+
+    /// Number of retries of requests rejected by RPC endpoints as rate limited.
+    #[envconfig(from = "RPC_RATE_LIMIT_RETRIES")]
+    pub rpc_rate_limit_retries: Option<u32>,
+
+    /// Number of entries in each of the caches of immutable L2 data.
+    #[envconfig(from = "L2_RPC_CACHE_CAPACITY")]
+    pub l2_rpc_cache_capacity: Option<NonZeroUsize>,
 
     #[envconfig(from = "DATABASE_URL")]
     pub database_url: Url,
@@ -58,11 +142,51 @@ pub struct Config {
     pub batch_finalization_gas_limit: String,
 
     #[envconfig(from = "WITHDRAWAL_FINALIZER_ACCOUNT_PRIVATE_KEY")]
-    pub account_private_key: String,
+    pub account_private_key: Option<String>,
+
+    /// Path to an encrypted JSON keystore of the finalizer account
+    #[envconfig(from = "WITHDRAWAL_FINALIZER_ACCOUNT_KEYSTORE_PATH")]
+    pub account_keystore_path: Option<PathBuf>,
+
+    /// Path to a file with the password of the keystore
+    #[envconfig(from = "WITHDRAWAL_FINALIZER_ACCOUNT_KEYSTORE_PASSWORD_PATH")]
+    pub account_keystore_password_path: Option<PathBuf>,
+
+    /// URL of the JSON-RPC API of a remote signer holding the key of the finalizer account
+    #[envconfig(from = "WITHDRAWAL_FINALIZER_REMOTE_SIGNER_URL")]
+    pub remote_signer_url: Option<Url>,
+
+    /// Address of the finalizer account the remote signer signs with
+    #[envconfig(from = "WITHDRAWAL_FINALIZER_ACCOUNT_ADDRESS")]
+    pub account_address: Option<Address>,
+
+    /// Comma-separated private keys of more accounts to finalize withdrawals from
+    #[envconfig(from = "ADDITIONAL_FINALIZER_ACCOUNT_PRIVATE_KEYS")]
+    pub additional_account_private_keys: Option<WalletList>,
+
+    /// More accounts to finalize withdrawals from held in keystores or by remote signers
+    #[envconfig(from = "ADDITIONAL_FINALIZER_ACCOUNTS")]
+    pub additional_accounts: Option<AccountConfigs>,
+
+    /// Minimal balance in ether of an account to finalize withdrawals from it
+    #[envconfig(from = "FINALIZER_ACCOUNT_MIN_BALANCE_ETH")]
+    pub account_min_balance_eth: Option<String>,
+
+    /// Maximal fees in ether an account may spend on finalization over a rolling 24 hours
+    #[envconfig(from = "DAILY_GAS_BUDGET_ETH")]
+    pub daily_gas_budget_eth: Option<String>,
+
+    /// Daily gas budgets in ether overriding `DAILY_GAS_BUDGET_ETH` for particular accounts
+    #[envconfig(from = "ACCOUNT_DAILY_GAS_BUDGETS_ETH")]
+    pub account_daily_gas_budgets_eth: Option<AccountBudgets>,
 
     #[envconfig(from = "TX_RETRY_TIMEOUT_SECS")]
     pub tx_retry_timeout: usize,
 
+    /// Number of finalization transactions of a chain that may be in flight at once.
+    #[envconfig(from = "MAX_IN_FLIGHT_FINALIZATION_TXS")]
+    pub max_in_flight_finalization_txs: Option<usize>,
+
     #[envconfig(from = "FINALIZE_ETH_TOKEN")]
     pub finalize_eth_token: Option<bool>,
 
@@ -78,17 +202,222 @@ pub struct Config {
     #[envconfig(from = "CUSTOM_TOKEN_ADDRESS_MAPPINGS")]
     pub custom_token_address_mappings: Option<CustomTokenAddressMappings>,
 
+    /// Only finalize withdrawals of the base token of at least this amount
     #[envconfig(from = "ETH_FINALIZATION_THRESHOLD")]
     pub eth_finalization_threshold: Option<String>,
 
+    /// Minimal amounts of finalized withdrawals of tokens in units of the tokens
+    #[envconfig(from = "TOKEN_FINALIZATION_THRESHOLDS")]
+    pub token_finalization_thresholds: Option<TokenThresholds>,
+
+    /// Only finalize withdrawals of the base token of at least this amount once approved
+    #[envconfig(from = "ETH_APPROVAL_THRESHOLD")]
+    pub eth_approval_threshold: Option<String>,
+
+    /// Only finalize withdrawals of tokens of at least these amounts in units of the tokens
+    /// once approved
+    #[envconfig(from = "TOKEN_APPROVAL_THRESHOLDS")]
+    pub token_approval_thresholds: Option<TokenThresholds>,
+
+    /// Finalize withdrawals below thresholds executed this many seconds ago.
+    #[envconfig(from = "BELOW_THRESHOLD_FINALIZATION_AGE_SECS")]
+    pub below_threshold_finalization_age_secs: Option<u64>,
+
+    /// Gas price in gwei at most which withdrawals below thresholds are finalized
+    /// in batches of their own.
+    #[envconfig(from = "BELOW_THRESHOLD_MAX_GAS_PRICE_GWEI")]
+    pub below_threshold_max_gas_price_gwei: Option<u64>,
+
+    /// Base fee in gwei above which finalization is deferred
+    #[envconfig(from = "MAX_BASE_FEE_GWEI")]
+    pub max_base_fee_gwei: Option<u64>,
+
+    /// Withdrawals are finalized regardless of the base fee
+    /// within this many seconds since their execution
+    #[envconfig(from = "FINALIZATION_DEADLINE_SECS")]
+    pub finalization_deadline_secs: Option<u64>,
+
+    /// Withdrawals are finalized regardless of the base fee once
+    /// they are this many seconds away from their deadline
+    #[envconfig(from = "FINALIZATION_DEADLINE_MARGIN_SECS")]
+    pub finalization_deadline_margin_secs: Option<u64>,
+
+    /// Withdrawals are accumulated into batches for up to this many seconds
+    /// since the execution of the oldest of them
+    #[envconfig(from = "BATCHING_WINDOW_SECS")]
+    pub batching_window_secs: Option<u64>,
+
+    /// Batches are finalized once they have this many withdrawals
+    /// within the batching window
+    #[envconfig(from = "BATCH_TARGET_SIZE")]
+    pub batch_target_size: Option<usize>,
+
+    /// A JSON file or an HTTP endpoint with USD prices of tokens
+    #[envconfig(from = "TOKEN_PRICES_SOURCE")]
+    pub token_prices_source: Option<PriceSource>,
+
+    /// Withdrawals worth less than this multiple of the fee of finalizing them are skipped
+    #[envconfig(from = "MIN_VALUE_TO_FEE_RATIO")]
+    pub min_value_to_fee_ratio: Option<f64>,
+
+    /// Minimal ratios of the value of withdrawals to the fee overridden for tokens
+    #[envconfig(from = "TOKEN_MIN_VALUE_TO_FEE_RATIOS")]
+    pub token_min_value_to_fee_ratios: Option<TokenRatios>,
+
+    /// Rolling window in seconds the circuit breaker tracks finalized volumes over
+    #[envconfig(from = "CIRCUIT_BREAKER_WINDOW_SECS")]
+    pub circuit_breaker_window_secs: Option<u64>,
+
+    /// Number of windows before the current one the baseline of volumes is averaged over
+    #[envconfig(from = "CIRCUIT_BREAKER_BASELINE_WINDOWS")]
+    pub circuit_breaker_baseline_windows: Option<u32>,
+
+    /// Maximal volumes of tokens within the window in units of the tokens
+    #[envconfig(from = "CIRCUIT_BREAKER_TOKEN_LIMITS")]
+    pub circuit_breaker_token_limits: Option<TokenVolumeLimits>,
+
+    /// Maximal number of withdrawals of all tokens within the window
+    #[envconfig(from = "CIRCUIT_BREAKER_MAX_WITHDRAWALS")]
+    pub circuit_breaker_max_withdrawals: Option<u64>,
+
+    /// Maximal multiple of the baseline volumes and number of withdrawals within the window
+    #[envconfig(from = "CIRCUIT_BREAKER_MAX_DEVIATION")]
+    pub circuit_breaker_max_deviation: Option<f64>,
+
     #[envconfig(from = "ONLY_L1_RECIPIENTS")]
     pub only_l1_recipients: Option<AddrList>,
 
+    /// Address to serve the API reporting the health of the finalizer on
+    #[envconfig(from = "HEALTH_API_ADDRESS")]
+    pub health_api_address: Option<SocketAddr>,
+
+    /// Address to serve the API accepting finalization requests on
+    #[envconfig(from = "FINALIZATION_REQUESTS_API_ADDRESS")]
+    pub finalization_requests_api_address: Option<SocketAddr>,
+
+    /// Maximal number of finalization requests a client may make through the API within an hour
+    #[envconfig(from = "FINALIZATION_REQUESTS_PER_HOUR")]
+    pub finalization_requests_per_hour: Option<u64>,
+
+    /// IP addresses of the clients allowed to request finalization through the API,
+    /// only the loopback addresses if not specified
+    #[envconfig(from = "FINALIZATION_REQUESTS_ALLOWED_IPS")]
+    pub finalization_requests_allowed_ips: Option<IpList>,
+
+    /// Withdrawals to these L1 recipients are finalized before others
+    #[envconfig(from = "PRIORITY_L1_RECIPIENTS")]
+    pub priority_l1_recipients: Option<AddrList>,
+
     /// Only finalize these tokens specified by their L2 addresses
     #[envconfig(from = "ONLY_FINALIZE_THESE_TOKENS")]
     pub only_finalize_these_tokens: Option<AddrList>,
 }
 
+/// A non-empty comma-separated list of urls.
+#[derive(Debug, Clone)]
+pub struct UrlList(pub Vec<Url>);
+
+impl FromStr for UrlList {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            eyre::bail!("at least one url is required");
+        }
+
+        let urls = s
+            .split(',')
+            .map(|url| Url::parse(url.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(UrlList(urls))
+    }
+}
+
+/// A comma-separated list of IP addresses.
+#[derive(Debug, Clone)]
+pub struct IpList(pub Vec<IpAddr>);
+
+impl FromStr for IpList {
+    type Err = std::net::AddrParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let ips = s
+            .split(',')
+            .map(|ip| ip.trim().parse())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(IpList(ips))
+    }
+}
+
+/// A comma-separated list of private keys of accounts.
+#[derive(Debug, Clone)]
+pub struct WalletList(pub Vec<LocalWallet>);
+
+impl FromStr for WalletList {
+    type Err = WalletError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let wallets = s
+            .split(',')
+            .map(|key| key.trim().parse())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(WalletList(wallets))
+    }
+}
+
+/// An account to finalize withdrawals from whose key is not given in the configuration.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum AccountConfig {
+    /// A key in an encrypted JSON keystore.
+    Keystore {
+        keystore_path: PathBuf,
+        keystore_password_path: PathBuf,
+    },
+
+    /// A key held by a remote signer.
+    Remote {
+        remote_signer_url: Url,
+        address: Address,
+    },
+}
+
+impl AccountConfig {
+    /// The signer of the account.
+    pub fn signer(&self) -> eyre::Result<AccountSigner> {
+        match self {
+            AccountConfig::Keystore {
+                keystore_path,
+                keystore_password_path,
+            } => Ok(AccountSigner::from_keystore(
+                keystore_path,
+                keystore_password_path,
+            )?),
+            AccountConfig::Remote {
+                remote_signer_url,
+                address,
+            } => Ok(AccountSigner::Remote(RemoteSigner::new(
+                Http::new(remote_signer_url.clone()),
+                *address,
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AccountConfigs(pub Vec<AccountConfig>);
+
+impl FromStr for AccountConfigs {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq)]
 pub struct CustomTokenAddressMapping {
     pub l_1_addr: Address,
@@ -116,7 +445,308 @@ impl FromStr for CustomTokenAddressMappings {
     }
 }
 
+/// Configuration of a ZK chain to finalize withdrawals of.
+#[derive(Deserialize, Serialize, Debug, Clone, Eq, PartialEq)]
+pub struct ChainConfig {
+    /// L2 HTTP Endpoints
+    #[serde(deserialize_with = "non_empty_urls")]
+    pub api_web3_json_rpc_http_url: Vec<Url>,
+
+    /// L2 WS Endpoints
+    #[serde(deserialize_with = "non_empty_urls")]
+    pub api_web3_json_rpc_ws_url: Vec<Url>,
+
+    /// Main contract of the chain
+    pub diamond_proxy_addr: Address,
+
+    /// Address of the `L2ERC20Bridge` contract.
+    pub l2_erc20_bridge_addr: Address,
+
+    #[serde(default)]
+    pub start_from_l2_block: Option<u64>,
+
+    #[serde(default)]
+    pub custom_token_deployer_addresses: Option<Vec<Address>>,
+
+    #[serde(default)]
+    pub custom_token_addresses: Option<Vec<Address>>,
+
+    /// Only finalize these tokens specified by their L2 addresses
+    #[serde(default)]
+    pub only_finalize_these_tokens: Option<Vec<Address>>,
+
+    /// L1 address of the base token if it is not ETH
+    #[serde(default)]
+    pub base_token_l1_address: Option<Address>,
+
+    #[serde(default)]
+    pub base_token_decimals: Option<u32>,
+
+    /// Only finalize withdrawals of the base token of at least this amount
+    #[serde(default)]
+    pub finalization_threshold: Option<String>,
+
+    /// Minimal amounts of finalized withdrawals of tokens in units of the tokens
+    #[serde(default)]
+    pub token_finalization_thresholds: Option<HashMap<Address, String>>,
+}
+
+/// A JSON object mapping L1 or L2 addresses of tokens to amounts in units of the tokens.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TokenThresholds(pub HashMap<Address, String>);
+
+impl FromStr for TokenThresholds {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+/// A JSON object mapping L1 or L2 addresses of tokens to ratios.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TokenRatios(pub HashMap<Address, f64>);
+
+impl FromStr for TokenRatios {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+/// A JSON object mapping L1 addresses of tokens to volumes in units of the tokens.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TokenVolumeLimits(pub HashMap<Address, f64>);
+
+impl FromStr for TokenVolumeLimits {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+/// A JSON object mapping addresses of accounts to amounts in ether.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AccountBudgets(pub HashMap<Address, String>);
+
+impl FromStr for AccountBudgets {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+impl ChainConfig {
+    /// The base token of the chain, ETH unless configured otherwise.
+    pub fn base_token(&self) -> BaseToken {
+        match self.base_token_l1_address {
+            Some(l1_address) => BaseToken {
+                l1_address,
+                decimals: self.base_token_decimals.unwrap_or(18),
+            },
+            None => BaseToken::ETH,
+        }
+    }
+
+    /// Minimal amounts of finalized withdrawals of tokens.
+    pub fn token_thresholds(&self) -> eyre::Result<Vec<TokenThreshold>> {
+        parse_token_thresholds(self.token_finalization_thresholds.iter().flatten())
+    }
+}
+
+fn parse_token_thresholds<'a>(
+    thresholds: impl Iterator<Item = (&'a Address, &'a String)>,
+) -> eyre::Result<Vec<TokenThreshold>> {
+    thresholds
+        .map(|(token, amount)| {
+            let amount = BigDecimal::from_str(amount)
+                .map_err(|e| eyre::anyhow!("threshold {amount} of {token:?}: {e}"))?;
+
+            Ok(TokenThreshold {
+                token: *token,
+                amount,
+            })
+        })
+        .collect()
+}
+
+/// Deserialize a list of endpoints rejecting empty ones.
+fn non_empty_urls<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<Url>, D::Error> {
+    let urls = Vec::<Url>::deserialize(deserializer)?;
+
+    if urls.is_empty() {
+        return Err(serde::de::Error::invalid_length(0, &"at least one url"));
+    }
+
+    Ok(urls)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ChainConfigs(pub Vec<ChainConfig>);
+
+impl FromStr for ChainConfigs {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
 impl Config {
+    /// The signer of the finalizer account configured by exactly one of
+    /// a private key, a keystore or a remote signer.
+    pub fn account_signer(&self) -> eyre::Result<AccountSigner> {
+        match (
+            &self.account_private_key,
+            &self.account_keystore_path,
+            &self.remote_signer_url,
+        ) {
+            (Some(key), None, None) => Ok(AccountSigner::Local(key.parse()?)),
+            (None, Some(keystore_path), None) => {
+                let password_path =
+                    self.account_keystore_password_path
+                        .as_ref()
+                        .ok_or_else(|| {
+                            eyre::anyhow!(
+                                "keystore password path has to be configured with the keystore"
+                            )
+                        })?;
+
+                Ok(AccountSigner::from_keystore(keystore_path, password_path)?)
+            }
+            (None, None, Some(url)) => {
+                let address = self.account_address.ok_or_else(|| {
+                    eyre::anyhow!("account address has to be configured with the remote signer")
+                })?;
+
+                Ok(AccountSigner::Remote(RemoteSigner::new(
+                    Http::new(url.clone()),
+                    address,
+                )))
+            }
+            _ => Err(eyre::anyhow!(
+                "exactly one of account private key, keystore or remote signer has to be configured"
+            )),
+        }
+    }
+
+    /// The signers of the accounts to finalize withdrawals from in addition
+    /// to the finalizer account.
+    pub fn additional_account_signers(&self) -> eyre::Result<Vec<AccountSigner>> {
+        let mut signers: Vec<_> = self
+            .additional_account_private_keys
+            .iter()
+            .flat_map(|keys| keys.0.iter().cloned().map(AccountSigner::Local))
+            .collect();
+
+        for account in self
+            .additional_accounts
+            .iter()
+            .flat_map(|accounts| accounts.0.iter())
+        {
+            signers.push(account.signer()?);
+        }
+
+        Ok(signers)
+    }
+
+    /// Amounts of withdrawals of tokens finalized only once approved.
+    pub fn token_approval_thresholds(&self) -> eyre::Result<Vec<TokenThreshold>> {
+        parse_token_thresholds(
+            self.token_approval_thresholds
+                .iter()
+                .flat_map(|thresholds| thresholds.0.iter()),
+        )
+    }
+
+    /// The circuit breaker if its window is configured.
+    pub fn circuit_breaker(&self) -> Option<CircuitBreaker> {
+        let window = self.circuit_breaker_window_secs?;
+
+        let mut circuit_breaker = CircuitBreaker::new(
+            Duration::from_secs(window),
+            self.circuit_breaker_baseline_windows
+                .unwrap_or(DEFAULT_CIRCUIT_BREAKER_BASELINE_WINDOWS),
+        );
+
+        if let Some(ref limits) = self.circuit_breaker_token_limits {
+            circuit_breaker =
+                circuit_breaker.with_token_limits(limits.0.iter().map(|(t, l)| (*t, *l)).collect());
+        }
+
+        if let Some(max_withdrawals) = self.circuit_breaker_max_withdrawals {
+            circuit_breaker = circuit_breaker.with_max_withdrawals(max_withdrawals);
+        }
+
+        if let Some(max_deviation) = self.circuit_breaker_max_deviation {
+            circuit_breaker = circuit_breaker.with_max_deviation(max_deviation);
+        }
+
+        Some(circuit_breaker)
+    }
+
+    /// The chains to finalize withdrawals of, the main one goes first.
+    pub fn chains(&self) -> Vec<ChainConfig> {
+        let main_chain = ChainConfig {
+            api_web3_json_rpc_http_url: self.api_web3_json_rpc_http_url.0.clone(),
+            api_web3_json_rpc_ws_url: self.api_web3_json_rpc_ws_url.0.clone(),
+            diamond_proxy_addr: self.diamond_proxy_addr,
+            l2_erc20_bridge_addr: self.l2_erc20_bridge_addr,
+            start_from_l2_block: self.start_from_l2_block,
+            custom_token_deployer_addresses: self
+                .custom_token_deployer_addresses
+                .as_ref()
+                .map(|list| list.0.clone()),
+            custom_token_addresses: self.custom_token_addresses.as_ref().map(|l| l.0.clone()),
+            only_finalize_these_tokens: self
+                .only_finalize_these_tokens
+                .as_ref()
+                .map(|list| list.0.clone()),
+            base_token_l1_address: self.base_token_l1_address,
+            base_token_decimals: self.base_token_decimals,
+            finalization_threshold: self.eth_finalization_threshold.clone(),
+            token_finalization_thresholds: self
+                .token_finalization_thresholds
+                .as_ref()
+                .map(|t| t.0.clone()),
+        };
+
+        std::iter::once(main_chain)
+            .chain(
+                self.additional_chains
+                    .iter()
+                    .flat_map(|chains| chains.0.iter().cloned()),
+            )
+            .collect()
+    }
+
+    /// Limits of requests to each of L1 HTTP endpoints.
+    pub fn l1_rate_limits(&self) -> RateLimits {
+        RateLimits {
+            requests_per_second: self.eth_client_requests_per_second,
+            max_in_flight: self.eth_client_max_in_flight_requests,
+            retries: self
+                .rpc_rate_limit_retries
+                .unwrap_or(DEFAULT_RPC_RATE_LIMIT_RETRIES),
+        }
+    }
+
+    /// Limits of requests to each of L2 HTTP endpoints.
+    pub fn l2_rate_limits(&self) -> RateLimits {
+        RateLimits {
+            requests_per_second: self.api_web3_json_rpc_requests_per_second,
+            max_in_flight: self.api_web3_json_rpc_max_in_flight_requests,
+            retries: self
+                .rpc_rate_limit_retries
+                .unwrap_or(DEFAULT_RPC_RATE_LIMIT_RETRIES),
+        }
+    }
+
     /// Returns a mapping of tokens (L1, L2) addresses.
     pub fn token_mappings(&self) -> Vec<(Address, Address)> {
         self.custom_token_address_mappings
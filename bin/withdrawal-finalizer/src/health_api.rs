@@ -0,0 +1,143 @@
+//! HTTP API reporting the health of the finalizer.
+
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+
+use ethers::providers::Middleware;
+use eyre::Result;
+use finalizer::SignerPool;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde_json::json;
+use sqlx::PgPool;
+use tokio::sync::watch;
+
+struct HealthApi<S> {
+    pool: PgPool,
+    chain_ids: Vec<u64>,
+    signers: Arc<SignerPool<S>>,
+}
+
+/// Serve the API on `address`:
+///
+/// * `GET /health` reports the finalizer accounts that are out of funds, the pauses
+///   of finalization of each chain and the trips of its circuit breaker. The status
+///   is `503 Service Unavailable` while all of the accounts are out of funds.
+pub fn run_health_api<S: Middleware + 'static>(
+    address: SocketAddr,
+    pool: PgPool,
+    chain_ids: Vec<u64>,
+    signers: Arc<SignerPool<S>>,
+) -> Result<watch::Sender<()>> {
+    let (shutdown_sender, mut shutdown_receiver) = watch::channel(());
+
+    let api = Arc::new(HealthApi {
+        pool,
+        chain_ids,
+        signers,
+    });
+
+    let make_service = make_service_fn(move |_| {
+        let api = api.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let api = api.clone();
+                async move { Ok::<_, Infallible>(api.handle(request).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&address)?
+        .serve(make_service)
+        .with_graceful_shutdown(async move {
+            shutdown_receiver.changed().await.ok();
+        });
+
+    tracing::info!("serving health on {address}");
+    tokio::spawn(server);
+
+    Ok(shutdown_sender)
+}
+
+impl<S: Middleware> HealthApi<S> {
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let response = match (request.method(), request.uri().path()) {
+            (&Method::GET, "/health") => self.health().await,
+            _ => Ok(json_response(
+                StatusCode::NOT_FOUND,
+                json!({ "error": "not found" }),
+            )),
+        };
+
+        response.unwrap_or_else(|e| {
+            tracing::error!("failed to report health: {e}");
+            json_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({ "error": "internal error" }),
+            )
+        })
+    }
+
+    async fn health(&self) -> Result<Response<Body>> {
+        let mut chains = Vec::with_capacity(self.chain_ids.len());
+
+        for chain_id in self.chain_ids.iter().copied() {
+            let pauses = storage::active_finalization_pauses(&self.pool, Some(chain_id)).await?;
+            let trips = storage::circuit_breaker_trips(&self.pool, Some(chain_id)).await?;
+
+            // Finalization of some withdrawals may go on while others are paused.
+            let paused =
+                pauses.iter().any(|p| p.is_global()) || trips.iter().any(|t| t.l1_token.is_none());
+
+            chains.push(json!({
+                "chain_id": chain_id,
+                "paused": paused,
+                "pauses": pauses
+                    .iter()
+                    .map(|p| json!({
+                        "id": p.id,
+                        "token": p.token,
+                        "l1_recipient": p.l1_recipient,
+                        "reason": p.reason,
+                        "paused_by": p.paused_by,
+                        "paused_secs": p.paused_secs,
+                    }))
+                    .collect::<Vec<_>>(),
+                "circuit_breaker_trips": trips
+                    .iter()
+                    .map(|t| json!({
+                        "id": t.id,
+                        "l1_token": t.l1_token,
+                        "reason": t.reason,
+                        "tripped_secs": t.tripped_secs,
+                    }))
+                    .collect::<Vec<_>>(),
+            }));
+        }
+
+        let out_of_funds = !self.signers.is_funded();
+        let status = match out_of_funds {
+            true => StatusCode::SERVICE_UNAVAILABLE,
+            false => StatusCode::OK,
+        };
+
+        Ok(json_response(
+            status,
+            json!({
+                "out_of_funds": out_of_funds,
+                "unfunded_accounts": self.signers.unfunded_accounts(),
+                "chains": chains,
+            }),
+        ))
+    }
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("response is valid; qed")
+}
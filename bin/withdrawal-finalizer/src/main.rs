@@ -5,13 +5,12 @@
 
 //! A withdraw-finalizer
 
-use std::{str::FromStr, sync::Arc, time::Duration};
+use std::{collections::HashMap, num::NonZeroUsize, str::FromStr, sync::Arc, time::Duration};
 
 use envconfig::Envconfig;
 use ethers::{
     prelude::SignerMiddleware,
-    providers::{Http, JsonRpcClient, Middleware, Provider},
-    signers::LocalWallet,
+    providers::{JsonRpcClient, Middleware, Provider},
     types::U256,
 };
 use eyre::{anyhow, Result};
@@ -21,8 +20,20 @@ use sqlx::{
 };
 
 use chain_events::{BlockEvents, L2EventsListener};
-use client::{l1bridge::codegen::IL1Bridge, zksync_contract::codegen::IZkSync, ZksyncMiddleware};
-use config::Config;
+use client::{
+    cache::{CachingMiddleware, DEFAULT_CACHE_CAPACITY},
+    failover::FailoverClient,
+    http::HttpTransport,
+    l1bridge::codegen::IL1Bridge,
+    l1sharedbridge::codegen::IL1SharedBridge,
+    rate_limit::RateLimitedClient,
+    signer::AccountSigner,
+    zksync_contract::codegen::IZkSync,
+    FinalizationContracts, ZksyncMiddleware, ETH_TOKEN_ADDRESS,
+};
+use config::{ChainConfig, Config};
+use finalizer::{ProfitabilityPolicy, SignerPool};
+use sqlx::PgPool;
 use tokio::sync::watch;
 use vise_exporter::MetricsExporter;
 use watcher::Watcher;
@@ -30,10 +41,22 @@ use watcher::Watcher;
 use crate::metrics::MAIN_FINALIZER_METRICS;
 
 mod config;
+mod health_api;
 mod metrics;
+mod requests_api;
 
 const CHANNEL_CAPACITY: usize = 1024 * 16;
 
+type L1Client = Provider<FailoverClient<RateLimitedClient<HttpTransport>>>;
+
+type L2Client = CachingMiddleware<Provider<FailoverClient<RateLimitedClient<HttpTransport>>>>;
+
+/// L1 methods requested from a quorum of endpoints: reads of withdrawals finalization status.
+const L1_QUORUM_METHODS: &[&str] = &["eth_call"];
+
+/// L2 methods requested from a quorum of endpoints: withdrawal proofs.
+const L2_QUORUM_METHODS: &[&str] = &["zks_getL2ToL1LogProof"];
+
 fn run_vise_exporter() -> Result<watch::Sender<()>> {
     let (shutdown_sender, mut shutdown_receiver) = watch::channel(());
     let exporter = MetricsExporter::default().with_graceful_shutdown(async move {
@@ -49,6 +72,7 @@ async fn start_from_l1_block<M1, M2>(
     client_l1: Arc<M1>,
     client_l2: Arc<M2>,
     conn: &mut PgConnection,
+    chain_id: u64,
 ) -> Result<u64>
 where
     M1: Middleware,
@@ -56,7 +80,7 @@ where
     M2: Middleware,
     <M2 as Middleware>::Provider: JsonRpcClient,
 {
-    match storage::last_l1_block_seen(conn).await? {
+    match storage::last_l1_block_seen(conn, chain_id).await? {
         Some(b2) => Ok(b2),
         None => {
             tracing::info!(concat!(
@@ -93,18 +117,19 @@ where
 // Determine an L2 block to start processing withdrawals from.
 //
 // The priority is:
-// 1. Config variable `start_from_l2_block`. If not present:
+// 1. Chain config variable `start_from_l2_block`. If not present:
 // 2. The block of last seen withdrawal event decremented by one. If not present:
 // 3. Last finalized block on L2.
 async fn start_from_l2_block<M: Middleware>(
     client: Arc<M>,
     conn: &mut PgConnection,
-    config: &Config,
+    chain_id: u64,
+    chain: &ChainConfig,
 ) -> Result<u64> {
-    let res = match config.start_from_l2_block {
+    let res = match chain.start_from_l2_block {
         Some(l2_block) => l2_block,
         None => {
-            if let Some(block_number) = storage::last_l2_block_seen(conn).await? {
+            if let Some(block_number) = storage::last_l2_block_seen(conn, chain_id).await? {
                 // May have stored not the last withdrawal event in `block_number`
                 // so to be sure, re-start from the previous block.
                 block_number - 1
@@ -124,63 +149,67 @@ async fn start_from_l2_block<M: Middleware>(
     Ok(res)
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    color_eyre::install()?;
-
-    dotenvy::dotenv().ok();
-    let config = Config::init_from_env()?;
-
-    let sentry_guard = vlog::init();
-
-    if sentry_guard.is_some() {
-        tracing::info!(
-            "Starting Sentry url: {}, l1_network: {}, l2_network {}",
-            std::env::var("MISC_SENTRY_URL").unwrap(),
-            std::env::var("CHAIN_ETH_NETWORK").unwrap(),
-            std::env::var("CHAIN_ETH_ZKSYNC_NETWORK").unwrap(),
-        );
-    } else {
-        tracing::info!("No sentry url configured");
+fn l2_client(config: &Config, chain: &ChainConfig) -> Arc<L2Client> {
+    let mut failover_l2 = FailoverClient::from_urls(
+        "l2_http",
+        &chain.api_web3_json_rpc_http_url,
+        &config.l2_rate_limits(),
+    );
+    if let Some(quorum) = config.api_web3_json_rpc_quorum {
+        failover_l2 = failover_l2.with_quorum(quorum, L2_QUORUM_METHODS);
     }
 
-    client::add_predefined_token_addrs(config.token_mappings().as_ref()).await;
-    let stop_vise_exporter = run_vise_exporter()?;
-
-    // Successful reconnections do not reset the reconnection count trackers in the
-    // `ethers-rs`. In the logic of reconnections have to happen as long
-    // as the application exists; below code configures that number to
-    // be `usize::MAX` as such.
-    let provider_l1 = Provider::<Http>::try_from(config.eth_client_http_url.as_ref()).unwrap();
-    let client_l1 = Arc::new(provider_l1);
+    Arc::new(CachingMiddleware::new(
+        Provider::new(failover_l2),
+        config.l2_rpc_cache_capacity.unwrap_or(
+            NonZeroUsize::new(DEFAULT_CACHE_CAPACITY).expect("default capacity is not zero; qed"),
+        ),
+    ))
+}
 
-    let provider_l2 =
-        Provider::<Http>::try_from(config.api_web3_json_rpc_http_url.as_str()).unwrap();
+// Everything a chain pipeline shares with the pipelines of other chains.
+struct Shared {
+    config: Arc<Config>,
+    pgpool: PgPool,
+    client_l1: Arc<L1Client>,
+    client_l1_status: Arc<L1Client>,
+    signers: Arc<SignerPool<SignerMiddleware<Arc<L1Client>, AccountSigner>>>,
+}
 
-    let client_l2 = Arc::new(provider_l2);
+// Watch and finalize withdrawals of a single chain, returns once any of its components ends.
+async fn run_chain(
+    shared: Arc<Shared>,
+    chain: ChainConfig,
+    chain_id: u64,
+    client_l2: Arc<L2Client>,
+) -> Result<()> {
+    let config = &shared.config;
+    let pgpool = shared.pgpool.clone();
+    let base_token = chain.base_token();
+
+    let base_token_threshold = match chain.finalization_threshold {
+        Some(ref threshold) => {
+            Some(ethers::utils::parse_units(threshold, base_token.decimals)?.into())
+        }
+        None => None,
+    };
+    let token_thresholds = chain.token_thresholds()?;
 
-    let event_mux = BlockEvents::new(config.eth_client_ws_url.as_ref());
+    let event_mux = BlockEvents::new(&config.eth_client_ws_url.0);
     let (blocks_tx, blocks_rx) = tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
 
     let blocks_tx_wrapped = tokio_util::sync::PollSender::new(blocks_tx.clone());
     let blocks_rx = tokio_stream::wrappers::ReceiverStream::new(blocks_rx);
 
-    let options =
-        PgConnectOptions::from_str(config.database_url.as_str())?.disable_statement_logging();
-
-    let pgpool = PgPoolOptions::new()
-        .acquire_timeout(Duration::from_secs(2))
-        .connect_with(options)
-        .await?;
-
     let from_l2_block = start_from_l2_block(
         client_l2.clone(),
         &mut pgpool.acquire().await?.detach(),
-        &config,
+        chain_id,
+        &chain,
     )
     .await?;
 
-    tracing::info!("Starting from L2 block number {from_l2_block}");
+    tracing::info!("Starting chain {chain_id} from L2 block number {from_l2_block}");
 
     let (we_tx, we_rx) = tokio::sync::mpsc::channel(CHANNEL_CAPACITY);
 
@@ -188,48 +217,74 @@ async fn main() -> Result<()> {
     let we_rx = tokio_stream::wrappers::ReceiverStream::new(we_rx);
 
     let from_l1_block = start_from_l1_block(
-        client_l1.clone(),
+        shared.client_l1.clone(),
         client_l2.clone(),
         &mut pgpool.acquire().await?.detach(),
+        chain_id,
     )
     .await?;
 
-    tracing::info!("Starting from L1 block number {from_l1_block}");
+    tracing::info!("Starting chain {chain_id} from L1 block number {from_l1_block}");
 
-    let (mut tokens, last_token_seen_at_block) = storage::get_tokens(&pgpool.clone()).await?;
+    let (mut tokens, last_token_seen_at_block) = storage::get_tokens(&pgpool, chain_id).await?;
 
-    if let Some(ref custom_tokens) = config.custom_token_addresses {
-        tokens.extend_from_slice(custom_tokens.0.as_slice());
+    if let Some(ref custom_tokens) = chain.custom_token_addresses {
+        tokens.extend_from_slice(custom_tokens.as_slice());
     }
 
-    if let Some(ref custom_tokens) = config.custom_token_deployer_addresses {
-        tokens.extend_from_slice(custom_tokens.0.as_slice());
+    if let Some(ref custom_tokens) = chain.custom_token_deployer_addresses {
+        tokens.extend_from_slice(custom_tokens.as_slice());
     }
 
-    if let Some(only_finalize_these_tokens) = config.only_finalize_these_tokens {
-        tokens.retain(|token| only_finalize_these_tokens.0.contains(token));
+    // All tokens are indexed for changes of their policies to apply retroactively.
+    if let Some(ref only_finalize_these_tokens) = chain.only_finalize_these_tokens {
+        let mut allowed_tokens = only_finalize_these_tokens.clone();
+        if config.finalize_eth_token.unwrap_or(true) {
+            allowed_tokens.push(ETH_TOKEN_ADDRESS);
+        }
+
+        storage::sync_allowed_tokens(&pgpool, chain_id, &allowed_tokens).await?;
     }
 
-    tracing::info!("tokens {tokens:?}");
+    tracing::info!("chain {chain_id} tokens {tokens:?}");
 
     let l2_events = L2EventsListener::new(
-        config.api_web3_json_rpc_ws_url.as_str(),
-        config
+        &chain.api_web3_json_rpc_ws_url,
+        chain
             .custom_token_deployer_addresses
-            .map(|list| list.0)
-            .unwrap_or(vec![config.l2_erc20_bridge_addr]),
+            .clone()
+            .unwrap_or(vec![chain.l2_erc20_bridge_addr]),
         tokens.into_iter().collect(),
         config.finalize_eth_token.unwrap_or(true),
     );
 
-    let l1_bridge = IL1Bridge::new(config.l1_erc20_bridge_proxy_addr, client_l1.clone());
-
-    let zksync_contract = IZkSync::new(config.diamond_proxy_addr, client_l1.clone());
+    let contracts = match config.l1_shared_bridge_proxy_addr {
+        Some(addr) => FinalizationContracts::SharedBridge {
+            shared_bridge: IL1SharedBridge::new(addr, shared.client_l1_status.clone()),
+            chain_id,
+        },
+        None => FinalizationContracts::Legacy {
+            zksync_contract: IZkSync::new(
+                chain.diamond_proxy_addr,
+                shared.client_l1_status.clone(),
+            ),
+            l1_bridge: IL1Bridge::new(
+                config.l1_erc20_bridge_proxy_addr,
+                shared.client_l1_status.clone(),
+            ),
+        },
+    };
 
     // by default meter withdrawals
     let meter_withdrawals = config.enable_withdrawal_metering.unwrap_or(true);
 
-    let watcher = Watcher::new(client_l2.clone(), pgpool.clone(), meter_withdrawals);
+    let watcher = Watcher::new(
+        chain_id,
+        client_l2.clone(),
+        pgpool.clone(),
+        meter_withdrawals,
+        base_token,
+    );
 
     let withdrawal_events_handle = tokio::spawn(l2_events.run_with_reconnects(
         from_l2_block,
@@ -240,8 +295,8 @@ async fn main() -> Result<()> {
     let watcher_handle = tokio::spawn(watcher.run(blocks_rx, we_rx, from_l2_block));
 
     let block_events_handle = tokio::spawn(event_mux.run_with_reconnects(
-        config.diamond_proxy_addr,
-        config.l2_erc20_bridge_addr,
+        chain.diamond_proxy_addr,
+        chain.l2_erc20_bridge_addr,
         from_l1_block,
         blocks_tx_wrapped,
     ));
@@ -250,83 +305,320 @@ async fn main() -> Result<()> {
         let mut interval = tokio::time::interval(Duration::from_secs(5));
         loop {
             interval.tick().await;
-            MAIN_FINALIZER_METRICS
-                .watcher_l1_channel_capacity
+            MAIN_FINALIZER_METRICS.watcher_l1_channel_capacity[&chain_id]
                 .set(blocks_tx.capacity() as i64);
-            MAIN_FINALIZER_METRICS
-                .watcher_l2_channel_capacity
+            MAIN_FINALIZER_METRICS.watcher_l2_channel_capacity[&chain_id]
                 .set(we_tx.capacity() as i64);
         }
     });
 
-    let wallet = config.account_private_key.parse::<LocalWallet>()?;
-
-    let client_l1_with_signer = Arc::new(
-        SignerMiddleware::new_with_provider_chain(client_l1, wallet)
-            .await
-            .unwrap(),
-    );
-
-    let finalizer_account_address = client_l1_with_signer.address();
-
-    let contract = client::withdrawal_finalizer::codegen::WithdrawalFinalizer::new(
-        config.withdrawal_finalizer_addr,
-        client_l1_with_signer,
-    );
     let batch_finalization_gas_limit = U256::from_dec_str(&config.batch_finalization_gas_limit)?;
     let one_withdrawal_gas_limit = U256::from_dec_str(&config.one_withdrawal_gas_limit)?;
 
-    tracing::info!(
-        "finalization gas limits one: {}, batch: {}",
-        config.one_withdrawal_gas_limit,
-        config.batch_finalization_gas_limit,
-    );
-
-    let eth_finalization_threshold = match config.eth_finalization_threshold {
-        Some(eth_finalization_threshold) => {
-            Some(ethers::utils::parse_ether(eth_finalization_threshold)?)
-        }
-        None => None,
-    };
-    let finalizer = finalizer::Finalizer::new(
+    let mut finalizer = finalizer::Finalizer::new(
         pgpool.clone(),
+        chain_id,
         one_withdrawal_gas_limit,
         batch_finalization_gas_limit,
-        contract,
-        zksync_contract,
-        l1_bridge,
+        shared.signers.clone(),
+        contracts,
         config.tx_retry_timeout,
-        finalizer_account_address,
         meter_withdrawals,
-        eth_finalization_threshold,
-        config.only_l1_recipients.map(|v| v.0.into_iter().collect()),
+        base_token,
+        base_token_threshold,
+        token_thresholds.clone(),
+        config.only_l1_recipients.as_ref().map(|v| v.0.clone()),
     );
+
+    if let Some(age) = config.below_threshold_finalization_age_secs {
+        let max_gas_price = config
+            .below_threshold_max_gas_price_gwei
+            .map(|gwei| U256::from(gwei) * U256::exp10(9));
+
+        finalizer =
+            finalizer.with_below_threshold_finalization(Duration::from_secs(age), max_gas_price);
+    }
+
+    if let Some(gwei) = config.max_base_fee_gwei {
+        finalizer = finalizer.with_max_base_fee(U256::from(gwei) * U256::exp10(9));
+    }
+
+    finalizer = finalizer.with_finalization_deadline(
+        Duration::from_secs(
+            config
+                .finalization_deadline_secs
+                .unwrap_or(config::DEFAULT_FINALIZATION_DEADLINE_SECS),
+        ),
+        Duration::from_secs(
+            config
+                .finalization_deadline_margin_secs
+                .unwrap_or(config::DEFAULT_FINALIZATION_DEADLINE_MARGIN_SECS),
+        ),
+    );
+
+    if let Some(address) = config.withdrawal_finalizer_addr {
+        finalizer = finalizer.with_finalizer_contract(address);
+    }
+
+    if let Some(window) = config.batching_window_secs {
+        finalizer =
+            finalizer.with_batching_window(config.batch_target_size, Duration::from_secs(window));
+    }
+
+    if let Some(max_in_flight_txs) = config.max_in_flight_finalization_txs {
+        finalizer = finalizer.with_max_in_flight_txs(max_in_flight_txs);
+    }
+
+    if let Some(ref recipients) = config.priority_l1_recipients {
+        finalizer = finalizer.with_priority_l1_recipients(recipients.0.clone());
+    }
+
+    if config.eth_approval_threshold.is_some() || config.token_approval_thresholds.is_some() {
+        let base_token_threshold = match config.eth_approval_threshold {
+            Some(ref threshold) => {
+                Some(ethers::utils::parse_units(threshold, base_token.decimals)?.into())
+            }
+            None => None,
+        };
+
+        finalizer = finalizer
+            .with_approval_thresholds(base_token_threshold, config.token_approval_thresholds()?);
+    }
+
+    if let Some(circuit_breaker) = config.circuit_breaker() {
+        finalizer = finalizer.with_circuit_breaker(circuit_breaker);
+    }
+
+    if let Some(ref source) = config.token_prices_source {
+        finalizer = finalizer.with_profitability_policy(ProfitabilityPolicy::new(
+            source.clone(),
+            config
+                .min_value_to_fee_ratio
+                .unwrap_or(config::DEFAULT_MIN_VALUE_TO_FEE_RATIO),
+            config
+                .token_min_value_to_fee_ratios
+                .iter()
+                .flat_map(|ratios| ratios.0.iter().map(|(t, r)| (*t, *r)))
+                .collect(),
+        ));
+    }
+
     let finalizer_handle = tokio::spawn(finalizer.run(client_l2));
 
     let metrics_handle = tokio::spawn(metrics::meter_unfinalized_withdrawals(
         pgpool.clone(),
-        eth_finalization_threshold,
+        chain_id,
+        base_token_threshold,
+        token_thresholds,
     ));
 
     tokio::select! {
         r = block_events_handle => {
-            tracing::error!("Block Events stream ended with {r:?}");
+            tracing::error!("Block Events stream of chain {chain_id} ended with {r:?}");
         }
         r = withdrawal_events_handle => {
-            tracing::error!("Withdrawals Events stream ended with {r:?}");
+            tracing::error!("Withdrawals Events stream of chain {chain_id} ended with {r:?}");
         }
         r = watcher_handle => {
-            tracing::error!("Finalizer main loop ended with {r:?}");
+            tracing::error!("Finalizer main loop of chain {chain_id} ended with {r:?}");
         }
         r = finalizer_handle => {
-            tracing::error!("Finalizer ended with {r:?}");
+            tracing::error!("Finalizer of chain {chain_id} ended with {r:?}");
         }
         _ = metrics_handle => {
-            tracing::error!("Metrics loop has ended");
+            tracing::error!("Metrics loop of chain {chain_id} has ended");
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    color_eyre::install()?;
+
+    dotenvy::dotenv().ok();
+    let config = Config::init_from_env()?;
+
+    let sentry_guard = vlog::init();
+
+    if sentry_guard.is_some() {
+        tracing::info!(
+            "Starting Sentry url: {}, l1_network: {}, l2_network {}",
+            std::env::var("MISC_SENTRY_URL").unwrap(),
+            std::env::var("CHAIN_ETH_NETWORK").unwrap(),
+            std::env::var("CHAIN_ETH_ZKSYNC_NETWORK").unwrap(),
+        );
+    } else {
+        tracing::info!("No sentry url configured");
+    }
+
+    let chains = config.chains();
+    if config.l1_shared_bridge_proxy_addr.is_none() {
+        if chains.len() > 1 {
+            return Err(anyhow!(
+                "finalizing several chains requires CONTRACTS_L1_SHARED_BRIDGE_PROXY_ADDR"
+            ));
+        }
+
+        if !chains[0].base_token().is_eth() {
+            return Err(anyhow!(
+                "finalizing withdrawals of a custom base token requires CONTRACTS_L1_SHARED_BRIDGE_PROXY_ADDR"
+            ));
         }
     }
 
+    client::add_predefined_token_addrs(config.token_mappings().as_ref()).await;
+    let stop_vise_exporter = run_vise_exporter()?;
+
+    // Successful reconnections do not reset the reconnection count trackers in the
+    // `ethers-rs`. In the logic of reconnections have to happen as long
+    // as the application exists; below code configures that number to
+    // be `usize::MAX` as such.
+    let failover_l1 = FailoverClient::from_urls(
+        "l1_http",
+        &config.eth_client_http_url.0,
+        &config.l1_rate_limits(),
+    );
+
+    // Finalization status of withdrawals is read through a separate client
+    // sharing the health of the same endpoints to optionally require a quorum.
+    let mut failover_l1_status = failover_l1.clone();
+    if let Some(quorum) = config.eth_client_quorum {
+        failover_l1_status = failover_l1_status.with_quorum(quorum, L1_QUORUM_METHODS);
+    }
+
+    let client_l1 = Arc::new(Provider::new(failover_l1));
+    let client_l1_status = Arc::new(Provider::new(failover_l1_status));
+
+    let mut chain_clients = Vec::with_capacity(chains.len());
+    for chain in chains {
+        let client_l2 = l2_client(&config, &chain);
+        let chain_id = client_l2
+            .get_chainid()
+            .await
+            .map_err(|e| anyhow!("{e}"))?
+            .as_u64();
+
+        chain_clients.push((chain, chain_id, client_l2));
+    }
+
+    let options =
+        PgConnectOptions::from_str(config.database_url.as_str())?.disable_statement_logging();
+
+    let pgpool = PgPoolOptions::new()
+        .acquire_timeout(Duration::from_secs(2))
+        .connect_with(options)
+        .await?;
+
+    // Rows stored before chains were told apart belong to the main chain,
+    // which `Config::chains` always puts first.
+    let (_, primary_chain_id, _) = &chain_clients[0];
+    storage::set_chain_id_of_legacy_rows(&pgpool, *primary_chain_id).await?;
+
+    let mut wallets = vec![config.account_signer()?];
+    wallets.extend(config.additional_account_signers()?);
+
+    let mut signers = Vec::with_capacity(wallets.len());
+    for wallet in wallets {
+        let client_l1_with_signer = Arc::new(
+            SignerMiddleware::new_with_provider_chain(client_l1.clone(), wallet)
+                .await
+                .unwrap(),
+        );
+
+        let address = client_l1_with_signer.address();
+        tracing::info!("finalizing withdrawals from {address:?}");
+
+        signers.push((address, client_l1_with_signer));
+    }
+
+    let min_balance = match config.account_min_balance_eth {
+        Some(ref eth) => ethers::utils::parse_ether(eth)?,
+        None => U256::zero(),
+    };
+
+    let daily_gas_budget = config
+        .daily_gas_budget_eth
+        .as_ref()
+        .map(ethers::utils::parse_ether)
+        .transpose()?;
+
+    let account_daily_gas_budgets = config
+        .account_daily_gas_budgets_eth
+        .iter()
+        .flat_map(|b| b.0.iter())
+        .map(|(address, eth)| Ok((*address, ethers::utils::parse_ether(eth)?)))
+        .collect::<eyre::Result<HashMap<_, _>>>()?;
+
+    tracing::info!(
+        "finalization gas limits one: {}, batch: {}",
+        config.one_withdrawal_gas_limit,
+        config.batch_finalization_gas_limit,
+    );
+
+    let shared = Arc::new(Shared {
+        config: Arc::new(config),
+        pgpool,
+        client_l1,
+        client_l1_status,
+        // All chains are finalized from the same accounts.
+        signers: Arc::new(
+            SignerPool::new(signers, min_balance)
+                .with_daily_budgets(daily_gas_budget, account_daily_gas_budgets),
+        ),
+    });
+
+    let stop_requests_api = match shared.config.finalization_requests_api_address {
+        Some(address) => Some(requests_api::run_requests_api(
+            address,
+            shared.pgpool.clone(),
+            chain_clients
+                .iter()
+                .map(|(_, chain_id, _)| *chain_id)
+                .collect(),
+            shared
+                .config
+                .finalization_requests_per_hour
+                .unwrap_or(config::DEFAULT_FINALIZATION_REQUESTS_PER_HOUR),
+            shared
+                .config
+                .finalization_requests_allowed_ips
+                .clone()
+                .map(|ips| ips.0),
+        )?),
+        None => None,
+    };
+
+    let stop_health_api = match shared.config.health_api_address {
+        Some(address) => Some(health_api::run_health_api(
+            address,
+            shared.pgpool.clone(),
+            chain_clients
+                .iter()
+                .map(|(_, chain_id, _)| *chain_id)
+                .collect(),
+            shared.signers.clone(),
+        )?),
+        None => None,
+    };
+
+    let chain_handles = chain_clients
+        .into_iter()
+        .map(|(chain, chain_id, client_l2)| {
+            tokio::spawn(run_chain(shared.clone(), chain, chain_id, client_l2))
+        });
+
+    let (r, _, _) = futures::future::select_all(chain_handles).await;
+    tracing::error!("Chain pipeline ended with {r:?}");
+
     stop_vise_exporter.send_replace(());
+    if let Some(stop_requests_api) = stop_requests_api {
+        stop_requests_api.send_replace(());
+    }
+    if let Some(stop_health_api) = stop_health_api {
+        stop_health_api.send_replace(());
+    }
 
     Ok(())
 }
@@ -2,54 +2,112 @@
 
 #![allow(unexpected_cfgs)]
 
-use std::time::Duration;
+use std::{collections::HashSet, time::Duration};
 
 use ethers::types::U256;
 use sqlx::PgPool;
-use vise::{Gauge, Metrics};
+use storage::TokenThreshold;
+use vise::{Gauge, LabeledFamily, Metrics};
 
 const METRICS_REFRESH_PERIOD: Duration = Duration::from_secs(15);
 
+const TOKEN_LABELS: [&str; 2] = ["chain_id", "token"];
+type TokenLabels = (u64, String);
+
 /// Main finalizer binary metrics
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "withdrawal_finalizer")]
 pub(super) struct FinalizerMainMetrics {
     /// Capacity of the channel sending L1 events.
-    pub watcher_l1_channel_capacity: Gauge,
+    #[metrics(labels = ["chain_id"])]
+    pub watcher_l1_channel_capacity: LabeledFamily<u64, Gauge>,
 
     /// Capacity of the channel sending L2 events.
-    pub watcher_l2_channel_capacity: Gauge,
+    #[metrics(labels = ["chain_id"])]
+    pub watcher_l2_channel_capacity: LabeledFamily<u64, Gauge>,
 
     /// The withdrawals that were not finalized but are executed
-    pub executed_eth_withdrawals_not_finalized: Gauge,
+    #[metrics(labels = ["chain_id"])]
+    pub executed_eth_withdrawals_not_finalized: LabeledFamily<u64, Gauge>,
 
     /// The withdrawals that
-    pub unexecuted_eth_withdrawals_below_current_threshold: Gauge,
+    #[metrics(labels = ["chain_id"])]
+    pub unexecuted_eth_withdrawals_below_current_threshold: LabeledFamily<u64, Gauge>,
+
+    /// The withdrawals of tokens with a threshold that are executed but not finalized
+    #[metrics(labels = TOKEN_LABELS)]
+    pub executed_token_withdrawals_not_finalized: LabeledFamily<TokenLabels, Gauge, 2>,
+
+    /// The withdrawals of tokens with a threshold that are not executed yet
+    #[metrics(labels = TOKEN_LABELS)]
+    pub unexecuted_token_withdrawals_above_threshold: LabeledFamily<TokenLabels, Gauge, 2>,
 }
 
 #[vise::register]
 pub(super) static MAIN_FINALIZER_METRICS: vise::Global<FinalizerMainMetrics> = vise::Global::new();
 
-pub async fn meter_unfinalized_withdrawals(pool: PgPool, eth_threshold: Option<U256>) {
+pub async fn meter_unfinalized_withdrawals(
+    pool: PgPool,
+    chain_id: u64,
+    base_token_threshold: Option<U256>,
+    token_thresholds: Vec<TokenThreshold>,
+) {
+    // Tokens metered in the previous iteration.
+    let mut metered_tokens = HashSet::new();
+
     loop {
         tokio::time::sleep(METRICS_REFRESH_PERIOD).await;
 
         let Ok(executed_not_finalized) =
-            storage::get_executed_and_not_finalized_withdrawals_count(&pool).await
+            storage::get_executed_and_not_finalized_withdrawals_count(&pool, chain_id).await
         else {
             continue;
         };
-        let Ok(unexecuted) = storage::get_unexecuted_withdrawals_count(&pool, eth_threshold).await
+        let Ok(unexecuted) =
+            storage::get_unexecuted_withdrawals_count(&pool, chain_id, base_token_threshold).await
         else {
             continue;
         };
 
-        MAIN_FINALIZER_METRICS
-            .executed_eth_withdrawals_not_finalized
+        MAIN_FINALIZER_METRICS.executed_eth_withdrawals_not_finalized[&chain_id]
             .set(executed_not_finalized);
 
-        MAIN_FINALIZER_METRICS
-            .unexecuted_eth_withdrawals_below_current_threshold
+        MAIN_FINALIZER_METRICS.unexecuted_eth_withdrawals_below_current_threshold[&chain_id]
             .set(unexecuted);
+
+        if token_thresholds.is_empty() {
+            continue;
+        }
+
+        let Ok(per_token) = storage::get_unfinalized_withdrawals_count_per_token(
+            &pool,
+            chain_id,
+            &token_thresholds,
+        )
+        .await
+        else {
+            continue;
+        };
+
+        let mut tokens = HashSet::new();
+
+        for count in per_token {
+            let labels = (chain_id, format!("{:?}", count.token));
+
+            MAIN_FINALIZER_METRICS.executed_token_withdrawals_not_finalized[&labels]
+                .set(count.executed);
+            MAIN_FINALIZER_METRICS.unexecuted_token_withdrawals_above_threshold[&labels]
+                .set(count.unexecuted);
+
+            tokens.insert(labels);
+        }
+
+        // Tokens without unfinalized withdrawals are missing from the counts.
+        for labels in metered_tokens.difference(&tokens) {
+            MAIN_FINALIZER_METRICS.executed_token_withdrawals_not_finalized[labels].set(0);
+            MAIN_FINALIZER_METRICS.unexecuted_token_withdrawals_above_threshold[labels].set(0);
+        }
+
+        metered_tokens = tokens;
     }
 }
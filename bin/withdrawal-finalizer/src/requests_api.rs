@@ -0,0 +1,214 @@
+//! HTTP API accepting requests to finalize withdrawals.
+
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use ethers::types::H256;
+use eyre::Result;
+use hyper::{
+    body::HttpBody,
+    server::conn::AddrStream,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgPool;
+use storage::{FinalizationRequest, FinalizationRequestOutcome};
+use tokio::sync::watch;
+
+/// Maximal size in bytes of the body of a request.
+const MAX_BODY_SIZE: usize = 1024;
+
+#[derive(Debug, Deserialize)]
+struct RequestBody {
+    chain_id: u64,
+    tx_hash: H256,
+}
+
+struct RequestsApi {
+    pool: PgPool,
+    chain_ids: Vec<u64>,
+    max_requests_per_hour: u64,
+    allowed_clients: Option<Vec<IpAddr>>,
+}
+
+/// Serve the API on `address`:
+///
+/// * `POST /finalization_requests` with a `{"chain_id": 324, "tx_hash": "0x..."}` body
+///   requests finalization of the withdrawals of an L2 transaction,
+/// * `GET /finalization_requests/<chain_id>/<tx_hash>` reports the status of the request.
+///
+/// Clients are told apart by their IP addresses for rate limiting. Only the clients
+/// with `allowed_clients` addresses, or loopback ones if `None`, may request finalization.
+pub fn run_requests_api(
+    address: SocketAddr,
+    pool: PgPool,
+    chain_ids: Vec<u64>,
+    max_requests_per_hour: u64,
+    allowed_clients: Option<Vec<IpAddr>>,
+) -> Result<watch::Sender<()>> {
+    let (shutdown_sender, mut shutdown_receiver) = watch::channel(());
+
+    let api = Arc::new(RequestsApi {
+        pool,
+        chain_ids,
+        max_requests_per_hour,
+        allowed_clients,
+    });
+
+    let make_service = make_service_fn(move |conn: &AddrStream| {
+        let api = api.clone();
+        let client = conn.remote_addr().ip();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let api = api.clone();
+                async move { Ok::<_, Infallible>(api.handle(request, client).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&address)?
+        .serve(make_service)
+        .with_graceful_shutdown(async move {
+            shutdown_receiver.changed().await.ok();
+        });
+
+    tracing::info!("serving finalization requests on {address}");
+    tokio::spawn(server);
+
+    Ok(shutdown_sender)
+}
+
+impl RequestsApi {
+    async fn handle(&self, request: Request<Body>, client: IpAddr) -> Response<Body> {
+        let method = request.method().clone();
+        let path = request.uri().path().to_owned();
+        let path: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        let response = match (method, path.as_slice()) {
+            (Method::POST, ["finalization_requests"]) => self.request(request, client).await,
+            (Method::GET, ["finalization_requests", chain_id, tx_hash]) => {
+                match (chain_id.parse(), tx_hash.parse()) {
+                    (Ok(chain_id), Ok(tx_hash)) => self.status(chain_id, tx_hash).await,
+                    _ => Ok(error(
+                        StatusCode::BAD_REQUEST,
+                        "invalid chain id or tx hash",
+                    )),
+                }
+            }
+            _ => Ok(error(StatusCode::NOT_FOUND, "not found")),
+        };
+
+        response.unwrap_or_else(|e| {
+            tracing::error!("failed to handle a finalization request: {e}");
+            error(StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+        })
+    }
+
+    async fn request(&self, request: Request<Body>, client: IpAddr) -> Result<Response<Body>> {
+        if !self.is_allowed(client) {
+            tracing::warn!("finalization request from {client} is not allowed");
+            return Ok(error(StatusCode::FORBIDDEN, "forbidden"));
+        }
+
+        // Bodies of unknown size such as chunked ones are read up to the limit.
+        let mut chunks = request.into_body();
+        let mut body = Vec::new();
+
+        while let Some(chunk) = chunks.data().await {
+            let chunk = chunk?;
+
+            if body.len() + chunk.len() > MAX_BODY_SIZE {
+                return Ok(error(StatusCode::PAYLOAD_TOO_LARGE, "request is too large"));
+            }
+
+            body.extend_from_slice(&chunk);
+        }
+
+        let Ok(body) = serde_json::from_slice::<RequestBody>(&body) else {
+            return Ok(error(StatusCode::BAD_REQUEST, "invalid request"));
+        };
+
+        if !self.chain_ids.contains(&body.chain_id) {
+            return Ok(error(StatusCode::NOT_FOUND, "unknown chain"));
+        }
+
+        let outcome = storage::request_finalization(
+            &self.pool,
+            body.chain_id,
+            body.tx_hash,
+            &client.to_string(),
+            Some(self.max_requests_per_hour),
+        )
+        .await?;
+
+        let status = match outcome {
+            FinalizationRequestOutcome::Added(_) => StatusCode::CREATED,
+            FinalizationRequestOutcome::Duplicate(_) => StatusCode::OK,
+            FinalizationRequestOutcome::RateLimited => {
+                return Ok(error(StatusCode::TOO_MANY_REQUESTS, "too many requests"));
+            }
+        };
+
+        tracing::info!(
+            "finalization of {:?} of chain {} requested by {client}: {outcome:?}",
+            body.tx_hash,
+            body.chain_id
+        );
+
+        self.status(body.chain_id, body.tx_hash)
+            .await
+            .map(|mut response| {
+                *response.status_mut() = status;
+                response
+            })
+    }
+
+    fn is_allowed(&self, client: IpAddr) -> bool {
+        // IPv4 clients of dual-stack sockets are reported as mapped IPv6 addresses.
+        let client = client.to_canonical();
+
+        match self.allowed_clients.as_ref() {
+            Some(allowed) => allowed.contains(&client),
+            None => client.is_loopback(),
+        }
+    }
+
+    async fn status(&self, chain_id: u64, tx_hash: H256) -> Result<Response<Body>> {
+        let requests =
+            storage::finalization_requests(&self.pool, Some(chain_id), Some(tx_hash)).await?;
+
+        let Some(request) = requests.first() else {
+            return Ok(error(StatusCode::NOT_FOUND, "no such request"));
+        };
+
+        Ok(json_response(StatusCode::OK, request_json(request)))
+    }
+}
+
+fn request_json(request: &FinalizationRequest) -> serde_json::Value {
+    json!({
+        "id": request.id,
+        "chain_id": request.chain_id,
+        "tx_hash": request.tx_hash,
+        "status": request.status.to_string(),
+        "error": request.error,
+    })
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    json_response(status, json!({ "error": message }))
+}
+
+fn json_response(status: StatusCode, body: serde_json::Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("response is valid; qed")
+}
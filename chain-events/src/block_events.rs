@@ -10,6 +10,7 @@ use ethers::{
 use futures::{Sink, SinkExt, StreamExt};
 
 use client::{
+    failover::EndpointsHealth,
     zksync_contract::codegen::{BlockCommitFilter, BlockExecutionFilter, BlocksVerificationFilter},
     BlockEvent,
 };
@@ -32,7 +33,8 @@ enum L1Events {
 // in the async context.
 /// Listener of block events on L1.
 pub struct BlockEvents {
-    url: String,
+    urls: Vec<String>,
+    endpoints: EndpointsHealth,
 }
 
 impl BlockEvents {
@@ -40,22 +42,27 @@ impl BlockEvents {
     ///
     /// # Arguments
     ///
-    /// * `middleware`: The middleware to perform requests with.
-    pub fn new(url: &str) -> BlockEvents {
+    /// * `urls`: Urls of the L1 WS endpoints to fail over between.
+    pub fn new<U: AsRef<str>>(urls: &[U]) -> BlockEvents {
         Self {
-            url: url.to_string(),
+            urls: urls.iter().map(|url| url.as_ref().to_string()).collect(),
+            endpoints: EndpointsHealth::new("l1_ws", urls),
         }
     }
 
-    async fn connect(&self) -> Option<Provider<Ws>> {
-        match Provider::<Ws>::connect_with_reconnects(&self.url, 0).await {
+    // Connect to the currently active endpoint, returns its index along with the provider.
+    async fn connect(&self) -> Option<(usize, Provider<Ws>)> {
+        let endpoint = self.endpoints.active();
+
+        match Provider::<Ws>::connect_with_reconnects(&self.urls[endpoint], 0).await {
             Ok(p) => {
                 CHAIN_EVENTS_METRICS.successful_l1_reconnects.inc();
-                Some(p)
+                Some((endpoint, p))
             }
             Err(e) => {
                 tracing::warn!("Block events stream reconnect attempt failed: {e}");
                 CHAIN_EVENTS_METRICS.l1_reconnects_on_error.inc();
+                self.endpoints.record_failure(endpoint);
                 None
             }
         }
@@ -81,7 +88,7 @@ impl BlockEvents {
         let mut from_block: BlockNumber = from_block.into();
 
         loop {
-            let Some(provider_l1) = self.connect().await else {
+            let Some((endpoint, provider_l1)) = self.connect().await else {
                 tokio::time::sleep(RECONNECT_BACKOFF).await;
                 continue;
             };
@@ -102,6 +109,10 @@ impl BlockEvents {
                 }
                 Ok(block) => from_block = block,
             }
+
+            // The subscription never ends by itself, so the endpoint has
+            // either errored or dropped the connection.
+            self.endpoints.record_failure(endpoint);
         }
     }
 }
@@ -5,6 +5,7 @@ use futures::{Sink, SinkExt, StreamExt};
 use client::{
     contracts_deployer::codegen::ContractDeployedFilter,
     ethtoken::codegen::WithdrawalFilter,
+    failover::EndpointsHealth,
     l2standard_token::codegen::{
         BridgeBurnFilter, BridgeInitializationFilter, BridgeInitializeFilter,
     },
@@ -29,7 +30,8 @@ struct NewTokenAdded;
 
 /// A convenience multiplexer for withdrawal-related events.
 pub struct L2EventsListener {
-    url: String,
+    urls: Vec<String>,
+    endpoints: EndpointsHealth,
     token_deployer_addrs: Vec<Address>,
     tokens: HashSet<Address>,
 }
@@ -59,9 +61,9 @@ impl L2EventsListener {
     ///
     /// # Arguments
     ///
-    /// * `middleware`: The middleware to perform requests with.
-    pub fn new(
-        url: &str,
+    /// * `urls`: Urls of the L2 WS endpoints to fail over between.
+    pub fn new<U: AsRef<str>>(
+        urls: &[U],
         token_deployer_addrs: Vec<Address>,
         mut tokens: HashSet<Address>,
         finalize_eth_token: bool,
@@ -73,21 +75,26 @@ impl L2EventsListener {
         tokens.insert(DEPLOYER_ADDRESS);
 
         Self {
-            url: url.to_string(),
+            urls: urls.iter().map(|url| url.as_ref().to_string()).collect(),
+            endpoints: EndpointsHealth::new("l2_ws", urls),
             token_deployer_addrs,
             tokens,
         }
     }
 
-    async fn connect(&self) -> Option<Provider<Ws>> {
-        match Provider::<Ws>::connect_with_reconnects(&self.url, 0).await {
+    // Connect to the currently active endpoint, returns its index along with the provider.
+    async fn connect(&self) -> Option<(usize, Provider<Ws>)> {
+        let endpoint = self.endpoints.active();
+
+        match Provider::<Ws>::connect_with_reconnects(&self.urls[endpoint], 0).await {
             Ok(p) => {
                 CHAIN_EVENTS_METRICS.successful_l2_reconnects.inc();
-                Some(p)
+                Some((endpoint, p))
             }
             Err(e) => {
                 tracing::warn!("Withdrawal events stream reconnect attempt failed: {e}");
                 CHAIN_EVENTS_METRICS.reconnects_on_error.inc();
+                self.endpoints.record_failure(endpoint);
                 None
             }
         }
@@ -237,7 +244,7 @@ impl L2EventsListener {
         let mut from_block: BlockNumber = from_block.into();
         let mut last_seen_l2_token_block: BlockNumber = last_seen_l2_token_block.into();
         loop {
-            let Some(provider_l1) = self.connect().await else {
+            let Some((endpoint, provider_l1)) = self.connect().await else {
                 tokio::time::sleep(RECONNECT_BACKOFF).await;
                 continue;
             };
@@ -278,11 +285,13 @@ impl L2EventsListener {
                                 );
                             }
                         }
-                        _ => (),
+                        RunResult::OtherError => self.endpoints.record_failure(endpoint),
+                        RunResult::NewTokenAdded => (),
                     }
                 }
                 Err(e) => {
                     tracing::warn!("Withdrawal events worker failed with {e}");
+                    self.endpoints.record_failure(endpoint);
                 }
             }
 
@@ -303,6 +312,7 @@ impl L2EventsListener {
 enum RunResult {
     PaginationTooLarge,
     AttemptPaginationIncrease,
+    NewTokenAdded,
     OtherError,
 }
 
@@ -432,7 +442,7 @@ impl L2EventsListener {
                     .await
                 {
                     Ok(Some(_new_token_added)) => {
-                        break;
+                        return Ok((last_seen_block, RunResult::NewTokenAdded));
                     }
                     Err(e) => {
                         tracing::warn!("Stopping event loop with an error {e}");
@@ -0,0 +1,324 @@
+//! Caching of immutable L2 data in front of a [`ZksyncMiddleware`].
+
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use ethers::{
+    abi::Token,
+    providers::{Middleware, MiddlewareError},
+    types::{H256, U64},
+};
+use futures::Future;
+use lru::LruCache;
+
+use crate::{
+    metrics::CLIENT_METRICS,
+    zksync_types::{BlockDetails, L2ToL1LogProof, TransactionReceipt as ZksyncTransactionReceipt},
+    Result, ZksyncMiddleware,
+};
+
+/// Default number of entries kept in each of the caches.
+pub const DEFAULT_CACHE_CAPACITY: usize = 4096;
+
+/// A bounded LRU cache that coalesces concurrent fetches of the same key.
+struct Cache<K, V> {
+    name: &'static str,
+    entries: Mutex<LruCache<K, V>>,
+    in_flight: Mutex<HashMap<K, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl<K, V> Debug for Cache<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cache").field("name", &self.name).finish()
+    }
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+{
+    fn new(name: &'static str, capacity: NonZeroUsize) -> Self {
+        Self {
+            name,
+            entries: Mutex::new(LruCache::new(capacity)),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn lookup(&self, key: &K) -> Option<V> {
+        self.entries
+            .lock()
+            .expect("cache lock is never poisoned; qed")
+            .get(key)
+            .cloned()
+    }
+
+    /// Get a value from the cache or fetch it, storing the fetched value
+    /// only if it is final as decided by `is_final`.
+    ///
+    /// Concurrent calls with the same key wait for the first of them to
+    /// finish and use its result if it has been cached.
+    async fn get_or_fetch<F, Fut>(&self, key: K, fetch: F, is_final: fn(&V) -> bool) -> Result<V>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        if let Some(value) = self.lookup(&key) {
+            CLIENT_METRICS.cache_hits[&self.name].inc();
+            return Ok(value);
+        }
+
+        let fetch_lock = self
+            .in_flight
+            .lock()
+            .expect("cache lock is never poisoned; qed")
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        let res = {
+            let _guard = fetch_lock.lock().await;
+
+            // The value may have been fetched while waiting for the lock.
+            if let Some(value) = self.lookup(&key) {
+                CLIENT_METRICS.cache_hits[&self.name].inc();
+                Ok(value)
+            } else {
+                CLIENT_METRICS.cache_misses[&self.name].inc();
+                let res = fetch().await;
+
+                if let Ok(ref value) = res {
+                    if is_final(value) {
+                        self.entries
+                            .lock()
+                            .expect("cache lock is never poisoned; qed")
+                            .put(key.clone(), value.clone());
+                    }
+                }
+
+                res
+            }
+        };
+
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .expect("cache lock is never poisoned; qed");
+
+        // The last of the concurrent callers cleans up the lock.
+        if Arc::strong_count(&fetch_lock) == 2 {
+            in_flight.remove(&key);
+        }
+
+        res
+    }
+}
+
+/// An error of the [`CachingMiddleware`].
+#[derive(Debug, thiserror::Error)]
+pub enum CachingMiddlewareError<M: Middleware> {
+    /// An error of the inner middleware.
+    #[error("{0}")]
+    MiddlewareError(M::Error),
+}
+
+impl<M: Middleware> MiddlewareError for CachingMiddlewareError<M> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        CachingMiddlewareError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            CachingMiddlewareError::MiddlewareError(e) => Some(e),
+        }
+    }
+}
+
+/// A [`ZksyncMiddleware`] that caches transaction receipts and other data
+/// that does not change once final and coalesces concurrent requests of it.
+///
+/// Receipts are cached once the transaction is included into an L1 batch,
+/// block details once the block is executed on L1.
+#[derive(Debug)]
+pub struct CachingMiddleware<M> {
+    inner: M,
+    receipts: Cache<H256, ZksyncTransactionReceipt>,
+    l1_batch_block_ranges: Cache<u32, Option<(U64, U64)>>,
+    block_details: Cache<u32, Option<BlockDetails>>,
+    log_proofs: Cache<(H256, Option<u64>), Option<L2ToL1LogProof>>,
+}
+
+impl<M> CachingMiddleware<M> {
+    /// Create a new [`CachingMiddleware`].
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`: The middleware to request data with
+    /// * `capacity`: Number of entries kept in each of the caches
+    pub fn new(inner: M, capacity: NonZeroUsize) -> Self {
+        Self {
+            inner,
+            receipts: Cache::new("receipts", capacity),
+            l1_batch_block_ranges: Cache::new("l1_batch_block_ranges", capacity),
+            block_details: Cache::new("block_details", capacity),
+            log_proofs: Cache::new("log_proofs", capacity),
+        }
+    }
+}
+
+#[async_trait]
+impl<M: Middleware> Middleware for CachingMiddleware<M> {
+    type Error = CachingMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<M: ZksyncMiddleware> ZksyncMiddleware for CachingMiddleware<M> {
+    async fn get_block_details(&self, block_number: u32) -> Result<Option<BlockDetails>> {
+        self.block_details
+            .get_or_fetch(
+                block_number,
+                || self.inner.get_block_details(block_number),
+                |details| {
+                    details
+                        .as_ref()
+                        .is_some_and(|d| d.execute_tx_hash.is_some())
+                },
+            )
+            .await
+    }
+
+    async fn get_log_proof(
+        &self,
+        tx_hash: H256,
+        l2_to_l1_index: Option<u64>,
+    ) -> Result<Option<L2ToL1LogProof>> {
+        self.log_proofs
+            .get_or_fetch(
+                (tx_hash, l2_to_l1_index),
+                || self.inner.get_log_proof(tx_hash, l2_to_l1_index),
+                Option::is_some,
+            )
+            .await
+    }
+
+    async fn get_l1_batch_block_range(&self, batch_number: u32) -> Result<Option<(U64, U64)>> {
+        self.l1_batch_block_ranges
+            .get_or_fetch(
+                batch_number,
+                || self.inner.get_l1_batch_block_range(batch_number),
+                Option::is_some,
+            )
+            .await
+    }
+
+    async fn get_confirmed_tokens(&self, from: u32, limit: u8) -> Result<Vec<Token>> {
+        self.inner.get_confirmed_tokens(from, limit).await
+    }
+
+    async fn zks_get_transaction_receipt(&self, tx_hash: H256) -> Result<ZksyncTransactionReceipt> {
+        self.receipts
+            .get_or_fetch(
+                tx_hash,
+                || self.inner.zks_get_transaction_receipt(tx_hash),
+                |receipt| receipt.l1_batch_number.is_some() && receipt.l1_batch_tx_index.is_some(),
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::providers::{MockProvider, Provider};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn receipt(l1_batch_number: Option<u64>) -> ZksyncTransactionReceipt {
+        ZksyncTransactionReceipt {
+            l1_batch_number: l1_batch_number.map(Into::into),
+            l1_batch_tx_index: l1_batch_number.map(|_| 0.into()),
+            ..Default::default()
+        }
+    }
+
+    fn client(mock: &MockProvider) -> CachingMiddleware<Provider<MockProvider>> {
+        CachingMiddleware::new(
+            Provider::new(mock.clone()),
+            NonZeroUsize::new(DEFAULT_CACHE_CAPACITY).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn caches_receipts_included_in_batch() {
+        let mock = MockProvider::new();
+        mock.push(receipt(Some(1))).unwrap();
+        let client = client(&mock);
+
+        for _ in 0..3 {
+            let res = client
+                .zks_get_transaction_receipt(H256::zero())
+                .await
+                .unwrap();
+            assert_eq!(res, receipt(Some(1)));
+        }
+
+        // Any further request would fail with an empty mock.
+        assert!(mock
+            .assert_request("eth_getTransactionReceipt", [H256::zero()])
+            .is_ok());
+        assert!(mock
+            .assert_request("eth_getTransactionReceipt", [H256::zero()])
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn does_not_cache_receipts_not_in_batch() {
+        let mock = MockProvider::new();
+        mock.push(receipt(Some(1))).unwrap();
+        mock.push(receipt(None)).unwrap();
+        let client = client(&mock);
+
+        let res = client
+            .zks_get_transaction_receipt(H256::zero())
+            .await
+            .unwrap();
+        assert_eq!(res, receipt(None));
+
+        let res = client
+            .zks_get_transaction_receipt(H256::zero())
+            .await
+            .unwrap();
+        assert_eq!(res, receipt(Some(1)));
+    }
+
+    #[tokio::test]
+    async fn serves_concurrent_requests_with_one_fetch() {
+        let mock = MockProvider::new();
+        mock.push::<Option<(U64, U64)>, _>(Some((1.into(), 2.into())))
+            .unwrap();
+        let client = client(&mock);
+
+        let res = futures::future::join_all((0..4).map(|_| client.get_l1_batch_block_range(1)))
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(res, vec![Some((1.into(), 2.into())); 4]);
+    }
+}
@@ -0,0 +1,456 @@
+//! Failover between several RPC endpoints serving the same chain.
+
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use crate::{
+    http::HttpTransport,
+    metrics::CLIENT_METRICS,
+    rate_limit::{is_rate_limited, RateLimitedClient, RateLimits},
+};
+
+/// Health bookkeeping of a set of RPC endpoints serving the same chain.
+///
+/// Every endpoint is scored by the number of its consecutive failures.
+/// Once the active endpoint fails, the healthiest of the other endpoints
+/// becomes the active one.
+#[derive(Debug)]
+pub struct EndpointsHealth {
+    chain: &'static str,
+    labels: Vec<String>,
+    state: Mutex<HealthState>,
+}
+
+#[derive(Debug)]
+struct HealthState {
+    active: usize,
+    consecutive_failures: Vec<u64>,
+}
+
+impl EndpointsHealth {
+    /// Create a new [`EndpointsHealth`].
+    ///
+    /// # Arguments
+    ///
+    /// * `chain`: Name of the chain and transport used to label metrics
+    /// * `urls`: Urls of the endpoints, the first one is initially active
+    pub fn new<U: AsRef<str>>(chain: &'static str, urls: &[U]) -> Self {
+        assert!(!urls.is_empty(), "at least one RPC endpoint is required");
+
+        let labels: Vec<_> = urls
+            .iter()
+            .enumerate()
+            .map(|(index, url)| endpoint_label(index, url.as_ref()))
+            .collect();
+
+        for (index, label) in labels.iter().enumerate() {
+            CLIENT_METRICS.rpc_endpoint_active[&(chain, label.clone())].set((index == 0) as i64);
+        }
+
+        Self {
+            chain,
+            state: Mutex::new(HealthState {
+                active: 0,
+                consecutive_failures: vec![0; labels.len()],
+            }),
+            labels,
+        }
+    }
+
+    /// Index of the currently active endpoint.
+    pub fn active(&self) -> usize {
+        self.lock().active
+    }
+
+    /// Indices of all endpoints in the order they should be tried in:
+    /// the active endpoint first, then the others from the healthiest one.
+    pub fn candidates(&self) -> Vec<usize> {
+        let state = self.lock();
+        let len = self.labels.len();
+
+        let mut candidates: Vec<_> = (0..len).map(|i| (state.active + i) % len).collect();
+        candidates[1..].sort_by_key(|i| state.consecutive_failures[*i]);
+
+        candidates
+    }
+
+    /// Record a successful request to the endpoint with a given index.
+    pub fn record_success(&self, index: usize) {
+        self.lock().consecutive_failures[index] = 0;
+    }
+
+    /// Record a failed request to the endpoint with a given index.
+    ///
+    /// If the failed endpoint is the active one, fail over to the healthiest
+    /// of the others.
+    pub fn record_failure(&self, index: usize) {
+        let mut state = self.lock();
+
+        state.consecutive_failures[index] += 1;
+        CLIENT_METRICS.rpc_endpoint_failures[&(self.chain, self.labels[index].clone())].inc();
+
+        if state.active != index || self.labels.len() == 1 {
+            return;
+        }
+
+        let len = self.labels.len();
+        let next = (1..len)
+            .map(|i| (index + i) % len)
+            .min_by_key(|i| state.consecutive_failures[*i])
+            .expect("there is more than one endpoint; qed");
+
+        tracing::warn!(
+            "{} RPC endpoint {} has failed, switching to {}",
+            self.chain,
+            self.labels[index],
+            self.labels[next],
+        );
+
+        state.active = next;
+        CLIENT_METRICS.rpc_failovers[&self.chain].inc();
+        CLIENT_METRICS.rpc_endpoint_active[&(self.chain, self.labels[index].clone())].set(0);
+        CLIENT_METRICS.rpc_endpoint_active[&(self.chain, self.labels[next].clone())].set(1);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HealthState> {
+        self.state
+            .lock()
+            .expect("endpoints health lock is never poisoned; qed")
+    }
+}
+
+// Urls of RPC providers often carry API keys, so only the host is exposed.
+fn endpoint_label(index: usize, url: &str) -> String {
+    let host = Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+
+    format!("{index}:{host}")
+}
+
+/// Error type of [`FailoverClient`].
+#[derive(Debug, thiserror::Error)]
+pub enum FailoverClientError {
+    /// Error returned by the underlying transport.
+    #[error(transparent)]
+    Provider(ProviderError),
+
+    /// (De)Serialization error.
+    #[error(transparent)]
+    SerdeJson(serde_json::Error),
+
+    /// Endpoints have responded but a quorum of them has not agreed on the result.
+    #[error("no quorum of {0} endpoints reached for {1}")]
+    NoQuorum(usize, String),
+}
+
+impl RpcError for FailoverClientError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            Self::Provider(e) => e.as_error_response(),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            Self::Provider(e) => e.as_serde_error(),
+            Self::SerdeJson(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<FailoverClientError> for ProviderError {
+    fn from(value: FailoverClientError) -> Self {
+        match value {
+            FailoverClientError::Provider(e) => e,
+            FailoverClientError::SerdeJson(e) => e.into(),
+            e => ProviderError::JsonRpcClientError(Box::new(e)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Quorum {
+    size: usize,
+    methods: HashSet<&'static str>,
+}
+
+/// A [`JsonRpcClient`] that sends requests to the active one of several
+/// endpoints and fails over to the others when it stops responding.
+///
+/// JSON-RPC error responses are returned as is since the endpoint is
+/// alive and any other endpoint would respond the same way, unless
+/// the endpoint has rejected the request by its rate limit.
+///
+/// Optionally, a set of critical methods can be configured to be
+/// requested from all endpoints at once and only succeed if a quorum
+/// of them agrees on the result.
+#[derive(Debug, Clone)]
+pub struct FailoverClient<T> {
+    transports: Vec<T>,
+    health: Arc<EndpointsHealth>,
+    quorum: Option<Quorum>,
+}
+
+impl FailoverClient<RateLimitedClient<HttpTransport>> {
+    /// Create a new [`FailoverClient`] over rate limited HTTP endpoints.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain`: Name of the chain used to label metrics
+    /// * `urls`: Urls of the endpoints, the first one is initially active
+    /// * `limits`: Limits of requests to each of the endpoints
+    pub fn from_urls(chain: &'static str, urls: &[Url], limits: &RateLimits) -> Self {
+        let transports = urls
+            .iter()
+            .map(|url| RateLimitedClient::new(HttpTransport::new(url.clone()), chain, limits))
+            .collect();
+
+        Self::new(Arc::new(EndpointsHealth::new(chain, urls)), transports)
+    }
+}
+
+impl<T> FailoverClient<T> {
+    /// Create a new [`FailoverClient`] given a set of transports and their health.
+    pub fn new(health: Arc<EndpointsHealth>, transports: Vec<T>) -> Self {
+        assert_eq!(health.labels.len(), transports.len());
+
+        Self {
+            transports,
+            health,
+            quorum: None,
+        }
+    }
+
+    /// Request the given methods from all endpoints and require
+    /// at least `size` of them to return the same result.
+    pub fn with_quorum(mut self, size: usize, methods: &[&'static str]) -> Self {
+        self.quorum = Some(Quorum {
+            size: size.clamp(1, self.transports.len()),
+            methods: methods.iter().copied().collect(),
+        });
+
+        self
+    }
+}
+
+impl<T> FailoverClient<T>
+where
+    T: JsonRpcClient,
+{
+    async fn request_endpoint(
+        &self,
+        index: usize,
+        method: &str,
+        params: Option<&Value>,
+    ) -> Result<Value, ProviderError> {
+        // Parameters of zero size are skipped by the transports, so
+        // they have to be passed through as such instead of as `null`.
+        let res = match params {
+            Some(params) => self.transports[index].request(method, params).await,
+            None => self.transports[index].request(method, ()).await,
+        };
+
+        res.map_err(Into::into)
+    }
+
+    async fn request_failover(
+        &self,
+        method: &str,
+        params: Option<&Value>,
+    ) -> Result<Value, FailoverClientError> {
+        let mut last_error = None;
+
+        for index in self.health.candidates() {
+            match self.request_endpoint(index, method, params).await {
+                Ok(value) => {
+                    self.health.record_success(index);
+                    return Ok(value);
+                }
+                Err(e) if e.as_error_response().is_some() && !is_rate_limited(&e) => {
+                    self.health.record_success(index);
+                    return Err(FailoverClientError::Provider(e));
+                }
+                Err(e) => {
+                    tracing::warn!("request {method} to {} failed: {e}", self.label(index));
+                    self.health.record_failure(index);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(FailoverClientError::Provider(
+            last_error.expect("there is always at least one endpoint; qed"),
+        ))
+    }
+
+    async fn request_quorum(
+        &self,
+        size: usize,
+        method: &str,
+        params: Option<&Value>,
+    ) -> Result<Value, FailoverClientError> {
+        let responses = futures::future::join_all(self.health.candidates().into_iter().map(
+            |index| async move { (index, self.request_endpoint(index, method, params).await) },
+        ))
+        .await;
+
+        let mut votes: Vec<(Value, usize)> = vec![];
+        let mut error_response = None;
+        let mut last_error = None;
+
+        for (index, response) in responses {
+            match response {
+                Ok(value) => {
+                    self.health.record_success(index);
+
+                    match votes.iter_mut().find(|(v, _)| *v == value) {
+                        Some((_, count)) => *count += 1,
+                        None => votes.push((value, 1)),
+                    }
+                }
+                Err(e) if e.as_error_response().is_some() && !is_rate_limited(&e) => {
+                    self.health.record_success(index);
+                    error_response.get_or_insert(e);
+                }
+                Err(e) => {
+                    tracing::warn!("request {method} to {} failed: {e}", self.label(index));
+                    self.health.record_failure(index);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if let Some((value, _)) = votes.iter().find(|(_, count)| *count >= size) {
+            return Ok(value.clone());
+        }
+
+        CLIENT_METRICS.rpc_quorum_failures[&self.health.chain].inc();
+
+        match error_response.or(last_error) {
+            Some(e) if votes.is_empty() => Err(FailoverClientError::Provider(e)),
+            _ => Err(FailoverClientError::NoQuorum(size, method.to_string())),
+        }
+    }
+
+    fn label(&self, index: usize) -> &str {
+        &self.health.labels[index]
+    }
+}
+
+#[async_trait]
+impl<T> JsonRpcClient for FailoverClient<T>
+where
+    T: JsonRpcClient,
+{
+    type Error = FailoverClientError;
+
+    async fn request<A, R>(&self, method: &str, params: A) -> Result<R, Self::Error>
+    where
+        A: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = if std::mem::size_of::<A>() == 0 {
+            None
+        } else {
+            Some(serde_json::to_value(params).map_err(FailoverClientError::SerdeJson)?)
+        };
+
+        let value = match self.quorum {
+            Some(ref quorum) if quorum.methods.contains(method) => {
+                self.request_quorum(quorum.size, method, params.as_ref())
+                    .await?
+            }
+            _ => self.request_failover(method, params.as_ref()).await?,
+        };
+
+        serde_json::from_value(value).map_err(FailoverClientError::SerdeJson)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::providers::{MockProvider, MockResponse};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn failover_client(transports: Vec<MockProvider>) -> FailoverClient<MockProvider> {
+        let urls: Vec<_> = (0..transports.len())
+            .map(|i| format!("http://endpoint-{i}.test"))
+            .collect();
+
+        FailoverClient::new(Arc::new(EndpointsHealth::new("test", &urls)), transports)
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_the_next_endpoint() {
+        let (first, second) = (MockProvider::new(), MockProvider::new());
+        second.push(42_u64).unwrap();
+        second.push(43_u64).unwrap();
+
+        let client = failover_client(vec![first, second]);
+
+        let res: u64 = client.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(res, 43);
+        assert_eq!(client.health.active(), 1);
+
+        let res: u64 = client.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(res, 42);
+    }
+
+    #[tokio::test]
+    async fn error_responses_are_not_failed_over() {
+        let (first, second) = (MockProvider::new(), MockProvider::new());
+        first.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "execution reverted".into(),
+            data: None,
+        }));
+        second.push(42_u64).unwrap();
+
+        let client = failover_client(vec![first, second]);
+
+        let err = client.request::<_, u64>("eth_call", ()).await.unwrap_err();
+        assert_eq!(err.as_error_response().unwrap().code, -32000);
+        assert_eq!(client.health.active(), 0);
+    }
+
+    #[tokio::test]
+    async fn quorum_requires_agreement() {
+        let transports = vec![
+            MockProvider::new(),
+            MockProvider::new(),
+            MockProvider::new(),
+        ];
+        transports[0].push(1_u64).unwrap();
+        transports[1].push(2_u64).unwrap();
+        transports[2].push(2_u64).unwrap();
+        transports[0].push(1_u64).unwrap();
+        transports[1].push(2_u64).unwrap();
+        transports[2].push(3_u64).unwrap();
+
+        let client = failover_client(transports).with_quorum(2, &["zks_getL2ToL1LogProof"]);
+
+        let err = client
+            .request::<_, u64>("zks_getL2ToL1LogProof", ())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, FailoverClientError::NoQuorum(2, _)));
+
+        let res: u64 = client.request("zks_getL2ToL1LogProof", ()).await.unwrap();
+        assert_eq!(res, 2);
+    }
+}
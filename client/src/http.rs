@@ -0,0 +1,164 @@
+//! HTTP transport of JSON-RPC requests keeping the statuses of failed responses.
+
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, JsonRpcError, ProviderError, RpcError};
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use url::Url;
+
+/// Error type of [`HttpTransport`].
+#[derive(Debug, thiserror::Error)]
+pub enum HttpTransportError {
+    /// The request has failed.
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+
+    /// The endpoint has responded with an unsuccessful status and a body that is not JSON-RPC.
+    #[error("HTTP status {status}: {body}")]
+    Status {
+        /// Status of the response.
+        status: StatusCode,
+
+        /// Body of the response.
+        body: String,
+    },
+
+    /// The endpoint has responded with a JSON-RPC error.
+    #[error(transparent)]
+    JsonRpc(#[from] JsonRpcError),
+
+    /// The response could not be deserialized.
+    #[error("Deserialization Error: {err}. Response: {text}")]
+    SerdeJson {
+        /// Underlying error.
+        err: serde_json::Error,
+
+        /// Body of the response.
+        text: String,
+    },
+}
+
+impl HttpTransportError {
+    /// The HTTP status of the response the request has failed with, if any.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Reqwest(e) => e.status(),
+            Self::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl RpcError for HttpTransportError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            Self::JsonRpc(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            Self::SerdeJson { err, .. } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<HttpTransportError> for ProviderError {
+    fn from(value: HttpTransportError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(value))
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    result: Value,
+    error: Option<JsonRpcError>,
+}
+
+/// A [`JsonRpcClient`] sending requests over HTTP.
+///
+/// Unlike [`ethers::providers::Http`] it reports the HTTP status of
+/// a response that is not JSON-RPC, such as a rejection by a rate limit.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    id: Arc<AtomicU64>,
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl HttpTransport {
+    /// Create a new [`HttpTransport`] sending requests to `url`.
+    pub fn new(url: Url) -> Self {
+        Self {
+            id: Arc::new(AtomicU64::new(1)),
+            client: reqwest::Client::new(),
+            url,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for HttpTransport {
+    type Error = HttpTransportError;
+
+    async fn request<A, R>(&self, method: &str, params: A) -> Result<R, Self::Error>
+    where
+        A: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let id = self.id.fetch_add(1, Ordering::SeqCst);
+
+        // Parameters of zero size are skipped like by the other transports.
+        let payload = if std::mem::size_of::<A>() == 0 {
+            json!({ "jsonrpc": "2.0", "id": id, "method": method })
+        } else {
+            json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+        };
+
+        let response = self
+            .client
+            .post(self.url.as_ref())
+            .json(&payload)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+
+        let response = match serde_json::from_slice::<Response>(&body) {
+            Ok(response) => response,
+            Err(_) if !status.is_success() => {
+                return Err(HttpTransportError::Status {
+                    status,
+                    body: String::from_utf8_lossy(&body).to_string(),
+                })
+            }
+            Err(err) => {
+                return Err(HttpTransportError::SerdeJson {
+                    err,
+                    text: String::from_utf8_lossy(&body).to_string(),
+                })
+            }
+        };
+
+        if let Some(error) = response.error {
+            return Err(error.into());
+        }
+
+        serde_json::from_value(response.result).map_err(|err| HttpTransportError::SerdeJson {
+            err,
+            text: String::from_utf8_lossy(&body).to_string(),
+        })
+    }
+}
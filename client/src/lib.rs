@@ -17,7 +17,7 @@ use async_trait::async_trait;
 use auto_impl::auto_impl;
 use ethers::{
     abi::{AbiDecode, AbiEncode, ParamType, RawLog, Token},
-    contract::{EthCall, EthEvent, EthLogDecode},
+    contract::{ContractCall, EthCall, EthEvent, EthLogDecode},
     providers::{JsonRpcClient, Middleware, Provider},
     types::{transaction::eip2718::TypedTransaction, Address, Bytes, H160, H256, U256, U64},
 };
@@ -26,10 +26,13 @@ use ethers_log_decode::EthLogDecode;
 use ethtoken::codegen::WithdrawalFilter;
 use l1bridge::codegen::{FinalizeWithdrawalCall, IL1Bridge};
 use l1messenger::codegen::L1MessageSentFilter;
+use l1sharedbridge::codegen::IL1SharedBridge;
 use l2standard_token::codegen::{BridgeBurnFilter, L1AddressCall};
 use lazy_static::lazy_static;
 use tokio::sync::Mutex;
-use withdrawal_finalizer::codegen::RequestFinalizeWithdrawal;
+use withdrawal_finalizer::codegen::{
+    RequestFinalizeWithdrawal, RequestFinalizeWithdrawalSharedBridge,
+};
 use zksync_contract::codegen::{FinalizeEthWithdrawalCall, IZkSync};
 use zksync_types::{
     BlockDetails, L2ToL1Log, L2ToL1LogProof, Log as ZKSLog,
@@ -51,6 +54,38 @@ pub const ETH_TOKEN_ADDRESS: Address = H160([
 /// Eth address
 pub const ETH_ADDRESS: Address = Address::zero();
 
+/// The base token of a ZK chain, the token its fees are paid in.
+///
+/// On L2 the base token always lives at [`ETH_TOKEN_ADDRESS`] and is withdrawn
+/// with the same messages as ETH is on chains with ETH as the base token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseToken {
+    /// Address of the token on L1, [`ETH_ADDRESS`] for ETH.
+    pub l1_address: Address,
+
+    /// Number of decimals of the token.
+    pub decimals: u32,
+}
+
+impl BaseToken {
+    /// ETH as the base token.
+    pub const ETH: BaseToken = BaseToken {
+        l1_address: ETH_ADDRESS,
+        decimals: 18,
+    };
+
+    /// Is the base token ETH?
+    pub fn is_eth(&self) -> bool {
+        self.l1_address == ETH_ADDRESS
+    }
+}
+
+impl Default for BaseToken {
+    fn default() -> Self {
+        Self::ETH
+    }
+}
+
 /// Address of Ethereum L1 messenger
 pub const L1_MESSENGER_ADDRESS: Address = H160([
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -62,12 +97,18 @@ pub const DEPLOYER_ADDRESS: Address = H160([
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x80, 0x06,
 ]);
+pub mod cache;
 pub mod contracts_deployer;
 pub mod ethtoken;
+pub mod failover;
+pub mod http;
 pub mod l1bridge;
 pub mod l1messenger;
+pub mod l1sharedbridge;
 pub mod l2bridge;
 pub mod l2standard_token;
+pub mod rate_limit;
+pub mod signer;
 pub mod withdrawal_finalizer;
 pub mod zksync_contract;
 pub mod zksync_types;
@@ -113,11 +154,30 @@ impl WithdrawalParams {
             gas: withdrawal_gas_limit,
         }
     }
+
+    /// Convert `WithdrawalData` into a `RequestFinalizeWithdrawalSharedBridge` given a gas limit.
+    pub fn into_shared_bridge_request_with_gaslimit(
+        self,
+        withdrawal_gas_limit: U256,
+    ) -> RequestFinalizeWithdrawalSharedBridge {
+        RequestFinalizeWithdrawalSharedBridge {
+            chain_id: self.chain_id.into(),
+            l_2_batch_number: self.l1_batch_number.as_u64().into(),
+            l_2_message_index: self.l2_message_index.into(),
+            l_2_tx_number_in_batch: self.l2_tx_number_in_block,
+            message: self.message,
+            merkle_proof: self.proof,
+            gas: withdrawal_gas_limit,
+        }
+    }
 }
 
 /// A key that uniquely identifies each withdrawal
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct WithdrawalKey {
+    /// Chain id of the ZK chain the withdrawal has happened on
+    pub chain_id: u64,
+
     /// A transaction in which the withdrawal has happened
     pub tx_hash: H256,
 
@@ -128,6 +188,11 @@ pub struct WithdrawalKey {
 /// Withdrawal parameters
 #[derive(Debug, Clone)]
 pub struct WithdrawalParams {
+    /// Chain id of the ZK chain the withdrawal has happened on.
+    ///
+    /// Not known to the L2 node, set by the caller.
+    pub chain_id: u64,
+
     /// Hash of the withdrawal transaction.
     pub tx_hash: H256,
 
@@ -165,6 +230,7 @@ impl WithdrawalParams {
     /// Get the key of this withdrawal.
     pub fn key(&self) -> WithdrawalKey {
         WithdrawalKey {
+            chain_id: self.chain_id,
             tx_hash: self.tx_hash,
             event_index_in_tx: self.event_index_in_tx,
         }
@@ -226,104 +292,6 @@ pub trait ZksyncMiddleware: Middleware {
     ///
     /// * `withdrawal_hash`: Hash of the TX in which withdrawal event was emitted
     /// * `index`: Index of the withdrawal event in transaction.
-    async fn finalize_withdrawal_params(
-        &self,
-        withdrawal_hash: H256,
-        index: usize,
-    ) -> Result<Option<WithdrawalParams>>;
-
-    /// Get the `zksync` withdrawal logs by tx hash.
-    ///
-    /// # Arguments
-    ///
-    /// * `client`: `JsonRpcClient` instance to perform the request with
-    /// * `tx_hash`: Hash of the transaction
-    async fn get_withdrawal_log(
-        &self,
-        tx_hash: H256,
-        index: usize,
-    ) -> Result<Option<(ZKSLog, Option<U64>)>>;
-
-    /// Get the `L2ToL1Log` by index.
-    ///
-    /// # Arguments
-    ///
-    /// * `client`: A `JsonRpcClient` to perform requests with
-    /// * `tx_hash`: Hash of the transaction
-    /// * `index`: Index of the `L2ToL1Log` from the transaction receipt.
-    async fn get_withdrawal_l2_to_l1_log(
-        &self,
-        tx_hash: H256,
-        index: usize,
-    ) -> Result<Option<(L2ToL1Log, Option<U64>)>>;
-}
-
-#[async_trait]
-impl<P: JsonRpcClient> ZksyncMiddleware for Provider<P> {
-    async fn get_block_details(&self, block_number: u32) -> Result<Option<BlockDetails>> {
-        let latency = CLIENT_METRICS.call[&"get_block_details"].start();
-        let res = self
-            .request::<[u32; 1], Option<BlockDetails>>("zks_getBlockDetails", [block_number])
-            .await?;
-
-        latency.observe();
-
-        Ok(res)
-    }
-
-    async fn get_log_proof(
-        &self,
-        tx_hash: H256,
-        l2_to_l1_index: Option<u64>,
-    ) -> Result<Option<L2ToL1LogProof>> {
-        let latency = CLIENT_METRICS.call[&"get_l2_to_l1_log_proof"].start();
-        let params = match l2_to_l1_index {
-            Some(idx) => vec![
-                ethers::utils::serialize(&tx_hash),
-                ethers::utils::serialize(&idx),
-            ],
-            None => vec![ethers::utils::serialize(&tx_hash)],
-        };
-        let res = self.request("zks_getL2ToL1LogProof", params).await?;
-
-        latency.observe();
-
-        Ok(res)
-    }
-
-    async fn get_l1_batch_block_range(&self, batch_number: u32) -> Result<Option<(U64, U64)>> {
-        let latency = CLIENT_METRICS.call[&"get_l1_batch_block_range"].start();
-        let res = self
-            .request::<[u32; 1], Option<(U64, U64)>>("zks_getL1BatchBlockRange", [batch_number])
-            .await?;
-
-        latency.observe();
-
-        Ok(res)
-    }
-
-    async fn get_confirmed_tokens(&self, from: u32, limit: u8) -> Result<Vec<Token>> {
-        let latency = CLIENT_METRICS.call[&"get_confirmed_tokens"].start();
-        let res = self
-            .request::<[u32; 2], Vec<Token>>("zks_getConfirmedTokens", [from, limit as u32])
-            .await?;
-
-        latency.observe();
-
-        Ok(res)
-    }
-
-    async fn zks_get_transaction_receipt(&self, tx_hash: H256) -> Result<ZksyncTransactionReceipt> {
-        let latency = CLIENT_METRICS.call[&"get_transaction_receipt"].start();
-        let res = self
-            .request::<[H256; 1], ZksyncTransactionReceipt>("eth_getTransactionReceipt", [tx_hash])
-            .await?;
-
-        latency.observe();
-
-        Ok(res)
-    }
-
     async fn finalize_withdrawal_params(
         &self,
         withdrawal_hash: H256,
@@ -363,7 +331,11 @@ impl<P: JsonRpcClient> ZksyncMiddleware for Provider<P> {
                         call.set_to(withdrawal_log.address);
                         call.set_data(l1_address_call.encode().into());
 
-                        let l1_address = Address::decode(self.call(&call, None).await?)?;
+                        let l1_address = Address::decode(
+                            self.call(&call, None)
+                                .await
+                                .map_err(|e| Error::Middleware(e.to_string()))?,
+                        )?;
 
                         addr_lock.insert(withdrawal_log.address, l1_address);
 
@@ -441,6 +413,7 @@ impl<P: JsonRpcClient> ZksyncMiddleware for Provider<P> {
         latency.observe();
 
         Ok(Some(WithdrawalParams {
+            chain_id: 0,
             tx_hash: withdrawal_hash,
             event_index_in_tx: index as u32,
             id: 0,
@@ -457,6 +430,54 @@ impl<P: JsonRpcClient> ZksyncMiddleware for Provider<P> {
         }))
     }
 
+    /// Get the withdrawal events emitted by a transaction in the order of their indices.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash`: Hash of the transaction
+    async fn get_withdrawal_events(&self, tx_hash: H256) -> Result<Vec<WithdrawalEvent>> {
+        let latency = CLIENT_METRICS.call[&"get_withdrawal_events"].start();
+
+        let receipt = self.zks_get_transaction_receipt(tx_hash).await?;
+
+        let events = receipt
+            .logs
+            .iter()
+            .filter(|log| {
+                log.topics[0] == BridgeBurnFilter::signature()
+                    || log.topics[0] == WithdrawalFilter::signature()
+            })
+            .map(|log| {
+                let raw_log: RawLog = log.clone().into();
+                let (amount, l1_receiver) = match WithdrawalEvents::decode_log(&raw_log)? {
+                    WithdrawalEvents::BridgeBurn(b) => (b.amount, None),
+                    WithdrawalEvents::Withdrawal(w) => (w.amount, Some(w.l_1_receiver)),
+                };
+
+                Ok(WithdrawalEvent {
+                    tx_hash,
+                    block_number: log
+                        .block_number
+                        .expect("log always has a block number; qed")
+                        .as_u64(),
+                    token: log.address,
+                    amount,
+                    l1_receiver,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        latency.observe();
+
+        Ok(events)
+    }
+
+    /// Get the `zksync` withdrawal logs by tx hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `client`: `JsonRpcClient` instance to perform the request with
+    /// * `tx_hash`: Hash of the transaction
     async fn get_withdrawal_log(
         &self,
         tx_hash: H256,
@@ -484,6 +505,13 @@ impl<P: JsonRpcClient> ZksyncMiddleware for Provider<P> {
         Ok(Some((log, receipt.l1_batch_tx_index)))
     }
 
+    /// Get the `L2ToL1Log` by index.
+    ///
+    /// # Arguments
+    ///
+    /// * `client`: A `JsonRpcClient` to perform requests with
+    /// * `tx_hash`: Hash of the transaction
+    /// * `index`: Index of the `L2ToL1Log` from the transaction receipt.
     async fn get_withdrawal_l2_to_l1_log(
         &self,
         tx_hash: H256,
@@ -510,13 +538,210 @@ impl<P: JsonRpcClient> ZksyncMiddleware for Provider<P> {
     }
 }
 
+#[async_trait]
+impl<P: JsonRpcClient> ZksyncMiddleware for Provider<P> {
+    async fn get_block_details(&self, block_number: u32) -> Result<Option<BlockDetails>> {
+        let latency = CLIENT_METRICS.call[&"get_block_details"].start();
+        let res = self
+            .request::<[u32; 1], Option<BlockDetails>>("zks_getBlockDetails", [block_number])
+            .await?;
+
+        latency.observe();
+
+        Ok(res)
+    }
+
+    async fn get_log_proof(
+        &self,
+        tx_hash: H256,
+        l2_to_l1_index: Option<u64>,
+    ) -> Result<Option<L2ToL1LogProof>> {
+        let latency = CLIENT_METRICS.call[&"get_l2_to_l1_log_proof"].start();
+        let params = match l2_to_l1_index {
+            Some(idx) => vec![
+                ethers::utils::serialize(&tx_hash),
+                ethers::utils::serialize(&idx),
+            ],
+            None => vec![ethers::utils::serialize(&tx_hash)],
+        };
+        let res = self.request("zks_getL2ToL1LogProof", params).await?;
+
+        latency.observe();
+
+        Ok(res)
+    }
+
+    async fn get_l1_batch_block_range(&self, batch_number: u32) -> Result<Option<(U64, U64)>> {
+        let latency = CLIENT_METRICS.call[&"get_l1_batch_block_range"].start();
+        let res = self
+            .request::<[u32; 1], Option<(U64, U64)>>("zks_getL1BatchBlockRange", [batch_number])
+            .await?;
+
+        latency.observe();
+
+        Ok(res)
+    }
+
+    async fn get_confirmed_tokens(&self, from: u32, limit: u8) -> Result<Vec<Token>> {
+        let latency = CLIENT_METRICS.call[&"get_confirmed_tokens"].start();
+        let res = self
+            .request::<[u32; 2], Vec<Token>>("zks_getConfirmedTokens", [from, limit as u32])
+            .await?;
+
+        latency.observe();
+
+        Ok(res)
+    }
+
+    async fn zks_get_transaction_receipt(&self, tx_hash: H256) -> Result<ZksyncTransactionReceipt> {
+        let latency = CLIENT_METRICS.call[&"get_transaction_receipt"].start();
+        let res = self
+            .request::<[H256; 1], ZksyncTransactionReceipt>("eth_getTransactionReceipt", [tx_hash])
+            .await?;
+
+        latency.observe();
+
+        Ok(res)
+    }
+}
+
+/// L1 contracts withdrawals of a ZK chain are finalized with.
+#[derive(Debug)]
+pub enum FinalizationContracts<M> {
+    /// Legacy flow: ETH withdrawals are finalized on the diamond proxy
+    /// and ERC20 withdrawals on the L1 ERC20 bridge.
+    ///
+    /// Only supports chains with ETH as the base token.
+    Legacy {
+        /// The diamond proxy of the chain.
+        zksync_contract: IZkSync<M>,
+
+        /// The L1 ERC20 bridge.
+        l1_bridge: IL1Bridge<M>,
+    },
+
+    /// All withdrawals are finalized on the L1 shared bridge, including
+    /// the ones of a custom base token.
+    SharedBridge {
+        /// The L1 shared bridge.
+        shared_bridge: IL1SharedBridge<M>,
+
+        /// Chain id of the ZK chain.
+        chain_id: u64,
+    },
+}
+
+impl<M> Clone for FinalizationContracts<M> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Legacy {
+                zksync_contract,
+                l1_bridge,
+            } => Self::Legacy {
+                zksync_contract: zksync_contract.clone(),
+                l1_bridge: l1_bridge.clone(),
+            },
+            Self::SharedBridge {
+                shared_bridge,
+                chain_id,
+            } => Self::SharedBridge {
+                shared_bridge: shared_bridge.clone(),
+                chain_id: *chain_id,
+            },
+        }
+    }
+}
+
+impl<M: Middleware> FinalizationContracts<M> {
+    /// Check if the withdrawal with given parameters is finalized on L1.
+    ///
+    /// # Arguments
+    ///
+    /// * `l1_batch_number`: Number of the L1 batch the withdrawal is in
+    /// * `l2_message_index`: Index of the withdrawal message in the batch
+    /// * `sender`: Sender of the withdrawal message on L2
+    pub async fn is_withdrawal_finalized(
+        &self,
+        l1_batch_number: U256,
+        l2_message_index: U256,
+        sender: Address,
+    ) -> Result<bool> {
+        let is_finalized = match self {
+            Self::Legacy {
+                zksync_contract, ..
+            } if is_eth(sender) => {
+                zksync_contract
+                    .is_eth_withdrawal_finalized(l1_batch_number, l2_message_index)
+                    .call()
+                    .await?
+            }
+            Self::Legacy { l1_bridge, .. } => {
+                l1_bridge
+                    .is_withdrawal_finalized(l1_batch_number, l2_message_index)
+                    .call()
+                    .await?
+            }
+            Self::SharedBridge {
+                shared_bridge,
+                chain_id,
+            } => {
+                shared_bridge
+                    .is_withdrawal_finalized((*chain_id).into(), l1_batch_number, l2_message_index)
+                    .call()
+                    .await?
+            }
+        };
+
+        Ok(is_finalized)
+    }
+
+    /// A call finalizing the withdrawal with given parameters directly on the contracts
+    /// of the chain: ETH withdrawals on the diamond proxy and ERC20 ones on the L1 ERC20
+    /// bridge in the legacy flow, all withdrawals on the L1 shared bridge otherwise.
+    pub fn finalize_withdrawal(&self, params: &WithdrawalParams) -> ContractCall<M, ()> {
+        let l1_batch_number = params.l1_batch_number.as_u64().into();
+        let l2_message_index = params.l2_message_index.into();
+        let message = params.message.clone();
+        let proof = params.proof.clone();
+
+        match self {
+            Self::Legacy {
+                zksync_contract, ..
+            } if is_eth(params.sender) => zksync_contract.finalize_eth_withdrawal(
+                l1_batch_number,
+                l2_message_index,
+                params.l2_tx_number_in_block,
+                message,
+                proof,
+            ),
+            Self::Legacy { l1_bridge, .. } => l1_bridge.finalize_withdrawal(
+                l1_batch_number,
+                l2_message_index,
+                params.l2_tx_number_in_block,
+                message,
+                proof,
+            ),
+            Self::SharedBridge {
+                shared_bridge,
+                chain_id,
+            } => shared_bridge.finalize_withdrawal(
+                (*chain_id).into(),
+                l1_batch_number,
+                l2_message_index,
+                params.l2_tx_number_in_block,
+                message,
+                proof,
+            ),
+        }
+    }
+}
+
 /// Check if the withdrawal is finalized on L1.
 pub async fn is_withdrawal_finalized<'a, M1, M2>(
     withdrawal_hash: H256,
     index: usize,
     sender: Address,
-    zksync_contract: &'a IZkSync<M1>,
-    l1_bridge: &'a IL1Bridge<M1>,
+    contracts: &'a FinalizationContracts<M1>,
     l2_middleware: &'a M2,
 ) -> Result<bool>
 where
@@ -555,21 +780,9 @@ where
         None => return Ok(false),
     };
 
-    if is_eth(sender) {
-        let is_finalized = zksync_contract
-            .is_eth_withdrawal_finalized(l1_batch_number, l2_message_index.into())
-            .call()
-            .await?;
-
-        Ok(is_finalized)
-    } else {
-        let is_finalized = l1_bridge
-            .is_withdrawal_finalized(l1_batch_number, l2_message_index.into())
-            .call()
-            .await?;
-
-        Ok(is_finalized)
-    }
+    contracts
+        .is_withdrawal_finalized(l1_batch_number, l2_message_index.into(), sender)
+        .await
 }
 
 fn get_l1_bridge_burn_message_keccak(
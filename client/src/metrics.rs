@@ -4,7 +4,10 @@
 
 use std::time::Duration;
 
-use vise::{Buckets, Histogram, LabeledFamily, Metrics};
+use vise::{Buckets, Counter, Gauge, Histogram, LabeledFamily, Metrics};
+
+const ENDPOINT_LABELS: [&str; 2] = ["chain", "endpoint"];
+type EndpointLabels = (&'static str, String);
 
 /// Client metrics.
 #[derive(Debug, Metrics)]
@@ -12,6 +15,42 @@ use vise::{Buckets, Histogram, LabeledFamily, Metrics};
 pub(super) struct ClientMetrics {
     #[metrics(buckets = Buckets::LATENCIES, labels = ["method"])]
     pub call: LabeledFamily<&'static str, Histogram<Duration>>,
+
+    /// Set to one for the currently active RPC endpoint of a chain.
+    #[metrics(labels = ENDPOINT_LABELS)]
+    pub rpc_endpoint_active: LabeledFamily<EndpointLabels, Gauge, 2>,
+
+    /// Number of failed requests to an RPC endpoint.
+    #[metrics(labels = ENDPOINT_LABELS)]
+    pub rpc_endpoint_failures: LabeledFamily<EndpointLabels, Counter, 2>,
+
+    /// Number of times a chain has failed over to another RPC endpoint.
+    #[metrics(labels = ["chain"])]
+    pub rpc_failovers: LabeledFamily<&'static str, Counter>,
+
+    /// Number of quorum reads that have not reached a quorum.
+    #[metrics(labels = ["chain"])]
+    pub rpc_quorum_failures: LabeledFamily<&'static str, Counter>,
+
+    /// Time requests spend waiting for the rate limiter of an RPC endpoint.
+    #[metrics(buckets = Buckets::LATENCIES, labels = ["chain"])]
+    pub rpc_rate_limiter_wait: LabeledFamily<&'static str, Histogram<Duration>>,
+
+    /// Number of requests waiting for a response from RPC endpoints.
+    #[metrics(labels = ["chain"])]
+    pub rpc_requests_in_flight: LabeledFamily<&'static str, Gauge>,
+
+    /// Number of requests rejected by RPC endpoints as rate limited.
+    #[metrics(labels = ["chain"])]
+    pub rpc_rate_limited_responses: LabeledFamily<&'static str, Counter>,
+
+    /// Number of requests served from a cache of L2 data.
+    #[metrics(labels = ["cache"])]
+    pub cache_hits: LabeledFamily<&'static str, Counter>,
+
+    /// Number of requests to L2 not found in a cache of L2 data.
+    #[metrics(labels = ["cache"])]
+    pub cache_misses: LabeledFamily<&'static str, Counter>,
 }
 
 #[vise::register]
@@ -0,0 +1,369 @@
+//! Rate limiting of requests to an RPC endpoint.
+
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, MockError, ProviderError, RpcError};
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tokio::{
+    sync::{Mutex, Semaphore, SemaphorePermit},
+    time::Instant,
+};
+
+use crate::{http::HttpTransportError, metrics::CLIENT_METRICS};
+
+/// Backoff before the first retry of a rate limited request, doubled on every next retry.
+const RATE_LIMITED_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// The longest backoff between retries of a rate limited request.
+const RATE_LIMITED_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Limits of requests to a single RPC endpoint.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RateLimits {
+    /// Maximum number of requests sent per second.
+    pub requests_per_second: Option<u32>,
+
+    /// Maximum number of requests waiting for a response at the same time.
+    pub max_in_flight: Option<usize>,
+
+    /// Number of retries of requests rejected by the endpoint as rate limited.
+    pub retries: u32,
+}
+
+/// Errors of transports telling apart rejections of requests by rate limits
+/// of the endpoint that are not JSON-RPC error responses.
+pub trait TransportError {
+    /// Is this error a rejection of the request by a rate limit of the endpoint.
+    fn is_rate_limited(&self) -> bool;
+}
+
+impl TransportError for HttpTransportError {
+    fn is_rate_limited(&self) -> bool {
+        self.status() == Some(StatusCode::TOO_MANY_REQUESTS)
+    }
+}
+
+impl TransportError for MockError {
+    fn is_rate_limited(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug)]
+struct RateLimiter {
+    interval: Option<Duration>,
+    next_slot: Mutex<Instant>,
+    in_flight: Option<Semaphore>,
+}
+
+impl RateLimiter {
+    fn new(limits: &RateLimits) -> Self {
+        Self {
+            interval: limits
+                .requests_per_second
+                .filter(|rps| *rps > 0)
+                .map(|rps| Duration::from_secs(1) / rps),
+            next_slot: Mutex::new(Instant::now()),
+            in_flight: limits.max_in_flight.map(|n| Semaphore::new(n.max(1))),
+        }
+    }
+
+    // Wait for a free in-flight slot and then for the next request slot of the rate.
+    async fn acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let permit = match self.in_flight {
+            Some(ref in_flight) => Some(
+                in_flight
+                    .acquire()
+                    .await
+                    .expect("rate limiter semaphore is never closed; qed"),
+            ),
+            None => None,
+        };
+
+        if let Some(interval) = self.interval {
+            let slot = {
+                let mut next_slot = self.next_slot.lock().await;
+                let slot = std::cmp::max(*next_slot, Instant::now());
+                *next_slot = slot + interval;
+                slot
+            };
+
+            tokio::time::sleep_until(slot).await;
+        }
+
+        permit
+    }
+}
+
+/// A [`JsonRpcClient`] that limits the rate and the concurrency of requests
+/// to the underlying endpoint and retries requests rejected as rate limited
+/// with an exponential backoff.
+///
+/// Clones share the same limits.
+#[derive(Debug, Clone)]
+pub struct RateLimitedClient<T> {
+    inner: T,
+    chain: &'static str,
+    limiter: Arc<RateLimiter>,
+    retries: u32,
+}
+
+impl<T> RateLimitedClient<T> {
+    /// Create a new [`RateLimitedClient`].
+    ///
+    /// # Arguments
+    ///
+    /// * `inner`: The transport to send requests with
+    /// * `chain`: Name of the chain used to label metrics
+    /// * `limits`: Limits of requests to the endpoint
+    pub fn new(inner: T, chain: &'static str, limits: &RateLimits) -> Self {
+        Self {
+            inner,
+            chain,
+            limiter: Arc::new(RateLimiter::new(limits)),
+            retries: limits.retries,
+        }
+    }
+}
+
+impl<T> RateLimitedClient<T>
+where
+    T: JsonRpcClient,
+    T::Error: TransportError,
+{
+    async fn request_limited(
+        &self,
+        method: &str,
+        params: Option<&Value>,
+    ) -> Result<Value, T::Error> {
+        let latency = CLIENT_METRICS.rpc_rate_limiter_wait[&self.chain].start();
+        let _permit = self.limiter.acquire().await;
+        latency.observe();
+
+        let _in_flight = CLIENT_METRICS.rpc_requests_in_flight[&self.chain].inc_guard(1);
+
+        // Parameters of zero size are skipped by the transports, so
+        // they have to be passed through as such instead of as `null`.
+        match params {
+            Some(params) => self.inner.request(method, params).await,
+            None => self.inner.request(method, ()).await,
+        }
+    }
+}
+
+#[async_trait]
+impl<T> JsonRpcClient for RateLimitedClient<T>
+where
+    T: JsonRpcClient,
+    T::Error: TransportError,
+{
+    type Error = ProviderError;
+
+    async fn request<A, R>(&self, method: &str, params: A) -> Result<R, Self::Error>
+    where
+        A: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = if std::mem::size_of::<A>() == 0 {
+            None
+        } else {
+            Some(serde_json::to_value(params)?)
+        };
+
+        let mut backoff = RATE_LIMITED_INITIAL_BACKOFF;
+        let mut retry = 0;
+
+        let value = loop {
+            let res = self.request_limited(method, params.as_ref()).await;
+            let rate_limited = matches!(res, Err(ref e) if e.is_rate_limited());
+
+            match res.map_err(Into::<ProviderError>::into) {
+                Err(e) if rate_limited || is_rate_limited(&e) => {
+                    CLIENT_METRICS.rpc_rate_limited_responses[&self.chain].inc();
+
+                    if retry >= self.retries {
+                        return Err(e);
+                    }
+
+                    tracing::debug!("request {method} was rate limited, retrying in {backoff:?}");
+                    tokio::time::sleep(backoff).await;
+
+                    retry += 1;
+                    backoff = std::cmp::min(backoff * 2, RATE_LIMITED_MAX_BACKOFF);
+                }
+                res => break res?,
+            }
+        };
+
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Is this error a JSON-RPC error response rejecting the request by a rate limit of the endpoint.
+///
+/// Responses with an HTTP 429 status and a body that is not JSON-RPC are
+/// told apart by the [`TransportError`] of the transport.
+pub fn is_rate_limited(e: &ProviderError) -> bool {
+    e.as_error_response().is_some_and(|e| {
+        // `429` is used by alchemy and `-32005` by infura.
+        e.code == 429 || e.code == -32005 || e.message.contains("rate limit")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        providers::{JsonRpcError, MockProvider, MockResponse},
+        types::U64,
+    };
+    use pretty_assertions::assert_eq;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::http::HttpTransport;
+
+    fn rate_limited_response() -> MockResponse {
+        MockResponse::Error(JsonRpcError {
+            code: 429,
+            message: "Your app has exceeded its compute units per second capacity".into(),
+            data: None,
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_rate_limited_requests() {
+        let transport = MockProvider::new();
+        transport.push(42_u64).unwrap();
+        transport.push_response(rate_limited_response());
+        transport.push_response(rate_limited_response());
+
+        let limits = RateLimits {
+            retries: 2,
+            ..Default::default()
+        };
+        let client = RateLimitedClient::new(transport, "test", &limits);
+
+        let res: u64 = client.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(res, 42);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_retries() {
+        let transport = MockProvider::new();
+        transport.push(42_u64).unwrap();
+        transport.push_response(rate_limited_response());
+        transport.push_response(rate_limited_response());
+
+        let limits = RateLimits {
+            retries: 1,
+            ..Default::default()
+        };
+        let client = RateLimitedClient::new(transport, "test", &limits);
+
+        let err = client
+            .request::<_, u64>("eth_blockNumber", ())
+            .await
+            .unwrap_err();
+        assert!(is_rate_limited(&err));
+    }
+
+    // Serve the given HTTP responses to requests one by one on a local port.
+    async fn serve_responses(responses: Vec<&'static str>) -> url::Url {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = vec![0; 4096];
+                let _ = stream.read(&mut request).await.unwrap();
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        url.parse().unwrap()
+    }
+
+    fn http_response(status: &str, body: &str) -> &'static str {
+        format!(
+            "HTTP/1.1 {status}\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .leak()
+    }
+
+    #[tokio::test]
+    async fn retries_requests_rejected_with_http_429() {
+        let url = serve_responses(vec![
+            http_response("429 Too Many Requests", "Too Many Requests"),
+            http_response("200 OK", r#"{"jsonrpc":"2.0","id":1,"result":"0x2a"}"#),
+        ])
+        .await;
+
+        let limits = RateLimits {
+            retries: 1,
+            ..Default::default()
+        };
+        let client = RateLimitedClient::new(HttpTransport::new(url), "test", &limits);
+
+        let res: U64 = client.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(res, 42.into());
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_other_errors_mentioning_429() {
+        let url = serve_responses(vec![http_response(
+            "500 Internal Server Error",
+            "failed to fetch block 0x429",
+        )])
+        .await;
+
+        let limits = RateLimits {
+            retries: 1,
+            ..Default::default()
+        };
+        let client = RateLimitedClient::new(HttpTransport::new(url), "test", &limits);
+
+        let err = client
+            .request::<_, U64>("eth_blockNumber", ())
+            .await
+            .unwrap_err();
+        assert!(!is_rate_limited(&err));
+        assert!(err.to_string().contains("500"));
+    }
+
+    #[test]
+    fn json_rpc_errors_mentioning_429_are_not_rate_limits() {
+        let err = ProviderError::from(HttpTransportError::JsonRpc(JsonRpcError {
+            code: 3,
+            message: "execution reverted: 0x4290".into(),
+            data: None,
+        }));
+
+        assert!(!is_rate_limited(&err));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paces_requests() {
+        let transport = MockProvider::new();
+        for _ in 0..5 {
+            transport.push(42_u64).unwrap();
+        }
+
+        let limits = RateLimits {
+            requests_per_second: Some(2),
+            ..Default::default()
+        };
+        let client = RateLimitedClient::new(transport, "test", &limits);
+
+        let start = Instant::now();
+        for _ in 0..5 {
+            let _: u64 = client.request("eth_blockNumber", ()).await.unwrap();
+        }
+
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,268 @@
+//! Signers of finalization transactions.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use ethers::{
+    providers::{Http, JsonRpcClient},
+    signers::{LocalWallet, Signer, WalletError},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Bytes, Signature,
+    },
+    utils::rlp::Rlp,
+};
+
+/// An error of signing with a [`RemoteSigner`] or an [`AccountSigner`].
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    /// An error of a local wallet.
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+
+    /// An error of a request to a remote signer.
+    #[error("remote signer request failed: {0}")]
+    Remote(String),
+
+    /// A remote signer has returned data that can not be decoded.
+    #[error("failed to decode remote signer response: {0}")]
+    Decode(String),
+
+    /// A remote signer has signed with a key of an account other than requested.
+    #[error("remote signer has signed with {0:?} instead of {1:?}")]
+    WrongAccount(Address, Address),
+
+    /// The signer does not support this kind of signing.
+    #[error("{0} is not supported by the signer")]
+    Unsupported(&'static str),
+}
+
+/// A [`Signer`] signing with a key held by a remote service over JSON-RPC.
+///
+/// Transactions are signed with `eth_signTransaction` and messages with
+/// `eth_sign` as offered by Web3Signer and similar services.
+#[derive(Debug)]
+pub struct RemoteSigner<P> {
+    client: P,
+    address: Address,
+    chain_id: u64,
+}
+
+impl<P: JsonRpcClient> RemoteSigner<P> {
+    /// Create a new [`RemoteSigner`].
+    ///
+    /// # Arguments
+    ///
+    /// * `client`: A client of the JSON-RPC API of the remote signer
+    /// * `address`: The address of the account to sign with
+    pub fn new(client: P, address: Address) -> Self {
+        Self {
+            client,
+            address,
+            chain_id: 1,
+        }
+    }
+}
+
+#[async_trait]
+impl<P: JsonRpcClient> Signer for RemoteSigner<P> {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        let signature: Bytes = self
+            .client
+            .request(
+                "eth_sign",
+                (self.address, Bytes::from(message.as_ref().to_vec())),
+            )
+            .await
+            .map_err(|e| SignerError::Remote(format!("{e}")))?;
+
+        Signature::try_from(signature.as_ref()).map_err(|e| SignerError::Decode(format!("{e}")))
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        tx.set_from(self.address);
+        tx.set_chain_id(self.chain_id);
+
+        let signed: Bytes = self
+            .client
+            .request("eth_signTransaction", [&tx])
+            .await
+            .map_err(|e| SignerError::Remote(format!("{e}")))?;
+
+        let (signed_tx, signature) = TypedTransaction::decode_signed(&Rlp::new(&signed))
+            .map_err(|e| SignerError::Decode(format!("{e}")))?;
+
+        // The signature is later applied to `tx`, so it has to be of the same transaction.
+        let signer = signature
+            .recover(tx.sighash())
+            .map_err(|e| SignerError::Decode(format!("{e}")))?;
+
+        if signer != self.address || signed_tx.sighash() != tx.sighash() {
+            return Err(SignerError::WrongAccount(signer, self.address));
+        }
+
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        _payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        Err(SignerError::Unsupported("signing typed data"))
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+/// A signer of the account finalization transactions are sent from.
+#[derive(Debug)]
+pub enum AccountSigner {
+    /// A key held in memory.
+    Local(LocalWallet),
+
+    /// A key held by a remote signer.
+    Remote(RemoteSigner<Http>),
+}
+
+impl AccountSigner {
+    /// Decrypt a key from an encrypted JSON keystore with the password read from a file.
+    pub fn from_keystore(
+        keystore_path: impl AsRef<Path>,
+        password_path: impl AsRef<Path>,
+    ) -> Result<Self, SignerError> {
+        let password = std::fs::read_to_string(password_path)
+            .map_err(|e| SignerError::Wallet(WalletError::IoError(e)))?;
+
+        let wallet = LocalWallet::decrypt_keystore(keystore_path, password.trim_end())?;
+
+        Ok(Self::Local(wallet))
+    }
+}
+
+#[async_trait]
+impl Signer for AccountSigner {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Local(s) => Ok(s.sign_message(message).await?),
+            Self::Remote(s) => s.sign_message(message).await,
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Local(s) => Ok(s.sign_transaction(tx).await?),
+            Self::Remote(s) => s.sign_transaction(tx).await,
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            Self::Local(s) => Ok(s.sign_typed_data(payload).await?),
+            Self::Remote(s) => s.sign_typed_data(payload).await,
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            Self::Local(s) => s.address(),
+            Self::Remote(s) => s.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            Self::Local(s) => s.chain_id(),
+            Self::Remote(s) => s.chain_id(),
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            Self::Local(s) => Self::Local(s.with_chain_id(chain_id)),
+            Self::Remote(s) => Self::Remote(s.with_chain_id(chain_id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        providers::MockProvider,
+        types::{Eip1559TransactionRequest, U256},
+    };
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    fn tx() -> TypedTransaction {
+        Eip1559TransactionRequest::new()
+            .to(Address::repeat_byte(1))
+            .value(1000)
+            .nonce(7)
+            .gas(21000)
+            .max_fee_per_gas(U256::exp10(10))
+            .max_priority_fee_per_gas(U256::exp10(9))
+            .into()
+    }
+
+    #[tokio::test]
+    async fn signs_transactions_remotely() {
+        let wallet = KEY.parse::<LocalWallet>().unwrap().with_chain_id(5u64);
+        let mut tx = tx();
+        tx.set_from(wallet.address());
+        tx.set_chain_id(5);
+        let expected = wallet.sign_transaction(&tx).await.unwrap();
+
+        let mock = MockProvider::new();
+        mock.push::<Bytes, _>(tx.rlp_signed(&expected)).unwrap();
+        let signer = RemoteSigner::new(mock, wallet.address()).with_chain_id(5u64);
+
+        let signature = signer.sign_transaction(&tx).await.unwrap();
+
+        assert_eq!(tx.rlp_signed(&signature), tx.rlp_signed(&expected));
+    }
+
+    #[tokio::test]
+    async fn rejects_signatures_of_other_accounts() {
+        let wallet = KEY.parse::<LocalWallet>().unwrap().with_chain_id(5u64);
+        let mut tx = tx();
+        tx.set_chain_id(5);
+        let signature = wallet.sign_transaction(&tx).await.unwrap();
+
+        let mock = MockProvider::new();
+        mock.push::<Bytes, _>(tx.rlp_signed(&signature)).unwrap();
+        let signer = RemoteSigner::new(mock, Address::repeat_byte(2)).with_chain_id(5u64);
+
+        assert!(matches!(
+            signer.sign_transaction(&tx).await,
+            Err(SignerError::WrongAccount(..))
+        ));
+    }
+}
@@ -20,7 +20,7 @@ pub struct BaseSystemContractsHashes {
 }
 
 /// A struct with the proof for the L2 to L1 log in a specific block.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct L2ToL1LogProof {
     /// The merkle path for the leaf.
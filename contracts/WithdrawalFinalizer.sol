@@ -6,11 +6,22 @@ import "@matterlabs/zksync-contracts/l1/contracts/bridge/interfaces/IL1Bridge.so
 import "@matterlabs/zksync-contracts/l1/contracts/zksync/interfaces/IMailbox.sol";
 import "@matterlabs/zksync-contracts/l1/contracts/common/libraries/UncheckedMath.sol";
 
+interface IL1SharedBridge {
+    function finalizeWithdrawal(
+        uint256 _chainId,
+        uint256 _l2BatchNumber,
+        uint256 _l2MessageIndex,
+        uint16 _l2TxNumberInBatch,
+        bytes calldata _message,
+        bytes32[] calldata _merkleProof
+    ) external;
+}
 
 contract WithdrawalFinalizer {
     using UncheckedMath for uint256;
     IMailbox constant ZKSYNC_MAILBOX = IMailbox($(ZKSYNC_ADDRESS));
     IL1Bridge constant ERC20_BRIDGE = IL1Bridge($(ERC20_BRIDGE_ADDRESS));
+    IL1SharedBridge constant SHARED_BRIDGE = IL1SharedBridge($(SHARED_BRIDGE_ADDRESS));
 
     struct RequestFinalizeWithdrawal {
         uint256 _l2BlockNumber;
@@ -22,6 +33,16 @@ contract WithdrawalFinalizer {
         uint256 _gas;
     }
 
+    struct RequestFinalizeWithdrawalSharedBridge {
+        uint256 _chainId;
+        uint256 _l2BatchNumber;
+        uint256 _l2MessageIndex;
+        uint16 _l2TxNumberInBatch;
+        bytes _message;
+        bytes32[] _merkleProof;
+        uint256 _gas;
+    }
+
     struct Result {
         uint256 _l2BlockNumber;
         uint256 _l2MessageIndex;
@@ -89,4 +110,40 @@ contract WithdrawalFinalizer {
         }
         return results;
     }
+
+    function finalizeWithdrawalsSharedBridge(
+        RequestFinalizeWithdrawalSharedBridge[] calldata requests
+    ) external returns (Result[] memory) {
+        uint256 requestsLength = requests.length;
+        Result[] memory results = new Result[](requestsLength);
+        for (uint256 i = 0; i < requestsLength; i = i.uncheckedInc()) {
+            require(gasleft() >= ((requests[i]._gas * 64) / 63) + 500, "i");
+            uint256 gasBefore = gasleft();
+            try
+                SHARED_BRIDGE.finalizeWithdrawal{gas: requests[i]._gas}(
+                    requests[i]._chainId,
+                    requests[i]._l2BatchNumber,
+                    requests[i]._l2MessageIndex,
+                    requests[i]._l2TxNumberInBatch,
+                    requests[i]._message,
+                    requests[i]._merkleProof
+                )
+            {
+                results[i] = Result({
+                    _l2BlockNumber: requests[i]._l2BatchNumber,
+                    _l2MessageIndex: requests[i]._l2MessageIndex,
+                    _gas: gasBefore - gasleft(),
+                    success: true
+                });
+            } catch {
+                results[i] = Result({
+                    _l2BlockNumber: requests[i]._l2BatchNumber,
+                    _l2MessageIndex: requests[i]._l2MessageIndex,
+                    _gas: 0,
+                    success: false
+                });
+            }
+        }
+        return results;
+    }
 }
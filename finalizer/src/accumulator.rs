@@ -1,10 +1,9 @@
-use std::collections::BTreeMap;
-
 use ethers::types::U256;
 
 use client::{
     withdrawal_finalizer::codegen::withdrawal_finalizer::Result as FinalizeResult, WithdrawalParams,
 };
+use storage::FinalizationCandidate;
 
 /// A struct that holds `RequestFinalizeWithdrawal`s and computes
 /// when there are enough in a batch to be submitted.
@@ -13,8 +12,9 @@ pub struct WithdrawalsAccumulator {
     tx_fee_limit: U256,
     batch_finalization_gas_limit: U256,
     one_withdrawal_gas_limit: U256,
-    // key: (l_2_block_number, l_2_message_index)
-    withdrawals: BTreeMap<(u64, u64), WithdrawalParams>,
+    target_batch_size: Option<usize>,
+    // in the order of priority of finalization
+    withdrawals: Vec<FinalizationCandidate>,
 }
 
 impl WithdrawalsAccumulator {
@@ -22,13 +22,26 @@ impl WithdrawalsAccumulator {
     pub fn take_withdrawals(&mut self) -> Vec<WithdrawalParams> {
         std::mem::take(&mut self.withdrawals)
             .into_iter()
-            .map(|v| v.1)
+            .map(|c| c.params)
             .collect()
     }
 
     /// Get a reference to a current set of withdrawals
     pub fn withdrawals(&self) -> impl Iterator<Item = &WithdrawalParams> {
-        self.withdrawals.values()
+        self.withdrawals.iter().map(|c| &c.params)
+    }
+
+    /// Get a reference to the current set of withdrawals along with their details
+    pub fn candidates(&self) -> &[FinalizationCandidate] {
+        &self.withdrawals
+    }
+
+    /// The L1 block the earliest executed of the current set of withdrawals has been executed in.
+    pub fn oldest_executed_l1_block(&self) -> Option<u64> {
+        self.withdrawals
+            .iter()
+            .filter_map(|c| c.executed_l1_block)
+            .min()
     }
 
     /// Remove unsuccessful withdrawals by returned results.
@@ -39,43 +52,62 @@ impl WithdrawalsAccumulator {
         let mut result = Vec::with_capacity(unsuccessful.len());
 
         for u in unsuccessful {
-            if let Some(wp) = self
-                .withdrawals
-                .remove(&(u.l_2_block_number.as_u64(), u.l_2_message_index.as_u64()))
-            {
-                result.push(wp);
+            if let Some(pos) = self.withdrawals.iter().position(|c| {
+                c.params.l1_batch_number.as_u64() == u.l_2_block_number.as_u64()
+                    && u64::from(c.params.l2_message_index) == u.l_2_message_index.as_u64()
+            }) {
+                result.push(self.withdrawals.remove(pos).params);
             }
         }
 
         result
     }
 
+    /// Remove the withdrawals matching the predicate.
+    pub fn remove_withdrawals(
+        &mut self,
+        mut predicate: impl FnMut(&FinalizationCandidate) -> bool,
+    ) -> Vec<FinalizationCandidate> {
+        let (removed, kept) = std::mem::take(&mut self.withdrawals)
+            .into_iter()
+            .partition(|c| predicate(c));
+        self.withdrawals = kept;
+
+        removed
+    }
+
     /// Create a new `WithdrawalsAccumulator`.
     pub fn new(
         gas_price: U256,
         tx_fee_limit: U256,
         batch_finalization_gas_limit: U256,
         one_withdrawal_gas_limit: U256,
+        target_batch_size: Option<usize>,
     ) -> Self {
         Self {
             gas_price,
             tx_fee_limit,
             batch_finalization_gas_limit,
             one_withdrawal_gas_limit,
-            withdrawals: BTreeMap::new(),
+            target_batch_size,
+            withdrawals: vec![],
         }
     }
 
     /// Add a finalization withdrawals request.
     ///
+    /// Withdrawals are finalized in the order they are added in.
+    ///
     /// # Argument
     ///
     /// * `request` A finalization request.
-    pub fn add_withdrawal(&mut self, data: WithdrawalParams) {
-        self.withdrawals.insert(
-            (data.l1_batch_number.as_u64(), data.l2_message_index.into()),
-            data,
-        );
+    pub fn add_withdrawal(&mut self, data: FinalizationCandidate) {
+        self.withdrawals.push(data);
+    }
+
+    /// Gas price the batch is going to be finalized with.
+    pub fn gas_price(&self) -> U256 {
+        self.gas_price
     }
 
     /// Get estimated gas consumption of the current set.
@@ -84,9 +116,110 @@ impl WithdrawalsAccumulator {
     }
 
     /// Is this batch of withdrawals ready to be finalized.
+    ///
+    /// A batch is ready once it reaches either of the gas and fee limits
+    /// or the target size if any.
     pub fn ready_to_finalize(&self) -> bool {
         let current_gas_usage = self.current_gas_usage();
         current_gas_usage >= self.batch_finalization_gas_limit
             || current_gas_usage * self.gas_price >= self.tx_fee_limit
+            || self
+                .target_batch_size
+                .is_some_and(|size| self.withdrawals.len() >= size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{Address, H256};
+    use pretty_assertions::assert_eq;
+
+    use client::ETH_TOKEN_ADDRESS;
+
+    use super::*;
+
+    fn candidate(id: u64, executed_l1_block: Option<u64>) -> FinalizationCandidate {
+        FinalizationCandidate {
+            params: WithdrawalParams {
+                chain_id: 324,
+                tx_hash: H256::random(),
+                event_index_in_tx: 0,
+                id,
+                l2_block_number: id,
+                l1_batch_number: id.into(),
+                l2_message_index: 0,
+                l2_tx_number_in_block: 0,
+                message: Default::default(),
+                sender: Address::random(),
+                proof: vec![],
+            },
+            below_threshold: false,
+            executed_l1_block,
+            l2_token: ETH_TOKEN_ADDRESS,
+            l1_token: None,
+            amount: U256::exp10(18),
+            decimals: 18,
+            requested: false,
+            urgent: false,
+        }
+    }
+
+    #[test]
+    fn ready_to_finalize_at_target_size() {
+        let mut accumulator =
+            WithdrawalsAccumulator::new(1.into(), U256::MAX, U256::MAX, 100.into(), Some(2));
+
+        accumulator.add_withdrawal(candidate(1, None));
+        assert!(!accumulator.ready_to_finalize());
+
+        accumulator.add_withdrawal(candidate(2, None));
+        assert!(accumulator.ready_to_finalize());
+    }
+
+    #[test]
+    fn ready_to_finalize_at_gas_and_fee_limits() {
+        let mut accumulator =
+            WithdrawalsAccumulator::new(1.into(), U256::MAX, 300.into(), 100.into(), None);
+
+        accumulator.add_withdrawal(candidate(1, None));
+        accumulator.add_withdrawal(candidate(2, None));
+        assert!(!accumulator.ready_to_finalize());
+
+        accumulator.add_withdrawal(candidate(3, None));
+        assert_eq!(accumulator.current_gas_usage(), 300.into());
+        assert!(accumulator.ready_to_finalize());
+
+        let mut accumulator =
+            WithdrawalsAccumulator::new(10.into(), 2_000.into(), U256::MAX, 100.into(), None);
+
+        accumulator.add_withdrawal(candidate(1, None));
+        assert!(!accumulator.ready_to_finalize());
+
+        accumulator.add_withdrawal(candidate(2, None));
+        assert!(accumulator.ready_to_finalize());
+    }
+
+    #[test]
+    fn tracks_oldest_executed_l1_block() {
+        let mut accumulator =
+            WithdrawalsAccumulator::new(1.into(), U256::MAX, U256::MAX, 100.into(), None);
+        assert_eq!(accumulator.oldest_executed_l1_block(), None);
+
+        accumulator.add_withdrawal(candidate(1, Some(20)));
+        accumulator.add_withdrawal(candidate(2, None));
+        accumulator.add_withdrawal(candidate(3, Some(10)));
+        assert_eq!(accumulator.oldest_executed_l1_block(), Some(10));
+
+        let removed = accumulator.remove_withdrawals(|c| c.params.id == 3);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(accumulator.oldest_executed_l1_block(), Some(20));
+        assert_eq!(
+            accumulator
+                .take_withdrawals()
+                .iter()
+                .map(|w| w.id)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
     }
 }
@@ -0,0 +1,404 @@
+//! Circuit breaker pausing finalization on anomalous finalized volumes.
+
+use std::{collections::HashMap, time::Duration};
+
+use ethers::types::Address;
+use sqlx::PgPool;
+use storage::{FinalizationCandidate, FinalizedVolume};
+
+use client::ETH_TOKEN_ADDRESS;
+
+use crate::{error::Result, metrics::FINALIZER_METRICS, pricing::to_f64};
+
+/// Pauses finalization of a token when the volume of its withdrawals finalized
+/// within a rolling window exceeds a limit or deviates sharply from its baseline,
+/// and of all tokens when the number of finalized withdrawals does.
+///
+/// The baseline is the average over the windows of the baseline period preceding
+/// the current window. Trips are recorded in the `circuit_breaker_trips` table and
+/// last until reset by an admin, volumes finalized before the reset are not counted.
+pub struct CircuitBreaker {
+    window: Duration,
+    baseline_windows: u32,
+    token_limits: HashMap<Address, f64>,
+    max_withdrawals: Option<u64>,
+    max_deviation: Option<f64>,
+}
+
+/// Volumes finalized recently together with the ones of the batches sent since
+/// and the tokens finalization of which is paused.
+pub(crate) struct Projection {
+    volumes: Vec<FinalizedVolume>,
+    paused: Vec<Option<Address>>,
+}
+
+impl Projection {
+    /// The tokens finalization of which is paused by their L1 addresses,
+    /// `None` if all of them are paused.
+    pub(crate) fn paused(&self) -> &[Option<Address>] {
+        &self.paused
+    }
+
+    // The volumes with the ones of the batch added.
+    fn with_batch(
+        &self,
+        base_token: Address,
+        batch: &[FinalizationCandidate],
+    ) -> Vec<FinalizedVolume> {
+        let mut volumes = self.volumes.clone();
+
+        for c in batch {
+            // Withdrawals of the base token are only known by its L2 address.
+            let l1_token = match c.l2_token == ETH_TOKEN_ADDRESS {
+                true => base_token,
+                false => c.l1_token.unwrap_or(c.l2_token),
+            };
+            let amount = to_f64(c.amount, c.decimals);
+
+            match volumes.iter_mut().find(|v| v.l1_token == l1_token) {
+                Some(v) => {
+                    v.volume += amount;
+                    v.withdrawals += 1;
+                }
+                None => volumes.push(FinalizedVolume {
+                    l1_token,
+                    volume: amount,
+                    withdrawals: 1,
+                    baseline_volume: 0.0,
+                    baseline_withdrawals: 0,
+                }),
+            }
+        }
+
+        volumes
+    }
+}
+
+impl CircuitBreaker {
+    /// Create a new [`CircuitBreaker`] with no limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `window`: The rolling window finalized volumes are tracked over.
+    /// * `baseline_windows`: The number of windows before the current one the baseline is averaged over.
+    pub fn new(window: Duration, baseline_windows: u32) -> Self {
+        Self {
+            window,
+            baseline_windows: baseline_windows.max(1),
+            token_limits: HashMap::new(),
+            max_withdrawals: None,
+            max_deviation: None,
+        }
+    }
+
+    /// Limit the volumes of tokens in units of the tokens by their L1 addresses.
+    pub fn with_token_limits(mut self, token_limits: Vec<(Address, f64)>) -> Self {
+        self.token_limits = token_limits.into_iter().collect();
+        self
+    }
+
+    /// Limit the number of withdrawals of all tokens.
+    pub fn with_max_withdrawals(mut self, max_withdrawals: u64) -> Self {
+        self.max_withdrawals = Some(max_withdrawals);
+        self
+    }
+
+    /// Limit volumes and the number of withdrawals to this multiple of their baselines.
+    pub fn with_max_deviation(mut self, max_deviation: f64) -> Self {
+        self.max_deviation = Some(max_deviation);
+        self
+    }
+
+    /// Trip on the volumes finalized recently and return them along with
+    /// the tokens finalization of which is paused.
+    pub(crate) async fn check(
+        &self,
+        pool: &PgPool,
+        chain_id: u64,
+        base_token: Address,
+        base_token_decimals: u32,
+    ) -> Result<Projection> {
+        let volumes = storage::finalized_volumes(
+            pool,
+            chain_id,
+            base_token,
+            base_token_decimals,
+            self.window,
+            self.window * self.baseline_windows,
+        )
+        .await?;
+
+        let mut projection = Projection {
+            paused: storage::active_circuit_breaker_trips(pool, chain_id).await?,
+            volumes,
+        };
+
+        self.trip(pool, chain_id, &mut projection.paused, &projection.volumes)
+            .await?;
+
+        Ok(projection)
+    }
+
+    /// Trip on the volumes projected to be finalized once the batch is finalized
+    /// and return whether the batch may be sent, counting it in the projection if so.
+    pub(crate) async fn check_batch(
+        &self,
+        pool: &PgPool,
+        chain_id: u64,
+        base_token: Address,
+        projection: &mut Projection,
+        batch: &[FinalizationCandidate],
+    ) -> Result<bool> {
+        let volumes = projection.with_batch(base_token, batch);
+
+        if self
+            .trip(pool, chain_id, &mut projection.paused, &volumes)
+            .await?
+        {
+            return Ok(false);
+        }
+
+        projection.volumes = volumes;
+
+        Ok(true)
+    }
+
+    // Record the trips on the volumes that are not active yet and return whether there are any.
+    async fn trip(
+        &self,
+        pool: &PgPool,
+        chain_id: u64,
+        active: &mut Vec<Option<Address>>,
+        volumes: &[FinalizedVolume],
+    ) -> Result<bool> {
+        let mut tripped = false;
+
+        for (l1_token, reason) in self.trips(volumes) {
+            if active.contains(&l1_token) {
+                continue;
+            }
+
+            tracing::error!(
+                "circuit breaker of {} of chain {chain_id} tripped: {reason}",
+                l1_token.map_or("all tokens".to_string(), |t| format!("{t:?}"))
+            );
+            FINALIZER_METRICS.circuit_breaker_trips.inc();
+
+            storage::trip_circuit_breaker(pool, chain_id, l1_token, &reason).await?;
+            active.push(l1_token);
+            tripped = true;
+        }
+
+        FINALIZER_METRICS.paused_by_circuit_breaker[&chain_id].set(active.len() as i64);
+
+        Ok(tripped)
+    }
+
+    fn trips(&self, volumes: &[FinalizedVolume]) -> Vec<(Option<Address>, String)> {
+        let baseline_windows = f64::from(self.baseline_windows);
+        let mut trips = vec![];
+
+        for v in volumes {
+            if let Some(limit) = self.token_limits.get(&v.l1_token) {
+                if v.volume > *limit {
+                    trips.push((
+                        Some(v.l1_token),
+                        format!("volume {} exceeds the limit {limit}", v.volume),
+                    ));
+                    continue;
+                }
+            }
+
+            let baseline = v.baseline_volume / baseline_windows;
+
+            if let Some(max_deviation) = self.max_deviation {
+                if baseline > 0.0 && v.volume > max_deviation * baseline {
+                    trips.push((
+                        Some(v.l1_token),
+                        format!("volume {} deviates from the baseline {baseline}", v.volume),
+                    ));
+                }
+            }
+        }
+
+        let withdrawals: u64 = volumes.iter().map(|v| v.withdrawals).sum();
+        let baseline =
+            volumes.iter().map(|v| v.baseline_withdrawals).sum::<u64>() as f64 / baseline_windows;
+
+        if let Some(max_withdrawals) = self.max_withdrawals {
+            if withdrawals > max_withdrawals {
+                trips.push((
+                    None,
+                    format!("{withdrawals} withdrawals exceed the limit {max_withdrawals}"),
+                ));
+                return trips;
+            }
+        }
+
+        if let Some(max_deviation) = self.max_deviation {
+            if baseline > 0.0 && withdrawals as f64 > max_deviation * baseline {
+                trips.push((
+                    None,
+                    format!("{withdrawals} withdrawals deviate from the baseline {baseline}"),
+                ));
+            }
+        }
+
+        trips
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{H256, U256};
+    use pretty_assertions::assert_eq;
+
+    use client::WithdrawalParams;
+
+    use super::*;
+
+    fn volume(l1_token: Address, volume: f64, withdrawals: u64) -> FinalizedVolume {
+        FinalizedVolume {
+            l1_token,
+            volume,
+            withdrawals,
+            baseline_volume: 0.0,
+            baseline_withdrawals: 0,
+        }
+    }
+
+    fn candidate(
+        l2_token: Address,
+        l1_token: Option<Address>,
+        amount: u64,
+    ) -> FinalizationCandidate {
+        FinalizationCandidate {
+            params: WithdrawalParams {
+                chain_id: 324,
+                tx_hash: H256::random(),
+                event_index_in_tx: 0,
+                id: 1,
+                l2_block_number: 1,
+                l1_batch_number: 1.into(),
+                l2_message_index: 0,
+                l2_tx_number_in_block: 0,
+                message: Default::default(),
+                sender: Address::random(),
+                proof: vec![],
+            },
+            below_threshold: false,
+            executed_l1_block: None,
+            l2_token,
+            l1_token,
+            amount: U256::from(amount) * U256::exp10(18),
+            decimals: 18,
+            requested: false,
+            urgent: false,
+        }
+    }
+
+    fn tripped(trips: Vec<(Option<Address>, String)>) -> Vec<Option<Address>> {
+        trips.into_iter().map(|(token, _)| token).collect()
+    }
+
+    #[test]
+    fn trips_on_token_limits() {
+        let token = Address::random();
+        let other_token = Address::random();
+        let circuit_breaker = CircuitBreaker::new(Duration::from_secs(3600), 7)
+            .with_token_limits(vec![(token, 100.0), (other_token, 100.0)]);
+
+        assert_eq!(
+            tripped(
+                circuit_breaker.trips(&[volume(token, 100.5, 1), volume(other_token, 100.0, 1)])
+            ),
+            vec![Some(token)]
+        );
+    }
+
+    #[test]
+    fn trips_on_deviations_from_baselines() {
+        let token = Address::random();
+        let new_token = Address::random();
+        let circuit_breaker =
+            CircuitBreaker::new(Duration::from_secs(3600), 2).with_max_deviation(3.0);
+
+        // The baseline of 20 over two windows is 10 per window.
+        let volumes = [
+            FinalizedVolume {
+                baseline_volume: 20.0,
+                baseline_withdrawals: 2,
+                ..volume(token, 31.0, 1)
+            },
+            // Tokens without a baseline do not trip on deviations.
+            volume(new_token, 1_000.0, 1),
+        ];
+        assert_eq!(tripped(circuit_breaker.trips(&volumes)), vec![Some(token)]);
+
+        let volumes = [FinalizedVolume {
+            baseline_volume: 20.0,
+            baseline_withdrawals: 2,
+            ..volume(token, 30.0, 4)
+        }];
+        assert_eq!(tripped(circuit_breaker.trips(&volumes)), vec![None]);
+    }
+
+    #[test]
+    fn trips_on_number_of_withdrawals() {
+        let circuit_breaker = CircuitBreaker::new(Duration::from_secs(3600), 7)
+            .with_max_withdrawals(10)
+            .with_max_deviation(2.0);
+
+        let volumes = [
+            volume(Address::random(), 1.0, 5),
+            volume(Address::random(), 1.0, 5),
+        ];
+        assert_eq!(tripped(circuit_breaker.trips(&volumes)), vec![]);
+
+        let volumes = [
+            volume(Address::random(), 1.0, 5),
+            volume(Address::random(), 1.0, 6),
+        ];
+        assert_eq!(tripped(circuit_breaker.trips(&volumes)), vec![None]);
+    }
+
+    #[test]
+    fn projects_volumes_of_batches() {
+        let base_token = Address::random();
+        let token = Address::random();
+        let unknown_token = Address::random();
+        let projection = Projection {
+            volumes: vec![volume(base_token, 1.0, 1)],
+            paused: vec![],
+        };
+
+        let volumes = projection.with_batch(
+            base_token,
+            &[
+                candidate(ETH_TOKEN_ADDRESS, None, 2),
+                candidate(Address::random(), Some(token), 3),
+                candidate(unknown_token, None, 4),
+            ],
+        );
+
+        assert_eq!(
+            volumes
+                .iter()
+                .map(|v| (v.l1_token, v.volume, v.withdrawals))
+                .collect::<Vec<_>>(),
+            vec![
+                (base_token, 3.0, 2),
+                (token, 3.0, 1),
+                (unknown_token, 4.0, 1),
+            ]
+        );
+
+        // A batch that would exceed a limit trips the circuit breaker.
+        let circuit_breaker = CircuitBreaker::new(Duration::from_secs(3600), 7)
+            .with_token_limits(vec![(base_token, 2.0)]);
+        assert_eq!(
+            tripped(circuit_breaker.trips(&volumes)),
+            vec![Some(base_token)]
+        );
+    }
+}
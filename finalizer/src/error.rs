@@ -19,6 +19,12 @@ pub enum Error {
 
     #[error("withdrawal transaction was reverted")]
     WithdrawalTransactionReverted,
+
+    #[error("failed to load token prices {0}")]
+    Prices(String),
+
+    #[error(transparent)]
+    Join(#[from] tokio::task::JoinError),
 }
 
 impl<M: Middleware> From<ContractError<M>> for Error {
@@ -5,35 +5,53 @@
 
 //! Finalization logic implementation.
 
-use std::{collections::HashSet, str::FromStr, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
 use accumulator::WithdrawalsAccumulator;
 use ethers::{
     abi::Address,
+    contract::ContractCall,
     providers::{Middleware, MiddlewareError},
-    types::{H256, U256},
+    types::{BlockNumber, TransactionReceipt, H256, U256},
 };
-use futures::TryFutureExt;
+use futures::{stream::FuturesUnordered, StreamExt, TryFutureExt};
 use sqlx::PgPool;
-
-use client::{
-    is_eth, withdrawal_finalizer::codegen::withdrawal_finalizer::Result as FinalizeResult,
-    WithdrawalKey,
+use storage::{
+    FinalizationCandidate, FinalizationCandidates, FinalizationCriteria, FinalizationRequestStatus,
+    PendingTransactionOutcome, StoredWithdrawal, TokenThreshold,
 };
+
 use client::{
-    l1bridge::codegen::IL1Bridge, withdrawal_finalizer::codegen::WithdrawalFinalizer,
-    zksync_contract::codegen::IZkSync, WithdrawalParams, ZksyncMiddleware,
+    withdrawal_finalizer::codegen::{
+        withdrawal_finalizer::Result as FinalizeResult, WithdrawalFinalizer,
+    },
+    BaseToken, FinalizationContracts, WithdrawalKey, ETH_TOKEN_ADDRESS,
 };
+use client::{WithdrawalParams, ZksyncMiddleware};
+use tx_sender::TxOutcome;
 use withdrawals_meterer::{MeteringComponent, WithdrawalsMeter};
 
 use crate::{
     error::{Error, Result},
-    metrics::FINALIZER_METRICS,
+    metrics::{TokenLabels, FINALIZER_METRICS},
 };
 
 mod accumulator;
+mod circuit_breaker;
 mod error;
 mod metrics;
+mod pricing;
+mod signer_pool;
+
+pub use circuit_breaker::CircuitBreaker;
+pub use pricing::{PriceSource, ProfitabilityPolicy};
+pub use signer_pool::{SignerLease, SignerPool};
 
 /// A limit to cap a transaction fee (in ether) for safety reasons.
 const TX_FEE_LIMIT: f64 = 0.8;
@@ -47,6 +65,32 @@ const LOOP_ITERATION_ERROR_BACKOFF: Duration = Duration::from_secs(5);
 /// Interval between successful loop iterations.
 const LOOP_ITERATION_OK_INTERVAL: Duration = Duration::from_secs(1);
 
+/// Time between L1 blocks.
+const L1_BLOCK_TIME: Duration = Duration::from_secs(12);
+
+/// Margin of the estimated gas of finalizing a withdrawal directly in percent.
+const DIRECT_FINALIZATION_GAS_MARGIN_PERCENT: u64 = 120;
+
+/// Gas every transaction is charged with regardless of its calldata and execution.
+const TX_BASE_GAS: u64 = 21_000;
+
+/// Gas a transaction is charged with for a zero byte of its calldata.
+const TX_ZERO_BYTE_GAS: u64 = 4;
+
+/// Gas a transaction is charged with for a non-zero byte of its calldata.
+const TX_NON_ZERO_BYTE_GAS: u64 = 16;
+
+/// A batch of withdrawals sent for finalization.
+struct SentBatch<E> {
+    signer: Address,
+    withdrawals: Vec<WithdrawalParams>,
+    // Gas predicted to be spent on each of the withdrawals.
+    withdrawals_gas: Vec<U256>,
+    // ID of the pending transaction in storage.
+    pending_tx: u64,
+    tx: std::result::Result<TxOutcome, E>,
+}
+
 /// A newtype that represents a set of addresses in JSON format.
 #[derive(Debug, Eq, PartialEq)]
 pub struct AddrList(pub Vec<Address>);
@@ -63,21 +107,35 @@ impl FromStr for AddrList {
 /// Finalizer.
 pub struct Finalizer<M1, M2> {
     pgpool: PgPool,
+    chain_id: u64,
     one_withdrawal_gas_limit: U256,
     batch_finalization_gas_limit: U256,
-    finalizer_contract: WithdrawalFinalizer<M1>,
-    zksync_contract: IZkSync<M2>,
-    l1_bridge: IL1Bridge<M2>,
+    signers: Arc<SignerPool<M1>>,
+    contracts: FinalizationContracts<M2>,
+    finalizer_contract: Option<Address>,
+    max_in_flight_txs: usize,
     unsuccessful: Vec<WithdrawalParams>,
 
     no_new_withdrawals_backoff: Duration,
     query_db_pagination_limit: u64,
     tx_fee_limit: U256,
     tx_retry_timeout: Duration,
-    account_address: Address,
     withdrawals_meterer: Option<WithdrawalsMeter>,
-    eth_threshold: Option<U256>,
+    base_token_decimals: u32,
+    base_token_threshold: Option<U256>,
+    token_thresholds: Vec<TokenThreshold>,
+    priority_l1_recipients: Vec<Address>,
+    below_threshold_finalization_age: Option<Duration>,
+    below_threshold_max_gas_price: Option<U256>,
     only_l1_recipients: Option<Vec<Address>>,
+    max_base_fee: Option<U256>,
+    finalization_deadline: Option<Duration>,
+    finalization_deadline_margin: Duration,
+    batching_window: Option<(Option<usize>, Duration)>,
+    base_token_l1_address: Address,
+    profitability_policy: Option<ProfitabilityPolicy>,
+    circuit_breaker: Option<CircuitBreaker>,
+    approval_thresholds: Option<(Option<U256>, Vec<TokenThreshold>)>,
 }
 
 const NO_NEW_WITHDRAWALS_BACKOFF: Duration = Duration::from_secs(5);
@@ -93,60 +151,193 @@ where
     /// * `S` is expected to be a [`Middleware`] instance equipped with [`SignerMiddleware`]
     /// * `M` is expected to be an ordinary read-only middleware to read information from L1.
     ///
+    /// Withdrawals are finalized one by one directly on the `contracts` unless
+    /// the finalizer contract is set with [`Finalizer::with_finalizer_contract`].
+    ///
+    /// Finalizers of different chains sending transactions from the same account
+    /// have to share the `signers` to not send them with the same nonce.
+    ///
     /// [`SignerMiddleware`]: https://docs.rs/ethers/latest/ethers/middleware/struct.SignerMiddleware.html
     /// [`Middleware`]: https://docs.rs/ethers/latest/ethers/providers/trait.Middleware.html
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         pgpool: PgPool,
+        chain_id: u64,
         one_withdrawal_gas_limit: U256,
         batch_finalization_gas_limit: U256,
-        finalizer_contract: WithdrawalFinalizer<S>,
-        zksync_contract: IZkSync<M>,
-        l1_bridge: IL1Bridge<M>,
+        signers: Arc<SignerPool<S>>,
+        contracts: FinalizationContracts<M>,
         tx_retry_timeout: usize,
-        account_address: Address,
         meter_withdrawals: bool,
-        eth_threshold: Option<U256>,
+        base_token: BaseToken,
+        base_token_threshold: Option<U256>,
+        token_thresholds: Vec<TokenThreshold>,
         only_l1_recipients: Option<Vec<Address>>,
     ) -> Self {
+        let base_token_decimals = base_token.decimals;
+        let base_token_l1_address = base_token.l1_address;
         let withdrawals_meterer = meter_withdrawals.then_some(WithdrawalsMeter::new(
             pgpool.clone(),
             MeteringComponent::FinalizedWithdrawals,
+            base_token,
         ));
         let tx_fee_limit = ethers::utils::parse_ether(TX_FEE_LIMIT)
             .expect("{TX_FEE_LIMIT} ether is a parsable amount; qed");
 
         Self {
             pgpool,
+            chain_id,
             one_withdrawal_gas_limit,
             batch_finalization_gas_limit,
-            finalizer_contract,
-            zksync_contract,
-            l1_bridge,
+            signers,
+            contracts,
+            finalizer_contract: None,
+            max_in_flight_txs: 1,
             unsuccessful: vec![],
             no_new_withdrawals_backoff: NO_NEW_WITHDRAWALS_BACKOFF,
             query_db_pagination_limit: QUERY_DB_PAGINATION_LIMIT,
             tx_fee_limit,
             tx_retry_timeout: Duration::from_secs(tx_retry_timeout as u64),
-            account_address,
             withdrawals_meterer,
-            eth_threshold,
+            base_token_decimals,
+            base_token_threshold,
+            token_thresholds,
+            priority_l1_recipients: vec![],
+            below_threshold_finalization_age: None,
+            below_threshold_max_gas_price: None,
             only_l1_recipients,
+            max_base_fee: None,
+            finalization_deadline: None,
+            finalization_deadline_margin: Duration::ZERO,
+            batching_window: None,
+            base_token_l1_address,
+            profitability_policy: None,
+            circuit_breaker: None,
+            approval_thresholds: None,
         }
     }
 
+    /// Finalize withdrawals in batches through the finalizer contract at `address`.
+    ///
+    /// Batches of a single withdrawal are still finalized directly on the contracts
+    /// of the chain if that takes less gas.
+    pub fn with_finalizer_contract(mut self, address: Address) -> Self {
+        self.finalizer_contract = Some(address);
+        self
+    }
+
+    /// Finalize withdrawals below thresholds once they have been executed `age` ago.
+    ///
+    /// Such withdrawals only take the spare capacity of batches of other withdrawals
+    /// unless the gas price is at most `max_gas_price`.
+    pub fn with_below_threshold_finalization(
+        mut self,
+        age: Duration,
+        max_gas_price: Option<U256>,
+    ) -> Self {
+        self.below_threshold_finalization_age = Some(age);
+        self.below_threshold_max_gas_price = max_gas_price;
+        self
+    }
+
+    /// Prioritize finalization of withdrawals to these L1 recipients.
+    pub fn with_priority_l1_recipients(mut self, priority_l1_recipients: Vec<Address>) -> Self {
+        self.priority_l1_recipients = priority_l1_recipients;
+        self
+    }
+
+    /// Defer finalization while the L1 base fee is above `max_base_fee`.
+    pub fn with_max_base_fee(mut self, max_base_fee: U256) -> Self {
+        self.max_base_fee = Some(max_base_fee);
+        self
+    }
+
+    /// Finalize withdrawals within `deadline` since their execution.
+    ///
+    /// Withdrawals deferred because of the L1 base fee are finalized
+    /// regardless of it once they are within `margin` of their deadline.
+    pub fn with_finalization_deadline(mut self, deadline: Duration, margin: Duration) -> Self {
+        self.finalization_deadline = Some(deadline);
+        self.finalization_deadline_margin = margin;
+        self
+    }
+
+    /// Accumulate withdrawals into batches of `target_size` unless
+    /// the oldest of them has been waiting for finalization for `window`.
+    ///
+    /// The batches are still capped by the gas and fee limits and
+    /// are only limited by them if no `target_size` is given.
+    pub fn with_batching_window(mut self, target_size: Option<usize>, window: Duration) -> Self {
+        self.batching_window = Some((target_size, window));
+        self
+    }
+
+    /// Have up to `max_in_flight_txs` finalization transactions in flight
+    /// instead of waiting for each of them to be mined before sending the next one.
+    pub fn with_max_in_flight_txs(mut self, max_in_flight_txs: usize) -> Self {
+        self.max_in_flight_txs = max_in_flight_txs.max(1);
+        self
+    }
+
+    /// Skip withdrawals according to the [`ProfitabilityPolicy`].
+    ///
+    /// The fee of finalizing a withdrawal is estimated from the gas predicted for it
+    /// by the finalizer contract and the current gas price.
+    pub fn with_profitability_policy(mut self, policy: ProfitabilityPolicy) -> Self {
+        self.profitability_policy = Some(policy);
+        self
+    }
+
+    /// Pause finalization on anomalous volumes with the [`CircuitBreaker`].
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Only finalize withdrawals of the base token of at least `base_token_threshold`
+    /// and of other tokens of at least their `token_thresholds` once approved by an operator.
+    pub fn with_approval_thresholds(
+        mut self,
+        base_token_threshold: Option<U256>,
+        token_thresholds: Vec<TokenThreshold>,
+    ) -> Self {
+        self.approval_thresholds = Some((base_token_threshold, token_thresholds));
+        self
+    }
+
     /// [`Finalizer`] main loop.
     ///
     /// `M2` is expected to be an [`ZksyncMiddleware`] to connect to L2.
     pub async fn run<M2>(self, middleware: M2) -> Result<()>
     where
-        M2: ZksyncMiddleware + 'static,
+        M2: ZksyncMiddleware + Clone + 'static,
     {
+        // Transactions of a previous run are no longer waited for, their withdrawals
+        // are found out to be finalized once they are predicted to fail.
+        let abandoned =
+            storage::abandon_pending_finalization_transactions(&self.pgpool, self.chain_id).await?;
+        for tx in abandoned {
+            tracing::warn!(
+                "abandoned finalization transaction {} of withdrawals {:?} sent from {:?} {}s ago",
+                tx.id,
+                tx.withdrawal_ids,
+                tx.account,
+                tx.pending_secs
+            );
+        }
+
         let params_fetcher_handle = tokio::spawn(params_fetcher_loop(
             self.pgpool.clone(),
+            self.chain_id,
+            middleware.clone(),
+            self.contracts.clone(),
+        ));
+
+        let requests_handle = tokio::spawn(finalization_requests_loop(
+            self.pgpool.clone(),
+            self.chain_id,
             middleware,
-            self.zksync_contract.clone(),
-            self.l1_bridge.clone(),
+            self.contracts.clone(),
         ));
 
         let finalizer_handle = tokio::spawn(self.finalizer_loop());
@@ -155,6 +346,9 @@ where
             m = params_fetcher_handle => {
                 tracing::error!("migrator ended with {m:?}");
             }
+            r = requests_handle => {
+                tracing::error!("finalization requests loop ended with {r:?}");
+            }
             f = finalizer_handle => {
                 tracing::error!("finalizer ended with {f:?}");
             }
@@ -163,68 +357,283 @@ where
         Ok(())
     }
 
+    // A call to the finalizer contract at `address` finalizing a set of withdrawals
+    // through the contracts of this chain.
+    fn finalize_withdrawals_call<'a, W: Iterator<Item = &'a WithdrawalParams>>(
+        &self,
+        address: Address,
+        withdrawals: W,
+    ) -> ContractCall<S, Vec<FinalizeResult>> {
+        let withdrawals = withdrawals.cloned();
+        let contract = WithdrawalFinalizer::new(address, self.signers.client());
+
+        match self.contracts {
+            FinalizationContracts::Legacy { .. } => contract.finalize_withdrawals(
+                withdrawals
+                    .map(|r| r.into_request_with_gaslimit(self.one_withdrawal_gas_limit))
+                    .collect(),
+            ),
+            FinalizationContracts::SharedBridge { .. } => contract
+                .finalize_withdrawals_shared_bridge(
+                    withdrawals
+                        .map(|r| {
+                            r.into_shared_bridge_request_with_gaslimit(
+                                self.one_withdrawal_gas_limit,
+                            )
+                        })
+                        .collect(),
+                ),
+        }
+    }
+
+    // Returns the withdrawals predicted to fail and the predicted results of the rest of them.
     async fn predict_fails<'a, W: Iterator<Item = &'a WithdrawalParams>>(
         &mut self,
         withdrawals: W,
-    ) -> Result<Vec<FinalizeResult>> {
-        let w: Vec<_> = withdrawals
-            .cloned()
-            .map(|r| r.into_request_with_gaslimit(self.one_withdrawal_gas_limit))
-            .collect();
+    ) -> Result<(Vec<FinalizeResult>, Vec<FinalizeResult>)> {
+        let (fails, successes): (Vec<_>, Vec<_>) = match self.finalizer_contract {
+            Some(address) => {
+                let results = self
+                    .finalize_withdrawals_call(address, withdrawals)
+                    .call()
+                    .await?;
+                tracing::info!("predicted results for withdrawals: {results:?}");
 
-        let results = self
-            .finalizer_contract
-            .finalize_withdrawals(w)
-            .call()
-            .await?;
-        tracing::info!("predicted results for withdrawals: {results:?}");
+                results
+                    .into_iter()
+                    .partition(|p| !p.success || p.gas > self.one_withdrawal_gas_limit)
+            }
+            None => {
+                let results = self.predict_direct(withdrawals).await?;
+                tracing::info!("predicted results for withdrawals: {results:?}");
 
-        Ok(results
-            .into_iter()
-            .filter(|p| !p.success || p.gas > self.one_withdrawal_gas_limit)
-            .collect())
+                results.into_iter().partition(|p| !p.success)
+            }
+        };
+
+        Ok((fails, successes))
     }
 
-    async fn finalize_batch(&mut self, withdrawals: Vec<WithdrawalParams>) -> Result<()> {
-        let Some(highest_batch_number) = withdrawals.iter().map(|w| w.l1_batch_number).max() else {
-            return Ok(());
+    // Predict the results of finalizing withdrawals directly on the contracts of the chain
+    // by estimating the gas of finalizing each of them.
+    //
+    // The estimates are of whole transactions, so withdrawals are predicted to fail
+    // if they take more than the gas limit of a withdrawal on top of the intrinsic gas
+    // of the transaction.
+    async fn predict_direct<'a, W: Iterator<Item = &'a WithdrawalParams>>(
+        &self,
+        withdrawals: W,
+    ) -> Result<Vec<FinalizeResult>> {
+        let mut results = vec![];
+
+        for w in withdrawals {
+            let call = self.contracts.finalize_withdrawal(w);
+            let gas_limit = self.one_withdrawal_gas_limit
+                + intrinsic_gas(call.tx.data().map_or(&[][..], |d| d.as_ref()));
+
+            let (gas, success) = match call.estimate_gas().await {
+                Ok(gas) => (gas, gas <= gas_limit),
+                Err(e) if e.is_revert() => (U256::zero(), false),
+                Err(e) => return Err(e.into()),
+            };
+
+            results.push(FinalizeResult {
+                l_2_block_number: w.l1_batch_number.as_u64().into(),
+                l_2_message_index: w.l2_message_index.into(),
+                gas,
+                success,
+            });
+        }
+
+        Ok(results)
+    }
+
+    // Gas limit to finalize a batch of withdrawals directly on the contracts of the chain with,
+    // `None` if it is finalized through the finalizer contract.
+    //
+    // Without the finalizer contract batches are of a single withdrawal and always finalized
+    // directly, with it a batch of a single withdrawal is finalized directly if that is cheaper
+    // than finalizing it through the finalizer contract.
+    //
+    // `withdrawals_gas` is the gas predicted for the withdrawals, without the finalizer
+    // contract it is the estimate of finalizing the withdrawal directly and is not estimated again.
+    async fn direct_gas_limit(
+        &self,
+        withdrawals: &[WithdrawalParams],
+        withdrawals_gas: &[U256],
+    ) -> Result<Option<U256>> {
+        let [withdrawal] = withdrawals else {
+            return Ok(None);
+        };
+
+        let predicted_gas = match (self.finalizer_contract, withdrawals_gas) {
+            (None, [gas]) if !gas.is_zero() => Some(*gas),
+            _ => None,
         };
 
+        let estimate = match predicted_gas {
+            Some(gas) => Ok(gas),
+            None => {
+                self.contracts
+                    .finalize_withdrawal(withdrawal)
+                    .estimate_gas()
+                    .await
+            }
+        };
+
+        let direct_gas = match estimate {
+            Ok(gas) => gas,
+            Err(e) if self.finalizer_contract.is_some() => {
+                tracing::debug!(
+                    "failed to estimate finalizing {} directly: {e}",
+                    withdrawal.id
+                );
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if let Some(address) = self.finalizer_contract {
+            let batch_gas = self
+                .finalize_withdrawals_call(address, withdrawals.iter())
+                .estimate_gas()
+                .await?;
+
+            if batch_gas <= direct_gas {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(
+            direct_gas * DIRECT_FINALIZATION_GAS_MARGIN_PERCENT / 100,
+        ))
+    }
+
+    // Send a batch of withdrawals for finalization, the returned future
+    // resolves once the transaction is mined and does not borrow the finalizer
+    // so that several batches may be in flight.
+    //
+    // `withdrawals_gas` is the gas predicted to be spent on each of the withdrawals
+    // themselves, the rest of the gas used by the transaction is its overhead.
+    //
+    // The batch is finalized directly on the contracts of the chain with
+    // `direct_gas_limit` if any, otherwise through the finalizer contract.
+    //
+    // The transaction is cancelled while pending if all of the withdrawals get
+    // finalized by someone else or an operator requests its cancellation.
+    fn send_batch(
+        &self,
+        signer: SignerLease<S>,
+        withdrawals: Vec<WithdrawalParams>,
+        withdrawals_gas: Vec<U256>,
+        direct_gas_limit: Option<U256>,
+        pending_tx: u64,
+    ) -> impl Future<Output = SentBatch<S::Error>> + Send + 'static {
         tracing::info!(
-            "finalizing batch {:?}",
-            withdrawals.iter().map(|w| w.id).collect::<Vec<_>>()
+            "finalizing batch {:?} from {:?}{}",
+            withdrawals.iter().map(|w| w.id).collect::<Vec<_>>(),
+            signer.address(),
+            if direct_gas_limit.is_some() {
+                " directly"
+            } else {
+                ""
+            },
         );
 
-        let w: Vec<_> = withdrawals
-            .iter()
-            .cloned()
-            .map(|r| r.into_request_with_gaslimit(self.one_withdrawal_gas_limit))
-            .collect();
-
-        let tx = self.finalizer_contract.finalize_withdrawals(w);
-        let nonce = self
-            .finalizer_contract
-            .client()
-            .get_transaction_count(self.account_address, None)
-            .await
-            .map_err(|e| Error::Middleware(format!("{e}")))?;
+        let (tx, gas_limit) = match (direct_gas_limit, self.finalizer_contract) {
+            (Some(gas_limit), _) => {
+                FINALIZER_METRICS.direct_finalization_transactions.inc();
+                (
+                    self.contracts.finalize_withdrawal(&withdrawals[0]).tx,
+                    gas_limit,
+                )
+            }
+            (None, Some(address)) => (
+                self.finalize_withdrawals_call(address, withdrawals.iter())
+                    .tx,
+                self.batch_finalization_gas_limit,
+            ),
+            (None, None) => unreachable!(
+                "withdrawals are finalized directly without the finalizer contract; qed"
+            ),
+        };
+        let tx_retry_timeout = self.tx_retry_timeout;
+        let pgpool = self.pgpool.clone();
+        let contracts = self.contracts.clone();
+
+        async move {
+            let tx = tx_sender::send_tx_with_nonce_manager_cancellable(
+                signer.client(),
+                tx,
+                tx_retry_timeout,
+                signer.nonce_manager(),
+                gas_limit,
+                || should_cancel(&pgpool, pending_tx, &withdrawals, &contracts),
+            )
+            .await;
+
+            SentBatch {
+                signer: signer.address(),
+                withdrawals,
+                withdrawals_gas,
+                pending_tx,
+                tx,
+            }
+        }
+    }
 
-        let tx = tx_sender::send_tx_adjust_gas(
-            self.finalizer_contract.client(),
-            tx.tx.clone(),
-            self.tx_retry_timeout,
-            nonce,
-            self.batch_finalization_gas_limit,
+    async fn process_sent_batch(&mut self, batch: SentBatch<S::Error>) -> Result<()> {
+        let SentBatch {
+            signer,
+            withdrawals,
+            withdrawals_gas,
+            pending_tx,
+            tx,
+        } = batch;
+
+        let (outcome, cancelled_in_tx) = match tx {
+            Ok(TxOutcome::Cancelled(ref tx)) => (
+                PendingTransactionOutcome::Cancelled,
+                Some(tx.transaction_hash),
+            ),
+            Ok(TxOutcome::Mined(ref tx)) if tx.status.is_some_and(|s| s.is_zero()) => {
+                (PendingTransactionOutcome::Reverted, None)
+            }
+            Ok(TxOutcome::Mined(_)) => (PendingTransactionOutcome::Mined, None),
+            Err(_) => (PendingTransactionOutcome::Failed, None),
+        };
+        storage::resolve_pending_finalization_transaction(
+            &self.pgpool,
+            pending_tx,
+            outcome,
+            cancelled_in_tx,
         )
-        .await;
+        .await?;
+
+        let highest_batch_number = withdrawals
+            .iter()
+            .map(|w| w.l1_batch_number)
+            .max()
+            .unwrap_or_default();
 
         let ids: Vec<_> = withdrawals.iter().map(|w| w.id as i64).collect();
+        let batch_size = withdrawals.len();
 
         // Turn actual withdrawals into info to update db with.
         let withdrawals = withdrawals.into_iter().map(|w| w.key()).collect::<Vec<_>>();
 
         match tx {
-            Ok(Some(tx)) if tx.status.expect("EIP-658 is enabled; qed").is_zero() => {
+            Ok(TxOutcome::Cancelled(tx)) => {
+                tracing::info!(
+                    "withdrawal transaction of batch {ids:?} cancelled in {:?}",
+                    tx.transaction_hash
+                );
+
+                // Withdrawals finalized by someone else are found out once
+                // they are predicted to fail in the next batches.
+                self.record_finalization_cost(&tx, signer, &[], &[]).await?;
+            }
+            Ok(TxOutcome::Mined(tx)) if tx.status.expect("EIP-658 is enabled; qed").is_zero() => {
                 tracing::error!(
                     "withdrawal transaction {:?} was reverted",
                     tx.transaction_hash
@@ -232,11 +641,15 @@ where
 
                 FINALIZER_METRICS.reverted_withdrawal_transactions.inc();
 
+                // Reverted transactions are paid for all the same.
+                self.record_finalization_cost(&tx, signer, &ids, &withdrawals_gas)
+                    .await?;
+
                 storage::inc_unsuccessful_finalization_attempts(&self.pgpool, &withdrawals).await?;
 
                 return Err(Error::WithdrawalTransactionReverted);
             }
-            Ok(Some(tx)) => {
+            Ok(TxOutcome::Mined(tx)) => {
                 tracing::info!(
                     "withdrawal transaction {:?} successfully mined",
                     tx.transaction_hash
@@ -249,20 +662,30 @@ where
                 )
                 .await?;
 
-                FINALIZER_METRICS
-                    .highest_finalized_batch_number
+                FINALIZER_METRICS.highest_finalized_batch_number[&self.chain_id]
                     .set(highest_batch_number.as_u64() as i64);
 
+                FINALIZER_METRICS.batch_size.observe(batch_size);
+
+                if let Some(gas_used) = tx.gas_used {
+                    let predicted_gas = withdrawals_gas
+                        .iter()
+                        .fold(U256::zero(), |gas, g| gas.saturating_add(*g));
+                    let overhead = gas_used.saturating_sub(predicted_gas) / batch_size;
+                    FINALIZER_METRICS
+                        .overhead_gas_per_withdrawal
+                        .observe(overhead.as_u64());
+
+                    self.record_finalization_cost(&tx, signer, &ids, &withdrawals_gas)
+                        .await?;
+                }
+
                 if let Some(ref mut withdrawals_meterer) = self.withdrawals_meterer {
                     if let Err(e) = withdrawals_meterer.meter_withdrawals_storage(&ids).await {
                         tracing::error!("Failed to meter the withdrawals: {e}");
                     }
                 }
             }
-            // TODO: why would a pending tx resolve to `None`?
-            Ok(None) => {
-                tracing::warn!("sent transaction resolved with none result",);
-            }
             Err(e) => {
                 tracing::error!(
                     "waiting for transaction status withdrawals failed with an error {:?}",
@@ -276,6 +699,7 @@ where
                         .await?;
                 } else {
                     tracing::error!("failed to send finalization withdrawal tx: {e}");
+                    self.signers.mark_unfunded(signer);
                     FINALIZER_METRICS
                         .failed_to_finalize_low_gas
                         .inc_by(withdrawals.len() as u64);
@@ -291,10 +715,60 @@ where
         Ok(())
     }
 
+    // Record what a finalization transaction has cost apportioning it to the withdrawals
+    // by the gas predicted to be spent on them.
+    async fn record_finalization_cost(
+        &self,
+        tx: &TransactionReceipt,
+        account: Address,
+        ids: &[i64],
+        withdrawals_gas: &[U256],
+    ) -> Result<()> {
+        let gas_used = tx.gas_used.unwrap_or_default();
+        let effective_gas_price = tx.effective_gas_price.unwrap_or_default();
+        let fee = gas_used.saturating_mul(effective_gas_price);
+
+        self.signers.record_spent(account, fee);
+
+        let costs: Vec<_> = ids
+            .iter()
+            .zip(apportion(gas_used, withdrawals_gas))
+            .zip(apportion(fee, withdrawals_gas))
+            .map(|((id, gas), fee)| storage::WithdrawalFinalizationCost {
+                withdrawal_id: *id as u64,
+                gas,
+                fee,
+            })
+            .collect();
+
+        let transaction = storage::FinalizationTransaction {
+            chain_id: self.chain_id,
+            tx_hash: tx.transaction_hash,
+            account,
+            gas_used,
+            effective_gas_price,
+            batch_size: ids.len(),
+        };
+
+        let fees_by_token =
+            storage::add_finalization_transaction(&self.pgpool, &transaction, &costs).await?;
+
+        for (token, fee) in fees_by_token {
+            let labels = TokenLabels {
+                chain_id: self.chain_id,
+                token: format!("{token:?}"),
+            };
+            FINALIZER_METRICS.finalization_fees_gwei[&labels]
+                .inc_by((fee / U256::exp10(9)).as_u64());
+        }
+
+        Ok(())
+    }
+
     // Create a new withdrawal accumulator given the current gas price.
     async fn new_accumulator(&self) -> Result<WithdrawalsAccumulator> {
         let gas_price = self
-            .finalizer_contract
+            .signers
             .client()
             .get_gas_price()
             .await
@@ -305,6 +779,13 @@ where
             self.tx_fee_limit,
             self.batch_finalization_gas_limit,
             self.one_withdrawal_gas_limit,
+            // Without the finalizer contract withdrawals are finalized one by one.
+            match self.finalizer_contract {
+                Some(_) => self
+                    .batching_window
+                    .and_then(|(target_size, _)| target_size),
+                None => Some(1),
+            },
         ))
     }
 
@@ -321,17 +802,257 @@ where
         }
     }
 
+    // The number and the base fee of the latest L1 block.
+    async fn latest_l1_block(&self) -> Result<(u64, Option<U256>)> {
+        let block = self
+            .signers
+            .client()
+            .get_block(BlockNumber::Latest)
+            .await
+            .map_err(|e| Error::Middleware(format!("{e}")))?
+            .ok_or_else(|| Error::Middleware("latest block is not available".to_string()))?;
+
+        let number = block
+            .number
+            .ok_or_else(|| Error::Middleware("latest block has no number".to_string()))?;
+
+        Ok((number.as_u64(), block.base_fee_per_gas))
+    }
+
+    // Criteria of withdrawals to finalize as of the current L1 block.
+    fn finalization_criteria(&self, current_l1_block: u64) -> FinalizationCriteria {
+        // The last L1 block withdrawals below thresholds executed in are finalized anyway.
+        let below_threshold_executed_up_to = self
+            .below_threshold_finalization_age
+            .map(|age| current_l1_block.saturating_sub(age.as_secs() / L1_BLOCK_TIME.as_secs()));
+
+        let (approval_base_token_threshold, approval_token_thresholds) =
+            match self.approval_thresholds.as_ref() {
+                Some((base_token_threshold, token_thresholds)) => {
+                    (*base_token_threshold, token_thresholds.clone())
+                }
+                None => (None, vec![]),
+            };
+
+        // Withdrawals executed in this L1 block or earlier are close to their deadlines.
+        let urgent_executed_up_to = self.finalization_deadline.map(|deadline| {
+            let urgent_after_blocks = deadline
+                .saturating_sub(self.finalization_deadline_margin)
+                .as_secs()
+                / L1_BLOCK_TIME.as_secs();
+
+            current_l1_block.saturating_sub(urgent_after_blocks)
+        });
+
+        FinalizationCriteria {
+            base_token_threshold: self.base_token_threshold,
+            base_token_decimals: self.base_token_decimals,
+            token_thresholds: self.token_thresholds.clone(),
+            below_threshold_executed_up_to,
+            current_l1_block,
+            l1_block_time: L1_BLOCK_TIME,
+            priority_l1_recipients: self.priority_l1_recipients.clone(),
+            only_l1_recipients: self.only_l1_recipients.clone(),
+            paused_tokens: vec![],
+            paused_l1_recipients: vec![],
+            paused_token_l1_recipients: vec![],
+            approval_base_token_threshold,
+            approval_token_thresholds,
+            urgent_executed_up_to,
+            unprofitable_at: None,
+        }
+    }
+
+    // Withdrawals close to their deadlines.
+    fn urgent(candidates: Vec<FinalizationCandidate>) -> Vec<FinalizationCandidate> {
+        candidates.into_iter().filter(|c| c.urgent).collect()
+    }
+
+    // Withdrawals to finalize at the current base fee and with the gas budgets left:
+    // all of them unless the base fee is above the ceiling or the budgets are spent,
+    // in which case only the ones close to their deadlines.
+    fn schedule(
+        &self,
+        candidates: Vec<FinalizationCandidate>,
+        base_fee: Option<U256>,
+        within_budget: bool,
+    ) -> Vec<FinalizationCandidate> {
+        if candidates.is_empty() {
+            return candidates;
+        }
+
+        if !within_budget {
+            let urgent = Self::urgent(candidates);
+
+            tracing::info!(
+                "gas budgets are spent, finalizing {} withdrawals close to their deadlines",
+                urgent.len()
+            );
+
+            if urgent.is_empty() {
+                FINALIZER_METRICS.deferred_by_budget.inc();
+            } else {
+                FINALIZER_METRICS
+                    .forced_by_deadline_withdrawals
+                    .inc_by(urgent.len() as u64);
+            }
+
+            return urgent;
+        }
+
+        let (Some(max_base_fee), Some(base_fee)) = (self.max_base_fee, base_fee) else {
+            return candidates;
+        };
+
+        if base_fee <= max_base_fee {
+            return candidates;
+        }
+
+        let urgent = Self::urgent(candidates);
+
+        tracing::info!(
+            "base fee {base_fee} is above {max_base_fee}, finalizing {} withdrawals close to their deadlines",
+            urgent.len()
+        );
+
+        if urgent.is_empty() {
+            FINALIZER_METRICS.deferred_by_base_fee.inc();
+        } else {
+            FINALIZER_METRICS
+                .forced_by_deadline_withdrawals
+                .inc_by(urgent.len() as u64);
+        }
+
+        urgent
+    }
+
     async fn loop_iteration(&mut self) -> Result<()> {
         tracing::debug!("begin iteration of the finalizer loop");
 
-        let try_finalize_these = storage::withdrawals_to_finalize(
+        let pauses = storage::active_finalization_pauses(&self.pgpool, Some(self.chain_id)).await?;
+
+        FINALIZER_METRICS.finalization_pauses[&self.chain_id].set(pauses.len() as i64);
+
+        // Finalization resumes once an operator resumes it, indexing goes on meanwhile.
+        if let Some(pause) = pauses.iter().find(|p| p.is_global()) {
+            FINALIZER_METRICS.finalization_paused[&self.chain_id].set(1);
+            tracing::debug!(
+                "finalization is paused by {}: {}",
+                pause.paused_by,
+                pause.reason
+            );
+            tokio::time::sleep(self.no_new_withdrawals_backoff).await;
+            return Ok(());
+        }
+
+        FINALIZER_METRICS.finalization_paused[&self.chain_id].set(0);
+
+        self.signers.refresh_balances().await;
+        self.signers.refresh_budgets(&self.pgpool).await?;
+
+        // Finalization resumes once any of the accounts is topped up.
+        if !self.signers.is_funded() {
+            tracing::debug!("finalization is paused until finalizer accounts are funded");
+            tokio::time::sleep(OUT_OF_FUNDS_BACKOFF).await;
+            return Ok(());
+        }
+
+        if let Some(policy) = self.profitability_policy.as_mut() {
+            policy.refresh_prices().await;
+        }
+
+        let mut projection = match self.circuit_breaker.as_ref() {
+            Some(circuit_breaker) => Some(
+                circuit_breaker
+                    .check(
+                        &self.pgpool,
+                        self.chain_id,
+                        self.base_token_l1_address,
+                        self.base_token_decimals,
+                    )
+                    .await?,
+            ),
+            None => None,
+        };
+        let paused_tokens = projection.as_ref().map_or(vec![], |p| p.paused().to_vec());
+
+        // Finalization resumes once the trips are reset by an admin.
+        if paused_tokens.contains(&None) {
+            tracing::debug!("finalization is paused by the circuit breaker");
+            tokio::time::sleep(self.no_new_withdrawals_backoff).await;
+            return Ok(());
+        }
+
+        if let Some((base_token_threshold, ref token_thresholds)) = self.approval_thresholds {
+            let requested = storage::request_withdrawal_approvals(
+                &self.pgpool,
+                self.chain_id,
+                base_token_threshold,
+                token_thresholds,
+            )
+            .await?;
+
+            if requested > 0 {
+                tracing::info!(
+                    "{requested} withdrawals of chain {} await approval",
+                    self.chain_id
+                );
+                FINALIZER_METRICS.approvals_required.inc_by(requested);
+            }
+        }
+
+        let (current_l1_block, base_fee) = self.latest_l1_block().await?;
+        let mut criteria = self.finalization_criteria(current_l1_block);
+
+        for pause in pauses {
+            match (pause.token, pause.l1_recipient) {
+                (Some(token), Some(l1_recipient)) => criteria
+                    .paused_token_l1_recipients
+                    .push((token, l1_recipient)),
+                (token, l1_recipient) => {
+                    criteria.paused_tokens.extend(token);
+                    criteria.paused_l1_recipients.extend(l1_recipient);
+                }
+            }
+        }
+
+        for token in paused_tokens.into_iter().flatten() {
+            // Withdrawals of the base token are only known by its L2 address.
+            if token == self.base_token_l1_address {
+                criteria.paused_tokens.push(ETH_TOKEN_ADDRESS);
+            }
+            criteria.paused_tokens.push(token);
+        }
+
+        let mut accumulator = self.new_accumulator().await?;
+
+        // Withdrawals found unprofitable are skipped until prices change or gas gets cheaper.
+        criteria.unprofitable_at = self
+            .profitability_policy
+            .as_ref()
+            .map(|policy| (policy.fingerprint(), accumulator.gas_price()));
+
+        let FinalizationCandidates {
+            candidates: try_finalize_these,
+            oldest_executed_l1_block,
+        } = storage::withdrawals_to_finalize(
             &self.pgpool,
+            self.chain_id,
             self.query_db_pagination_limit,
-            self.eth_threshold,
-            self.only_l1_recipients.as_deref(),
+            &criteria,
         )
         .await?;
 
+        let oldest_eligible_withdrawal_age = oldest_executed_l1_block.map_or(0, |b| {
+            criteria.current_l1_block.saturating_sub(b) * L1_BLOCK_TIME.as_secs()
+        });
+        FINALIZER_METRICS.oldest_eligible_withdrawal_age_seconds[&self.chain_id]
+            .set(oldest_eligible_withdrawal_age);
+
+        // Once the gas budgets are spent only urgent withdrawals are finalized.
+        let within_budget = self.signers.within_budget();
+        let try_finalize_these = self.schedule(try_finalize_these, base_fee, within_budget);
+
         tracing::debug!("trying to finalize these {try_finalize_these:?}");
 
         if try_finalize_these.is_empty() {
@@ -339,41 +1060,253 @@ where
             return Ok(());
         }
 
-        let mut accumulator = self.new_accumulator().await?;
-        let mut iter = try_finalize_these.into_iter().peekable();
+        // Withdrawals below thresholds make up batches of their own only if gas is cheap.
+        let gas_is_cheap = self
+            .below_threshold_max_gas_price
+            .is_some_and(|max_gas_price| accumulator.gas_price() <= max_gas_price);
 
-        while let Some(t) = iter.next() {
-            accumulator.add_withdrawal(t);
+        let (below_threshold, above_threshold): (Vec<_>, Vec<_>) = try_finalize_these
+            .into_iter()
+            .partition(|c| c.below_threshold);
 
-            if accumulator.ready_to_finalize() || iter.peek().is_none() {
-                tracing::info!(
-                    "predicting results for withdrawals: {:?}",
-                    accumulator.withdrawals().map(|w| w.id).collect::<Vec<_>>()
-                );
+        if above_threshold.is_empty() && !gas_is_cheap {
+            tokio::time::sleep(self.no_new_withdrawals_backoff).await;
+            return Ok(());
+        }
 
-                let predicted_to_fail = self.predict_fails(accumulator.withdrawals()).await?;
+        let mut iter = above_threshold.into_iter().peekable();
+        let mut below_threshold = below_threshold.into_iter().peekable();
+        let mut predicted_gas = HashMap::new();
+        let mut held_back = false;
+
+        let mut in_flight = FuturesUnordered::new();
+
+        let res: Result<()> = async {
+            loop {
+                let t = match iter.next() {
+                    Some(t) => t,
+                    None if gas_is_cheap => match below_threshold.next() {
+                        Some(t) => {
+                            FINALIZER_METRICS.below_threshold_withdrawals_batched.inc();
+                            t
+                        }
+                        None => break,
+                    },
+                    None => break,
+                };
+                accumulator.add_withdrawal(t);
+
+                let is_last =
+                    iter.peek().is_none() && (!gas_is_cheap || below_threshold.peek().is_none());
+
+                if accumulator.ready_to_finalize() || is_last {
+                    // Withdrawals below thresholds take the spare capacity of the batch.
+                    while !accumulator.ready_to_finalize() {
+                        let Some(t) = below_threshold.next() else {
+                            break;
+                        };
+                        FINALIZER_METRICS.below_threshold_withdrawals_batched.inc();
+                        accumulator.add_withdrawal(t);
+                    }
 
-                FINALIZER_METRICS
-                    .predicted_to_fail_withdrawals
-                    .inc_by(predicted_to_fail.len() as u64);
+                    // An incomplete batch waits for more withdrawals within the batching window.
+                    if !accumulator.ready_to_finalize()
+                        && self.within_batching_window(&accumulator, current_l1_block)
+                    {
+                        tracing::debug!(
+                            "holding back withdrawals {:?} within the batching window",
+                            accumulator.withdrawals().map(|w| w.id).collect::<Vec<_>>()
+                        );
+                        held_back = true;
+                        break;
+                    }
 
-                tracing::debug!("predicted to fail: {predicted_to_fail:?}");
+                    tracing::info!(
+                        "predicting results for withdrawals: {:?}",
+                        accumulator.withdrawals().map(|w| w.id).collect::<Vec<_>>()
+                    );
+
+                    let (predicted_to_fail, predicted_to_succeed) =
+                        self.predict_fails(accumulator.withdrawals()).await?;
+
+                    predicted_gas = predicted_to_succeed
+                        .iter()
+                        .map(|p| {
+                            (
+                                (p.l_2_block_number.as_u64(), p.l_2_message_index.as_u64()),
+                                p.gas,
+                            )
+                        })
+                        .collect();
+
+                    if let Some(policy) = self.profitability_policy.as_ref() {
+                        let gas_price = accumulator.gas_price();
+                        let unprofitable = accumulator.remove_withdrawals(|c| {
+                            let key = (
+                                c.params.l1_batch_number.as_u64(),
+                                u64::from(c.params.l2_message_index),
+                            );
+                            predicted_gas.get(&key).is_some_and(|gas| {
+                                !policy.is_profitable(
+                                    c,
+                                    self.base_token_l1_address,
+                                    *gas,
+                                    gas_price,
+                                )
+                            })
+                        });
+
+                        if !unprofitable.is_empty() {
+                            let ids: Vec<_> = unprofitable.iter().map(|c| c.params.id).collect();
+                            tracing::info!("skipping unprofitable withdrawals {ids:?}");
+
+                            storage::set_withdrawals_unprofitable(
+                                &self.pgpool,
+                                &ids,
+                                policy.fingerprint(),
+                                gas_price,
+                            )
+                            .await?;
+                            FINALIZER_METRICS
+                                .unprofitable_withdrawals_skipped
+                                .inc_by(unprofitable.len() as u64);
+                        }
+                    }
+
+                    FINALIZER_METRICS
+                        .predicted_to_fail_withdrawals
+                        .inc_by(predicted_to_fail.len() as u64);
+
+                    tracing::debug!("predicted to fail: {predicted_to_fail:?}");
 
-                if !predicted_to_fail.is_empty() {
-                    let mut removed = accumulator.remove_unsuccessful(&predicted_to_fail);
+                    if !predicted_to_fail.is_empty() {
+                        let mut removed = accumulator.remove_unsuccessful(&predicted_to_fail);
 
-                    self.unsuccessful.append(&mut removed);
+                        self.unsuccessful.append(&mut removed);
+                    }
+                }
+
+                let is_last =
+                    iter.peek().is_none() && (!gas_is_cheap || below_threshold.peek().is_none());
+
+                if accumulator.ready_to_finalize() || is_last {
+                    // Batches are only sent as long as the volumes stay within the limits.
+                    if let (Some(circuit_breaker), Some(projection)) =
+                        (self.circuit_breaker.as_ref(), projection.as_mut())
+                    {
+                        if !circuit_breaker
+                            .check_batch(
+                                &self.pgpool,
+                                self.chain_id,
+                                self.base_token_l1_address,
+                                projection,
+                                accumulator.candidates(),
+                            )
+                            .await?
+                        {
+                            tracing::error!(
+                                "withdrawals {:?} are not finalized as the circuit breaker has tripped",
+                                accumulator.withdrawals().map(|w| w.id).collect::<Vec<_>>()
+                            );
+                            break;
+                        }
+                    }
+
+                    let requests = accumulator.take_withdrawals();
+                    if !requests.is_empty() {
+                        let Some(signer) = self.signers.lease(!within_budget) else {
+                            tracing::error!(
+                                "no funded accounts to send finalization transactions from"
+                            );
+                            FINALIZER_METRICS
+                                .failed_to_finalize_low_gas
+                                .inc_by(requests.len() as u64);
+                            tokio::time::sleep(OUT_OF_FUNDS_BACKOFF).await;
+                            break;
+                        };
+
+                        let withdrawals_gas: Vec<_> = requests
+                            .iter()
+                            .map(|w| {
+                                let key =
+                                    (w.l1_batch_number.as_u64(), u64::from(w.l2_message_index));
+                                predicted_gas.get(&key).copied().unwrap_or_default()
+                            })
+                            .collect();
+
+                        let direct_gas_limit =
+                            self.direct_gas_limit(&requests, &withdrawals_gas).await?;
+
+                        let ids: Vec<_> = requests.iter().map(|w| w.id as i64).collect();
+                        let pending_tx = storage::add_pending_finalization_transaction(
+                            &self.pgpool,
+                            self.chain_id,
+                            signer.address(),
+                            &ids,
+                        )
+                        .await?;
+
+                        // Batches are sent concurrently with batching the next ones.
+                        in_flight.push(tokio::spawn(self.send_batch(
+                            signer,
+                            requests,
+                            withdrawals_gas,
+                            direct_gas_limit,
+                            pending_tx,
+                        )));
+                    }
+
+                    if in_flight.len() >= self.max_in_flight_txs {
+                        if let Some(batch) = in_flight.next().await {
+                            self.process_sent_batch(batch?).await?;
+                        }
+                    }
+
+                    accumulator = self.new_accumulator().await?;
                 }
             }
 
-            if accumulator.ready_to_finalize() || iter.peek().is_none() {
-                let requests = accumulator.take_withdrawals();
-                self.finalize_batch(requests).await?;
-                accumulator = self.new_accumulator().await?;
+            Ok(())
+        }
+        .await;
+
+        // Batches already in flight are waited for even if batching has failed.
+        while let Some(batch) = in_flight.next().await {
+            let processed = match batch {
+                Ok(batch) => self.process_sent_batch(batch).await,
+                Err(e) => Err(e.into()),
+            };
+
+            if let Err(e) = processed {
+                tracing::error!("processing of a sent batch has failed with {e}");
             }
         }
 
-        self.process_unsuccessful().await
+        res?;
+
+        self.process_unsuccessful().await?;
+
+        if held_back {
+            tokio::time::sleep(self.no_new_withdrawals_backoff).await;
+        }
+
+        Ok(())
+    }
+
+    // Whether the current batch is within the batching window, that is none of its
+    // withdrawals has been waiting for finalization since execution for the whole window.
+    fn within_batching_window(
+        &self,
+        accumulator: &WithdrawalsAccumulator,
+        current_l1_block: u64,
+    ) -> bool {
+        let Some((_, window)) = self.batching_window else {
+            return false;
+        };
+
+        accumulator.oldest_executed_l1_block().is_some_and(|b| {
+            current_l1_block.saturating_sub(b) * L1_BLOCK_TIME.as_secs() < window.as_secs()
+        })
     }
 
     // process withdrawals that have been predicted as unsuccessful.
@@ -390,8 +1323,7 @@ where
 
         let predicted = std::mem::take(&mut self.unsuccessful);
         tracing::debug!("requesting finalization status of withdrawals");
-        let are_finalized =
-            get_finalized_withdrawals(&predicted, &self.zksync_contract, &self.l1_bridge).await?;
+        let are_finalized = get_finalized_withdrawals(&predicted, &self.contracts).await?;
 
         let mut already_finalized = vec![];
         let mut unsuccessful = vec![];
@@ -442,10 +1374,46 @@ where
     }
 }
 
+// Whether the pending transaction finalizing `withdrawals` should be cancelled, either because
+// an operator has requested it or because all of the withdrawals have been finalized since.
+async fn should_cancel<M>(
+    pool: &PgPool,
+    pending_tx: u64,
+    withdrawals: &[WithdrawalParams],
+    contracts: &FinalizationContracts<M>,
+) -> bool
+where
+    M: Middleware,
+{
+    match storage::finalization_transaction_cancellation_requested(pool, pending_tx).await {
+        Ok(true) => {
+            tracing::info!("cancellation of pending transaction {pending_tx} requested");
+            return true;
+        }
+        Ok(false) => (),
+        Err(e) => {
+            tracing::error!(
+                "failed to check cancellation of pending transaction {pending_tx}: {e}"
+            );
+        }
+    }
+
+    match get_finalized_withdrawals(withdrawals, contracts).await {
+        Ok(finalized) if finalized.len() == withdrawals.len() => {
+            tracing::info!("withdrawals of pending transaction {pending_tx} have been finalized");
+            true
+        }
+        Ok(_) => false,
+        Err(e) => {
+            tracing::error!("failed to check withdrawals of pending transaction {pending_tx}: {e}");
+            false
+        }
+    }
+}
+
 async fn get_finalized_withdrawals<M>(
     withdrawals: &[WithdrawalParams],
-    zksync_contract: &IZkSync<M>,
-    l1_bridge: &IL1Bridge<M>,
+    contracts: &FinalizationContracts<M>,
 ) -> Result<HashSet<WithdrawalKey>>
 where
     M: Middleware,
@@ -455,19 +1423,10 @@ where
             let l1_batch_number = U256::from(wd.l1_batch_number.as_u64());
             let l2_message_index = U256::from(wd.l2_message_index);
 
-            if is_eth(wd.sender) {
-                zksync_contract
-                    .is_eth_withdrawal_finalized(l1_batch_number, l2_message_index)
-                    .call()
-                    .await
-                    .map_err(|e| e.into())
-            } else {
-                l1_bridge
-                    .is_withdrawal_finalized(l1_batch_number, l2_message_index)
-                    .call()
-                    .await
-                    .map_err(|e| e.into())
-            }
+            contracts
+                .is_withdrawal_finalized(l1_batch_number, l2_message_index, wd.sender)
+                .await
+                .map_err(|e| e.into())
         }))
         .await
         .into_iter()
@@ -485,6 +1444,47 @@ where
     Ok(set)
 }
 
+// Split `total` between parts in proportion to their `weights`, equally if all of the weights
+// are zero. The remainder of rounding goes to the last part so that the parts add up to `total`.
+fn apportion(total: U256, weights: &[U256]) -> Vec<U256> {
+    let Some(last) = weights.len().checked_sub(1) else {
+        return vec![];
+    };
+
+    let weights_sum = weights
+        .iter()
+        .fold(U256::zero(), |sum, w| sum.saturating_add(*w));
+
+    let mut parts: Vec<_> = weights
+        .iter()
+        .map(|w| match weights_sum.is_zero() {
+            true => total / weights.len(),
+            false => U256::try_from(total.full_mul(*w) / weights_sum)
+                .expect("a part is not greater than the total; qed"),
+        })
+        .collect();
+
+    let apportioned = parts[..last]
+        .iter()
+        .fold(U256::zero(), |sum, p| sum.saturating_add(*p));
+    parts[last] = total.saturating_sub(apportioned);
+
+    parts
+}
+
+// Gas a transaction with the given calldata is charged with before its execution.
+fn intrinsic_gas(data: &[u8]) -> U256 {
+    let calldata_gas: u64 = data
+        .iter()
+        .map(|b| match b {
+            0 => TX_ZERO_BYTE_GAS,
+            _ => TX_NON_ZERO_BYTE_GAS,
+        })
+        .sum();
+
+    (TX_BASE_GAS + calldata_gas).into()
+}
+
 fn is_gas_required_exceeds_allowance<M: Middleware>(e: &<M as Middleware>::Error) -> bool {
     if let Some(e) = e.as_error_response() {
         return e.code == -32000 && e.message.starts_with("gas required exceeds allowance ");
@@ -497,6 +1497,7 @@ fn is_gas_required_exceeds_allowance<M: Middleware>(e: &<M as Middleware>::Error
 // Returns `None` if params for some withdrawal are not ready yet.
 async fn request_finalize_params<M2>(
     pgpool: &PgPool,
+    chain_id: u64,
     middleware: M2,
     hash_and_indices: &[(H256, u16, u64)],
 ) -> Option<Vec<WithdrawalParams>>
@@ -514,6 +1515,7 @@ where
             .map_ok(|mut r| {
                 if let Some(r) = r.as_mut() {
                     r.id = *id;
+                    r.chain_id = chain_id;
                 }
                 r
             })
@@ -537,7 +1539,7 @@ where
             Err(e) => {
                 FINALIZER_METRICS.failed_to_fetch_withdrawal_params.inc();
                 if let Error::Client(client::Error::WithdrawalLogNotFound(index, tx_hash)) = e {
-                    storage::set_withdrawal_unfinalizable(pgpool, tx_hash, index)
+                    storage::set_withdrawal_unfinalizable(pgpool, chain_id, tx_hash, index)
                         .await
                         .ok();
                 }
@@ -557,16 +1559,16 @@ where
 // finalizer db table.
 async fn params_fetcher_loop<M1, M2>(
     pool: PgPool,
+    chain_id: u64,
     middleware: M2,
-    zksync_contract: IZkSync<M1>,
-    l1_bridge: IL1Bridge<M1>,
+    contracts: FinalizationContracts<M1>,
 ) where
     M1: Middleware,
     M2: ZksyncMiddleware,
 {
     loop {
         if let Err(e) =
-            params_fetcher_loop_iteration(&pool, &middleware, &zksync_contract, &l1_bridge).await
+            params_fetcher_loop_iteration(&pool, chain_id, &middleware, &contracts).await
         {
             tracing::error!("params fetcher iteration ended with {e}");
             tokio::time::sleep(LOOP_ITERATION_ERROR_BACKOFF).await;
@@ -578,15 +1580,23 @@ async fn params_fetcher_loop<M1, M2>(
 
 async fn params_fetcher_loop_iteration<M1, M2>(
     pool: &PgPool,
+    chain_id: u64,
     middleware: &M2,
-    zksync_contract: &IZkSync<M1>,
-    l1_bridge: &IL1Bridge<M1>,
+    contracts: &FinalizationContracts<M1>,
 ) -> Result<()>
 where
     M1: Middleware,
     M2: ZksyncMiddleware,
 {
-    let newly_executed_withdrawals = storage::get_withdrawals_with_no_data(pool, 1000).await?;
+    let pauses = storage::active_finalization_pauses(pool, Some(chain_id)).await?;
+
+    if pauses.iter().any(|p| p.is_global()) {
+        tokio::time::sleep(NO_NEW_WITHDRAWALS_BACKOFF).await;
+        return Ok(());
+    }
+
+    let newly_executed_withdrawals =
+        storage::get_withdrawals_with_no_data(pool, chain_id, 1000).await?;
 
     if newly_executed_withdrawals.is_empty() {
         tokio::time::sleep(NO_NEW_WITHDRAWALS_BACKOFF).await;
@@ -600,14 +1610,15 @@ where
         .map(|p| (p.key.tx_hash, p.key.event_index_in_tx as u16, p.id))
         .collect();
 
-    let Some(params) = request_finalize_params(pool, &middleware, &hash_and_index_and_id).await
+    let Some(params) =
+        request_finalize_params(pool, chain_id, &middleware, &hash_and_index_and_id).await
     else {
         // Early-return if params are not ready.
         tracing::info!("Params are not ready");
         return Ok(());
     };
 
-    let already_finalized: Vec<_> = get_finalized_withdrawals(&params, zksync_contract, l1_bridge)
+    let already_finalized: Vec<_> = get_finalized_withdrawals(&params, contracts)
         .await?
         .into_iter()
         .collect();
@@ -617,3 +1628,499 @@ where
 
     Ok(())
 }
+
+// Continuously index the transactions requested to be finalized, request finalizing
+// params for their withdrawals and store them so that they are queued for finalization.
+async fn finalization_requests_loop<M1, M2>(
+    pool: PgPool,
+    chain_id: u64,
+    middleware: M2,
+    contracts: FinalizationContracts<M1>,
+) where
+    M1: Middleware,
+    M2: ZksyncMiddleware,
+{
+    loop {
+        if let Err(e) =
+            finalization_requests_loop_iteration(&pool, chain_id, &middleware, &contracts).await
+        {
+            tracing::error!("finalization requests iteration ended with {e}");
+            tokio::time::sleep(LOOP_ITERATION_ERROR_BACKOFF).await;
+        } else {
+            tokio::time::sleep(NO_NEW_WITHDRAWALS_BACKOFF).await;
+        }
+    }
+}
+
+async fn finalization_requests_loop_iteration<M1, M2>(
+    pool: &PgPool,
+    chain_id: u64,
+    middleware: &M2,
+    contracts: &FinalizationContracts<M1>,
+) -> Result<()>
+where
+    M1: Middleware,
+    M2: ZksyncMiddleware,
+{
+    let requests = storage::pending_finalization_requests(pool, chain_id).await?;
+
+    FINALIZER_METRICS.pending_finalization_requests[&chain_id].set(requests.len() as i64);
+
+    for request in requests {
+        // The withdrawals of tokens that are not tracked are not indexed by the watcher.
+        let events = match middleware.get_withdrawal_events(request.tx_hash).await {
+            Ok(events) if events.is_empty() => {
+                fail_finalization_request(pool, request.id, "no withdrawals in the transaction")
+                    .await?;
+                continue;
+            }
+            Ok(events) => events,
+            // Only a transaction unknown to the node fails the request,
+            // other errors are retried on the next iteration.
+            Err(e) => {
+                match middleware.get_transaction(request.tx_hash).await {
+                    Ok(None) => {
+                        fail_finalization_request(pool, request.id, "transaction not found")
+                            .await?;
+                    }
+                    _ => tracing::warn!(
+                        "failed to get withdrawals of requested transaction {:?}: {e}",
+                        request.tx_hash
+                    ),
+                }
+                continue;
+            }
+        };
+
+        let withdrawals: Vec<_> = events
+            .into_iter()
+            .enumerate()
+            .map(|(index_in_tx, event)| StoredWithdrawal {
+                chain_id,
+                event,
+                index_in_tx,
+            })
+            .collect();
+
+        storage::add_withdrawals(pool, &withdrawals).await?;
+
+        let no_data =
+            storage::get_withdrawals_of_tx_with_no_data(pool, chain_id, request.tx_hash).await?;
+
+        if !no_data.is_empty() {
+            let hash_and_index_and_id: Vec<_> = no_data
+                .iter()
+                .map(|w| (w.key.tx_hash, w.key.event_index_in_tx as u16, w.id))
+                .collect();
+
+            let Some(params) =
+                request_finalize_params(pool, chain_id, middleware, &hash_and_index_and_id).await
+            else {
+                tracing::debug!(
+                    "params of requested transaction {:?} are not ready",
+                    request.tx_hash
+                );
+                continue;
+            };
+
+            if params.len() < no_data.len() {
+                fail_finalization_request(pool, request.id, "failed to fetch withdrawal params")
+                    .await?;
+                continue;
+            }
+
+            let already_finalized: Vec<_> = get_finalized_withdrawals(&params, contracts)
+                .await?
+                .into_iter()
+                .collect();
+
+            storage::add_withdrawals_data(pool, &params).await?;
+            storage::finalization_data_set_finalized_in_tx(pool, &already_finalized, H256::zero())
+                .await?;
+        }
+
+        tracing::info!(
+            "withdrawals of transaction {:?} requested by {} are queued for finalization",
+            request.tx_hash,
+            request.requested_by
+        );
+
+        storage::set_finalization_request_status(
+            pool,
+            request.id,
+            FinalizationRequestStatus::Queued,
+            None,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn fail_finalization_request(pool: &PgPool, id: u64, error: &str) -> Result<()> {
+    tracing::warn!("finalization request {id} failed: {error}");
+    FINALIZER_METRICS.failed_finalization_requests.inc();
+
+    storage::set_finalization_request_status(
+        pool,
+        id,
+        FinalizationRequestStatus::Failed,
+        Some(error),
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use client::{l1bridge::codegen::IL1Bridge, zksync_contract::codegen::IZkSync};
+    use ethers::providers::{JsonRpcError, MockProvider, MockResponse, Provider};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const ONE_WITHDRAWAL_GAS_LIMIT: u64 = 500_000;
+
+    type TestFinalizer = Finalizer<Provider<MockProvider>, Provider<MockProvider>>;
+
+    fn finalizer(mock: &MockProvider) -> TestFinalizer {
+        let provider = Arc::new(Provider::new(mock.clone()));
+
+        Finalizer::new(
+            PgPool::connect_lazy("postgres://localhost/finalizer").unwrap(),
+            324,
+            ONE_WITHDRAWAL_GAS_LIMIT.into(),
+            5_000_000.into(),
+            Arc::new(SignerPool::new(
+                vec![(Address::random(), provider.clone())],
+                U256::zero(),
+            )),
+            FinalizationContracts::Legacy {
+                zksync_contract: IZkSync::new(Address::random(), provider.clone()),
+                l1_bridge: IL1Bridge::new(Address::random(), provider),
+            },
+            60,
+            false,
+            BaseToken::ETH,
+            None,
+            vec![],
+            None,
+        )
+    }
+
+    fn withdrawal(id: u64) -> WithdrawalParams {
+        WithdrawalParams {
+            chain_id: 324,
+            tx_hash: H256::random(),
+            event_index_in_tx: 0,
+            id,
+            l2_block_number: id,
+            l1_batch_number: id.into(),
+            l2_message_index: 0,
+            l2_tx_number_in_block: 0,
+            message: Default::default(),
+            sender: ETH_TOKEN_ADDRESS,
+            proof: vec![],
+        }
+    }
+
+    fn candidate(id: u64, urgent: bool) -> FinalizationCandidate {
+        FinalizationCandidate {
+            params: withdrawal(id),
+            below_threshold: false,
+            executed_l1_block: Some(id),
+            l2_token: ETH_TOKEN_ADDRESS,
+            l1_token: None,
+            amount: U256::exp10(18),
+            decimals: 18,
+            requested: false,
+            urgent,
+        }
+    }
+
+    fn ids(candidates: Vec<FinalizationCandidate>) -> Vec<u64> {
+        candidates.into_iter().map(|c| c.params.id).collect()
+    }
+
+    fn revert() -> MockResponse {
+        MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".into(),
+            data: None,
+        })
+    }
+
+    #[test]
+    fn apportion_splits_total_by_weights() {
+        assert_eq!(apportion(100.into(), &[]), vec![]);
+        assert_eq!(apportion(100.into(), &[7.into()]), vec![100.into()]);
+        assert_eq!(
+            apportion(100.into(), &[1.into(), 3.into()]),
+            vec![25.into(), 75.into()]
+        );
+
+        // The last part takes the remainder of rounding down.
+        assert_eq!(
+            apportion(100.into(), &[1.into(), 1.into(), 1.into()]),
+            vec![33.into(), 33.into(), 34.into()]
+        );
+    }
+
+    #[test]
+    fn apportion_splits_total_evenly_without_weights() {
+        assert_eq!(
+            apportion(10.into(), &[U256::zero(), U256::zero(), U256::zero()]),
+            vec![3.into(), 3.into(), 4.into()]
+        );
+    }
+
+    #[test]
+    fn apportion_does_not_overflow() {
+        assert_eq!(
+            apportion(U256::MAX, &[1.into(), 1.into()]),
+            vec![U256::MAX / 2, U256::MAX / 2 + 1]
+        );
+    }
+
+    #[test]
+    fn intrinsic_gas_is_charged_for_calldata() {
+        assert_eq!(intrinsic_gas(&[]), 21_000.into());
+        assert_eq!(
+            intrinsic_gas(&[0, 1, 0, 255]),
+            (21_000 + 4 + 16 + 4 + 16).into()
+        );
+    }
+
+    #[tokio::test]
+    async fn predict_direct_allows_intrinsic_gas_on_top_of_withdrawal_gas_limit() {
+        let mock = MockProvider::new();
+        let finalizer = finalizer(&mock);
+        let withdrawals = [withdrawal(1), withdrawal(2), withdrawal(3)];
+
+        // Responses are popped in the reverse order.
+        mock.push_response(revert());
+        mock.push(U256::from(ONE_WITHDRAWAL_GAS_LIMIT + 200_000))
+            .unwrap();
+        mock.push(U256::from(ONE_WITHDRAWAL_GAS_LIMIT + 21_000))
+            .unwrap();
+
+        let results = finalizer
+            .predict_direct(withdrawals.iter())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.l_2_block_number.as_u64(), r.gas.as_u64(), r.success))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            results,
+            vec![
+                (1, ONE_WITHDRAWAL_GAS_LIMIT + 21_000, true),
+                (2, ONE_WITHDRAWAL_GAS_LIMIT + 200_000, false),
+                (3, 0, false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn direct_gas_limit_reuses_predicted_gas() {
+        let mock = MockProvider::new();
+        let finalizer = finalizer(&mock);
+
+        // Nothing is estimated as there are no responses.
+        assert_eq!(
+            finalizer
+                .direct_gas_limit(&[withdrawal(1)], &[100_000.into()])
+                .await
+                .unwrap(),
+            Some(120_000.into())
+        );
+
+        mock.push(U256::from(200_000)).unwrap();
+        assert_eq!(
+            finalizer
+                .direct_gas_limit(&[withdrawal(1)], &[U256::zero()])
+                .await
+                .unwrap(),
+            Some(240_000.into())
+        );
+    }
+
+    #[tokio::test]
+    async fn direct_gas_limit_finalizes_single_withdrawals_directly_if_cheaper() {
+        let mock = MockProvider::new();
+        let finalizer = finalizer(&mock).with_finalizer_contract(Address::random());
+
+        // Batches of several withdrawals are never finalized directly.
+        assert_eq!(
+            finalizer
+                .direct_gas_limit(
+                    &[withdrawal(1), withdrawal(2)],
+                    &[100_000.into(), 100_000.into()]
+                )
+                .await
+                .unwrap(),
+            None
+        );
+
+        // The direct estimate comes first, then the one of the finalizer contract.
+        mock.push(U256::from(150_000)).unwrap();
+        mock.push(U256::from(100_000)).unwrap();
+        assert_eq!(
+            finalizer
+                .direct_gas_limit(&[withdrawal(1)], &[50_000.into()])
+                .await
+                .unwrap(),
+            Some(120_000.into())
+        );
+
+        mock.push(U256::from(80_000)).unwrap();
+        mock.push(U256::from(100_000)).unwrap();
+        assert_eq!(
+            finalizer
+                .direct_gas_limit(&[withdrawal(1)], &[50_000.into()])
+                .await
+                .unwrap(),
+            None
+        );
+
+        mock.push_response(revert());
+        assert_eq!(
+            finalizer
+                .direct_gas_limit(&[withdrawal(1)], &[50_000.into()])
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn finalization_criteria_mark_withdrawals_close_to_deadlines_urgent() {
+        let mock = MockProvider::new();
+        let finalizer = finalizer(&mock);
+
+        assert_eq!(
+            finalizer
+                .finalization_criteria(10_000)
+                .urgent_executed_up_to,
+            None
+        );
+
+        // 23 hours are 6900 L1 blocks.
+        let finalizer = finalizer
+            .with_finalization_deadline(Duration::from_secs(24 * 3600), Duration::from_secs(3600));
+        assert_eq!(
+            finalizer
+                .finalization_criteria(10_000)
+                .urgent_executed_up_to,
+            Some(3_100)
+        );
+        assert_eq!(
+            finalizer.finalization_criteria(100).urgent_executed_up_to,
+            Some(0)
+        );
+    }
+
+    #[tokio::test]
+    async fn schedule_finalizes_all_withdrawals_within_base_fee_and_budget() {
+        let mock = MockProvider::new();
+        let candidates = || vec![candidate(1, false), candidate(2, true)];
+
+        let finalizer = finalizer(&mock);
+        assert_eq!(
+            ids(finalizer.schedule(candidates(), Some(100.into()), true)),
+            vec![1, 2]
+        );
+
+        let finalizer = finalizer.with_max_base_fee(100.into());
+        assert_eq!(
+            ids(finalizer.schedule(candidates(), Some(100.into()), true)),
+            vec![1, 2]
+        );
+        assert_eq!(
+            ids(finalizer.schedule(candidates(), None, true)),
+            vec![1, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn schedule_finalizes_urgent_withdrawals_above_max_base_fee() {
+        let mock = MockProvider::new();
+        let finalizer = finalizer(&mock).with_max_base_fee(100.into());
+
+        assert_eq!(
+            ids(finalizer.schedule(
+                vec![candidate(1, false), candidate(2, true), candidate(3, false)],
+                Some(101.into()),
+                true
+            )),
+            vec![2]
+        );
+        assert_eq!(
+            ids(finalizer.schedule(vec![candidate(1, false)], Some(101.into()), true)),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[tokio::test]
+    async fn schedule_finalizes_urgent_withdrawals_once_budgets_are_spent() {
+        let mock = MockProvider::new();
+        let finalizer = finalizer(&mock);
+
+        assert_eq!(
+            ids(finalizer.schedule(
+                vec![candidate(1, true), candidate(2, false), candidate(3, true)],
+                Some(1.into()),
+                false
+            )),
+            vec![1, 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn accumulator_targets_batch_size_with_finalizer_contract() {
+        let mock = MockProvider::new();
+        let finalizer = finalizer(&mock);
+
+        // Without the finalizer contract withdrawals are finalized one by one.
+        mock.push(U256::one()).unwrap();
+        let mut accumulator = finalizer.new_accumulator().await.unwrap();
+        accumulator.add_withdrawal(candidate(1, false));
+        assert!(accumulator.ready_to_finalize());
+
+        let finalizer = finalizer
+            .with_finalizer_contract(Address::random())
+            .with_batching_window(Some(2), Duration::from_secs(600));
+
+        mock.push(U256::one()).unwrap();
+        let mut accumulator = finalizer.new_accumulator().await.unwrap();
+        accumulator.add_withdrawal(candidate(1, false));
+        assert!(!accumulator.ready_to_finalize());
+        accumulator.add_withdrawal(candidate(2, false));
+        assert!(accumulator.ready_to_finalize());
+    }
+
+    #[tokio::test]
+    async fn holds_back_batches_within_batching_window() {
+        let mock = MockProvider::new();
+        let mut accumulator =
+            WithdrawalsAccumulator::new(1.into(), U256::MAX, U256::MAX, 1.into(), None);
+
+        // Withdrawals of `candidate` are executed in the L1 block of their id.
+        accumulator.add_withdrawal(candidate(100, false));
+
+        let finalizer = finalizer(&mock);
+        assert!(!finalizer.within_batching_window(&accumulator, 100));
+
+        // 600 seconds are 50 L1 blocks.
+        let finalizer = finalizer.with_batching_window(None, Duration::from_secs(600));
+        assert!(finalizer.within_batching_window(&accumulator, 100));
+        assert!(finalizer.within_batching_window(&accumulator, 149));
+        assert!(!finalizer.within_batching_window(&accumulator, 150));
+
+        // The oldest withdrawal of the batch decides.
+        accumulator.add_withdrawal(candidate(90, false));
+        assert!(!finalizer.within_batching_window(&accumulator, 140));
+    }
+}
@@ -2,14 +2,68 @@
 
 #![allow(unexpected_cfgs)]
 
-use vise::{Counter, Gauge, Metrics};
+use vise::{
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, LabeledFamily,
+    Metrics,
+};
+
+/// Status of finalization
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "status", rename_all = "snake_case")]
+pub enum FinalizerStatus {
+    /// Withdrawals are being finalized.
+    Running,
+
+    /// Finalization is paused because no account has enough funds.
+    OutOfFunds,
+}
+
+impl FinalizerStatus {
+    const ALL: [Self; 2] = [Self::Running, Self::OutOfFunds];
+
+    /// Set the gauge of the current status to 1 and of the others to 0.
+    pub(super) fn report(current: Self) {
+        for status in Self::ALL {
+            FINALIZER_METRICS.status[&status].set(i64::from(status == current));
+        }
+    }
+}
+
+/// Labels of metrics of a token of a chain
+#[derive(Debug, Clone, PartialEq, Eq, Hash, EncodeLabelSet)]
+pub(super) struct TokenLabels {
+    /// Chain id of the ZK chain.
+    pub chain_id: u64,
+
+    /// Address of the token on L2.
+    pub token: String,
+}
 
 /// Finalizer metrics
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "finalizer")]
 pub(super) struct FinalizerMetrics {
     /// Highest finalized batch number
-    pub highest_finalized_batch_number: Gauge,
+    #[metrics(labels = ["chain_id"])]
+    pub highest_finalized_batch_number: LabeledFamily<u64, Gauge>,
+
+    /// Balance in ether of the accounts sending finalization transactions.
+    #[metrics(labels = ["address"])]
+    pub signer_balance: LabeledFamily<String, Gauge<f64>>,
+
+    /// Gas budget in ether the accounts sending finalization transactions have left
+    /// for the last 24 hours.
+    #[metrics(labels = ["address"])]
+    pub signer_remaining_budget: LabeledFamily<String, Gauge<f64>>,
+
+    /// Projected time in seconds the balances of accounts last for at the recent spend rate.
+    pub signers_runway_seconds: Gauge<f64>,
+
+    /// Fees in gwei spent on finalizing withdrawals of each token.
+    pub finalization_fees_gwei: Family<TokenLabels, Counter>,
+
+    /// The current status of finalization is set to 1.
+    pub status: Family<FinalizerStatus, Gauge>,
 
     /// Number of withdrawals failed to finalize because of insufficient funds.
     pub failed_to_finalize_low_gas: Counter,
@@ -20,8 +74,64 @@ pub(super) struct FinalizerMetrics {
     /// Number of withdrawals failed to fetch withdrawal parameters for.
     pub failed_to_fetch_withdrawal_params: Counter,
 
+    /// Number of requests to finalize withdrawals awaiting indexing of their transactions.
+    #[metrics(labels = ["chain_id"])]
+    pub pending_finalization_requests: LabeledFamily<u64, Gauge>,
+
+    /// Number of requests to finalize withdrawals that have failed.
+    pub failed_finalization_requests: Counter,
+
+    /// Number of transactions finalizing a withdrawal directly on the contracts of its chain.
+    pub direct_finalization_transactions: Counter,
+
     /// Number of withdrawal transactions that were reverted.
     pub reverted_withdrawal_transactions: Counter,
+
+    /// Age in seconds of the oldest withdrawal eligible for finalization.
+    #[metrics(labels = ["chain_id"])]
+    pub oldest_eligible_withdrawal_age_seconds: LabeledFamily<u64, Gauge<u64>>,
+
+    /// Number of withdrawals in finalized batches.
+    #[metrics(buckets = Buckets::exponential(1.0..=256.0, 2.0))]
+    pub batch_size: Histogram<usize>,
+
+    /// Gas used by finalization transactions on top of the withdrawals per withdrawal.
+    #[metrics(buckets = Buckets::exponential(1_000.0..=1_024_000.0, 2.0))]
+    pub overhead_gas_per_withdrawal: Histogram<u64>,
+
+    /// Number of iterations finalization has been deferred in because of the L1 base fee.
+    pub deferred_by_base_fee: Counter,
+
+    /// Number of iterations finalization has been deferred in because of spent gas budgets.
+    pub deferred_by_budget: Counter,
+
+    /// Number of withdrawals finalized regardless of the L1 base fee because of their deadlines.
+    pub forced_by_deadline_withdrawals: Counter,
+
+    /// Number of withdrawals skipped for being worth too little compared to the fee.
+    pub unprofitable_withdrawals_skipped: Counter,
+
+    /// Set to 1 while finalization of all withdrawals is paused by an operator.
+    #[metrics(labels = ["chain_id"])]
+    pub finalization_paused: LabeledFamily<u64, Gauge>,
+
+    /// Number of active pauses of finalization set by operators, of all scopes.
+    #[metrics(labels = ["chain_id"])]
+    pub finalization_pauses: LabeledFamily<u64, Gauge>,
+
+    /// Number of withdrawals routed to manual approval.
+    pub approvals_required: Counter,
+
+    /// Number of trips of the circuit breaker.
+    pub circuit_breaker_trips: Counter,
+
+    /// Number of tokens finalization of which is paused by the circuit breaker,
+    /// including the trip of all tokens.
+    #[metrics(labels = ["chain_id"])]
+    pub paused_by_circuit_breaker: LabeledFamily<u64, Gauge>,
+
+    /// Number of withdrawals below finalization thresholds added to batches.
+    pub below_threshold_withdrawals_batched: Counter,
 }
 
 #[vise::register]
@@ -0,0 +1,443 @@
+//! Prices of tokens and the profitability policy of finalization.
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use client::ETH_ADDRESS;
+use ethers::types::{Address, U256};
+use storage::FinalizationCandidate;
+use url::Url;
+
+use crate::error::{Error, Result};
+
+/// Interval between reloads of token prices from their source.
+const PRICES_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Timeout of requests of token prices from an HTTP source.
+const PRICES_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A source of USD prices of tokens.
+///
+/// The source provides a JSON object mapping addresses of tokens on L1 or L2
+/// to their prices in USD, the price of ETH is the one of [`ETH_ADDRESS`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PriceSource {
+    /// A JSON file.
+    File(PathBuf),
+
+    /// An HTTP endpoint responding with JSON to `GET` requests.
+    Http(Url),
+}
+
+impl FromStr for PriceSource {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match Url::parse(s) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(Self::Http(url)),
+            _ => Ok(Self::File(s.into())),
+        }
+    }
+}
+
+impl PriceSource {
+    async fn fetch(&self, client: &reqwest::Client) -> Result<HashMap<Address, f64>> {
+        match self {
+            Self::File(path) => {
+                let prices = tokio::fs::read(path)
+                    .await
+                    .map_err(|e| Error::Prices(format!("{e}")))?;
+
+                serde_json::from_slice(&prices).map_err(|e| Error::Prices(format!("{e}")))
+            }
+            Self::Http(url) => client
+                .get(url.clone())
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| Error::Prices(format!("{e}")))?
+                .json()
+                .await
+                .map_err(|e| Error::Prices(format!("{e}"))),
+        }
+    }
+}
+
+/// Skips finalization of withdrawals worth less than a multiple of the fee
+/// of finalizing them.
+///
+/// Withdrawals of tokens without known prices are only skipped if they have been
+/// requested to be finalized as anyone may request withdrawals of worthless tokens.
+pub struct ProfitabilityPolicy {
+    source: PriceSource,
+    client: reqwest::Client,
+    prices: HashMap<Address, f64>,
+    fingerprint: u64,
+    refreshed_at: Option<Instant>,
+    min_value_to_fee_ratio: f64,
+    token_ratios: HashMap<Address, f64>,
+}
+
+impl ProfitabilityPolicy {
+    /// Create a new [`ProfitabilityPolicy`].
+    ///
+    /// # Arguments
+    ///
+    /// * `source`: The source of USD prices of tokens.
+    /// * `min_value_to_fee_ratio`: Minimal ratio of the value of a withdrawal to the fee
+    ///   of finalizing it for the withdrawal to be finalized.
+    /// * `token_ratios`: Minimal ratios overriding `min_value_to_fee_ratio` for particular
+    ///   tokens by their L1 or L2 addresses.
+    pub fn new(
+        source: PriceSource,
+        min_value_to_fee_ratio: f64,
+        token_ratios: Vec<(Address, f64)>,
+    ) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(PRICES_REQUEST_TIMEOUT)
+            .build()
+            .expect("TLS backend cannot be initialized");
+
+        let mut policy = Self {
+            source,
+            client,
+            prices: HashMap::new(),
+            fingerprint: 0,
+            refreshed_at: None,
+            min_value_to_fee_ratio,
+            token_ratios: token_ratios.into_iter().collect(),
+        };
+        policy.fingerprint = policy.compute_fingerprint();
+
+        policy
+    }
+
+    /// A fingerprint of the prices and ratios that changes whenever they do.
+    ///
+    /// Withdrawals found unprofitable are not predicted again until it changes.
+    pub(crate) fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    fn compute_fingerprint(&self) -> u64 {
+        let mut prices: Vec<_> = self.prices.iter().collect();
+        prices.sort_by_key(|(token, _)| **token);
+        let mut token_ratios: Vec<_> = self.token_ratios.iter().collect();
+        token_ratios.sort_by_key(|(token, _)| **token);
+
+        let mut hasher = DefaultHasher::new();
+        for (token, price) in prices {
+            token.hash(&mut hasher);
+            price.to_bits().hash(&mut hasher);
+        }
+        self.min_value_to_fee_ratio.to_bits().hash(&mut hasher);
+        for (token, ratio) in token_ratios {
+            token.hash(&mut hasher);
+            ratio.to_bits().hash(&mut hasher);
+        }
+
+        // Stored as a `BIGINT`.
+        hasher.finish() >> 1
+    }
+
+    /// Reload the prices from the source if they have not been for a while.
+    ///
+    /// The previously loaded prices are kept if the source fails.
+    pub(crate) async fn refresh_prices(&mut self) {
+        if self
+            .refreshed_at
+            .is_some_and(|t| t.elapsed() < PRICES_REFRESH_INTERVAL)
+        {
+            return;
+        }
+
+        match self.source.fetch(&self.client).await {
+            Ok(prices) => {
+                tracing::debug!("loaded prices of {} tokens", prices.len());
+                self.prices = prices;
+                self.fingerprint = self.compute_fingerprint();
+            }
+            Err(e) => tracing::warn!("failed to load token prices: {e}"),
+        }
+
+        self.refreshed_at = Some(Instant::now());
+    }
+
+    /// Whether the withdrawal is worth finalizing with the given gas at the given gas price.
+    ///
+    /// `base_token` is the L1 address of the base token of the chain the withdrawal is from.
+    pub(crate) fn is_profitable(
+        &self,
+        candidate: &FinalizationCandidate,
+        base_token: Address,
+        gas: U256,
+        gas_price: U256,
+    ) -> bool {
+        let l1_token = match candidate.l1_token {
+            Some(token) => token,
+            None if client::is_eth(candidate.l2_token) => base_token,
+            None => return !candidate.requested,
+        };
+
+        let (Some(token_price), Some(eth_price)) = (
+            self.price(l1_token, candidate.l2_token),
+            self.prices.get(&ETH_ADDRESS),
+        ) else {
+            return !candidate.requested;
+        };
+
+        let ratio = self
+            .token_ratios
+            .get(&l1_token)
+            .or_else(|| self.token_ratios.get(&candidate.l2_token))
+            .copied()
+            .unwrap_or(self.min_value_to_fee_ratio);
+
+        let value = to_f64(candidate.amount, candidate.decimals) * token_price;
+        let fee = to_f64(gas.saturating_mul(gas_price), 18) * eth_price;
+
+        value >= ratio * fee
+    }
+
+    fn price(&self, l1_token: Address, l2_token: Address) -> Option<f64> {
+        self.prices
+            .get(&l1_token)
+            .or_else(|| self.prices.get(&l2_token))
+            .copied()
+    }
+}
+
+// Convert an amount in the smallest units of a token to the units of the token.
+pub(crate) fn to_f64(amount: U256, decimals: u32) -> f64 {
+    ethers::utils::format_units(amount, decimals)
+        .ok()
+        .and_then(|a| a.parse().ok())
+        .unwrap_or(f64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+    use pretty_assertions::assert_eq;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use client::WithdrawalParams;
+
+    use super::*;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    // Serve the responses to consecutive requests from a local HTTP endpoint,
+    // a response of `None` is never sent.
+    async fn price_stub(responses: Vec<Option<(u16, String)>>) -> PriceSource {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/prices", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await.unwrap();
+
+                let Some((status, body)) = response else {
+                    // Keep the connection open without responding.
+                    tokio::spawn(async move {
+                        tokio::time::sleep(Duration::from_secs(3600)).await;
+                        drop(stream);
+                    });
+                    continue;
+                };
+
+                let response = format!(
+                    "HTTP/1.1 {status} Stub\r\ncontent-type: application/json\r\n\
+                     content-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        url.parse().unwrap()
+    }
+
+    fn prices(prices: &[(Address, f64)]) -> Option<(u16, String)> {
+        let prices: HashMap<_, _> = prices.iter().copied().collect();
+
+        Some((200, serde_json::to_string(&prices).unwrap()))
+    }
+
+    fn candidate(
+        l1_token: Option<Address>,
+        amount: U256,
+        requested: bool,
+    ) -> FinalizationCandidate {
+        FinalizationCandidate {
+            params: WithdrawalParams {
+                chain_id: 324,
+                tx_hash: H256::random(),
+                event_index_in_tx: 0,
+                id: 1,
+                l2_block_number: 1,
+                l1_batch_number: 1.into(),
+                l2_message_index: 0,
+                l2_tx_number_in_block: 0,
+                message: Default::default(),
+                sender: Address::random(),
+                proof: vec![],
+            },
+            below_threshold: false,
+            executed_l1_block: Some(1),
+            l2_token: Address::random(),
+            l1_token,
+            amount,
+            decimals: 18,
+            requested,
+            urgent: false,
+        }
+    }
+
+    #[test]
+    fn price_source_is_http_endpoint_or_file() {
+        assert_eq!(
+            "https://prices.example/tokens".parse::<PriceSource>(),
+            Ok(PriceSource::Http(
+                "https://prices.example/tokens".parse().unwrap()
+            ))
+        );
+        assert_eq!(
+            "/etc/prices.json".parse::<PriceSource>(),
+            Ok(PriceSource::File("/etc/prices.json".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn finalizes_withdrawals_worth_multiple_of_fee() {
+        let token = Address::random();
+        let source = price_stub(vec![prices(&[(ETH_ADDRESS, 2000.0), (token, 1.0)])]).await;
+        let mut policy = ProfitabilityPolicy::new(source, 2.0, vec![]);
+        policy.refresh_prices().await;
+
+        // A fee of 100_000 gas at 10 gwei is worth 0.001 ETH or 2 USD.
+        let gas = 100_000.into();
+        let gas_price = (10 * GWEI).into();
+        let worth = |amount: u64| candidate(Some(token), U256::exp10(18) * amount, false);
+
+        assert!(policy.is_profitable(&worth(4), ETH_ADDRESS, gas, gas_price));
+        assert!(!policy.is_profitable(&worth(3), ETH_ADDRESS, gas, gas_price));
+    }
+
+    #[tokio::test]
+    async fn token_ratios_override_min_ratio() {
+        let token = Address::random();
+        let source = price_stub(vec![prices(&[(ETH_ADDRESS, 2000.0), (token, 1.0)])]).await;
+        let mut policy = ProfitabilityPolicy::new(source, 2.0, vec![(token, 1.0)]);
+        policy.refresh_prices().await;
+
+        let withdrawal = candidate(Some(token), U256::exp10(18) * 3, false);
+        assert!(policy.is_profitable(&withdrawal, ETH_ADDRESS, 100_000.into(), (10 * GWEI).into()));
+    }
+
+    #[tokio::test]
+    async fn skips_only_requested_withdrawals_of_tokens_without_prices() {
+        let source = price_stub(vec![prices(&[(ETH_ADDRESS, 2000.0)])]).await;
+        let mut policy = ProfitabilityPolicy::new(source, 2.0, vec![]);
+        policy.refresh_prices().await;
+
+        let token = Some(Address::random());
+        let gas = 100_000.into();
+        let gas_price = (10 * GWEI).into();
+
+        assert!(policy.is_profitable(
+            &candidate(token, 1.into(), false),
+            ETH_ADDRESS,
+            gas,
+            gas_price
+        ));
+        assert!(!policy.is_profitable(
+            &candidate(token, 1.into(), true),
+            ETH_ADDRESS,
+            gas,
+            gas_price
+        ));
+    }
+
+    #[tokio::test]
+    async fn keeps_prices_if_source_fails() {
+        let source = price_stub(vec![
+            prices(&[(ETH_ADDRESS, 2000.0)]),
+            Some((500, "{}".into())),
+            Some((200, "not json".into())),
+        ])
+        .await;
+        let mut policy = ProfitabilityPolicy::new(source, 2.0, vec![]);
+        policy.refresh_prices().await;
+        let fingerprint = policy.fingerprint();
+
+        for _ in 0..2 {
+            policy.refreshed_at = None;
+            policy.refresh_prices().await;
+
+            assert_eq!(policy.prices, HashMap::from([(ETH_ADDRESS, 2000.0)]));
+            assert_eq!(policy.fingerprint(), fingerprint);
+        }
+    }
+
+    #[tokio::test]
+    async fn refreshes_prices_once_in_interval() {
+        let source = price_stub(vec![
+            prices(&[(ETH_ADDRESS, 2000.0)]),
+            prices(&[(ETH_ADDRESS, 3000.0)]),
+        ])
+        .await;
+        let mut policy = ProfitabilityPolicy::new(source, 2.0, vec![]);
+        policy.refresh_prices().await;
+        policy.refresh_prices().await;
+
+        assert_eq!(policy.prices, HashMap::from([(ETH_ADDRESS, 2000.0)]));
+    }
+
+    #[tokio::test]
+    async fn fingerprint_changes_only_with_prices() {
+        let source = price_stub(vec![
+            prices(&[(ETH_ADDRESS, 2000.0)]),
+            prices(&[(ETH_ADDRESS, 2000.0)]),
+            prices(&[(ETH_ADDRESS, 2001.0)]),
+        ])
+        .await;
+        let mut policy = ProfitabilityPolicy::new(source, 2.0, vec![]);
+        let initial = policy.fingerprint();
+
+        policy.refresh_prices().await;
+        let loaded = policy.fingerprint();
+        assert_ne!(loaded, initial);
+
+        policy.refreshed_at = None;
+        policy.refresh_prices().await;
+        assert_eq!(policy.fingerprint(), loaded);
+
+        policy.refreshed_at = None;
+        policy.refresh_prices().await;
+        assert_ne!(policy.fingerprint(), loaded);
+
+        // The fingerprint fits a `BIGINT`.
+        assert!(policy.fingerprint() <= i64::MAX as u64);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn requests_to_unresponsive_source_time_out() {
+        let source = price_stub(vec![None]).await;
+        let policy = ProfitabilityPolicy::new(source, 2.0, vec![]);
+
+        let err = policy.source.fetch(&policy.client).await.unwrap_err();
+        assert!(format!("{err}").contains("timed out"), "{err}");
+    }
+}
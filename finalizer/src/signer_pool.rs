@@ -0,0 +1,545 @@
+//! A pool of accounts sending finalization transactions.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use ethers::{
+    providers::Middleware,
+    types::{Address, U256},
+};
+use sqlx::PgPool;
+use tx_sender::NonceManager;
+
+use crate::{
+    error::Result,
+    metrics::{FinalizerStatus, FINALIZER_METRICS},
+};
+
+/// The period the spend rate of accounts is averaged over.
+const SPEND_RATE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// The rolling period gas budgets of accounts are spent over.
+const BUDGET_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Balance of an account and the rate it is spent at in wei per second.
+#[derive(Default)]
+struct Spending {
+    last_balance: Option<(U256, Instant)>,
+    rate: f64,
+}
+
+impl Spending {
+    fn update(&mut self, balance: U256, now: Instant) {
+        if let Some((last_balance, last_time)) = self.last_balance {
+            let elapsed = now.duration_since(last_time);
+
+            if elapsed < Duration::from_secs(1) {
+                return;
+            }
+
+            // Balance growth is a top up rather than spending.
+            let spent = last_balance.saturating_sub(balance);
+            let rate = wei_to_f64(spent) / elapsed.as_secs_f64();
+
+            // Exponential moving average over irregular intervals.
+            let alpha = 1.0 - (-elapsed.as_secs_f64() / SPEND_RATE_PERIOD.as_secs_f64()).exp();
+            self.rate += alpha * (rate - self.rate);
+        }
+
+        self.last_balance = Some((balance, now));
+    }
+}
+
+struct Signer<S> {
+    client: Arc<S>,
+    nonce_manager: NonceManager,
+    in_flight: AtomicUsize,
+    funded: AtomicBool,
+    // The balance the account has been found out of funds at by a transaction,
+    // the account stays unfunded until its balance goes up from the lowest since.
+    unfunded_balance: Mutex<Option<U256>>,
+    spending: Mutex<Spending>,
+    budget: Option<U256>,
+    // Fees spent within the budget period.
+    spent: Mutex<U256>,
+}
+
+impl<S> Signer<S> {
+    fn within_budget(&self) -> bool {
+        let spent = *self
+            .spent
+            .lock()
+            .expect("spent lock is never poisoned; qed");
+        self.budget.map_or(true, |budget| spent < budget)
+    }
+}
+
+/// A pool of accounts sending finalization transactions, each with
+/// its own stream of nonces.
+///
+/// Transactions are sent from the funded account with the least
+/// transactions in flight, accounts with balance below the minimal
+/// one are excluded until they are funded again. Accounts that have
+/// spent their gas budget over the last 24 hours are excluded as well
+/// except for urgent transactions.
+pub struct SignerPool<S> {
+    signers: Vec<Signer<S>>,
+    min_balance: U256,
+}
+
+impl<S: Middleware> SignerPool<S> {
+    /// Create a new [`SignerPool`].
+    ///
+    /// # Arguments
+    ///
+    /// * `signers`: The addresses of the accounts and middlewares signing
+    ///   transactions with them, at least one.
+    /// * `min_balance`: Minimal balance of an account to send transactions from it.
+    pub fn new(signers: Vec<(Address, Arc<S>)>, min_balance: U256) -> Self {
+        assert!(!signers.is_empty(), "signer pool is not empty");
+
+        let signers = signers
+            .into_iter()
+            .map(|(address, client)| Signer {
+                client,
+                nonce_manager: NonceManager::new(address),
+                in_flight: AtomicUsize::new(0),
+                funded: AtomicBool::new(true),
+                unfunded_balance: Mutex::new(None),
+                spending: Mutex::new(Spending::default()),
+                budget: None,
+                spent: Mutex::new(U256::zero()),
+            })
+            .collect();
+
+        Self {
+            signers,
+            min_balance,
+        }
+    }
+
+    /// Limit the fees accounts may spend over the last 24 hours to `default_budget`
+    /// or to their own budgets by addresses.
+    pub fn with_daily_budgets(
+        mut self,
+        default_budget: Option<U256>,
+        budgets: HashMap<Address, U256>,
+    ) -> Self {
+        for signer in &mut self.signers {
+            signer.budget = budgets
+                .get(&signer.nonce_manager.address())
+                .copied()
+                .or(default_budget);
+        }
+        self
+    }
+
+    /// The middleware of the first account to make calls that are not transactions with.
+    pub fn client(&self) -> Arc<S> {
+        self.signers[0].client.clone()
+    }
+
+    /// The addresses of the accounts that are not funded to send transactions from.
+    pub fn unfunded_accounts(&self) -> Vec<Address> {
+        self.signers
+            .iter()
+            .filter(|s| !s.funded.load(Ordering::Relaxed))
+            .map(|s| s.nonce_manager.address())
+            .collect()
+    }
+
+    /// Whether any of the accounts is funded to send transactions from.
+    pub fn is_funded(&self) -> bool {
+        self.signers
+            .iter()
+            .any(|s| s.funded.load(Ordering::Relaxed))
+    }
+
+    /// Update the balances of the accounts and exclude the ones
+    /// with balance below the minimal one.
+    ///
+    /// The runway of the accounts is projected from the rate
+    /// their balances have been spent at recently. Accounts whose
+    /// balances fail to be fetched keep their status.
+    pub async fn refresh_balances(&self) {
+        let mut spendable = 0.0;
+        let mut spend_rate = 0.0;
+
+        for signer in &self.signers {
+            let address = signer.nonce_manager.address();
+
+            // The account keeps its status until its balance is fetched.
+            let balance = match signer.client.get_balance(address, None).await {
+                Ok(balance) => balance,
+                Err(e) => {
+                    tracing::warn!("failed to fetch the balance of {address:?}: {e}");
+                    continue;
+                }
+            };
+
+            FINALIZER_METRICS.signer_balance[&format!("{address:?}")]
+                .set(wei_to_f64(balance) / 1e18);
+
+            {
+                let mut spending = signer
+                    .spending
+                    .lock()
+                    .expect("spending lock is never poisoned; qed");
+                spending.update(balance, Instant::now());
+
+                spendable += wei_to_f64(balance.saturating_sub(self.min_balance));
+                spend_rate += spending.rate;
+            }
+
+            let funded = {
+                let mut unfunded_balance = signer
+                    .unfunded_balance
+                    .lock()
+                    .expect("unfunded balance lock is never poisoned; qed");
+
+                match *unfunded_balance {
+                    Some(unfunded) if balance <= unfunded => {
+                        *unfunded_balance = Some(balance);
+                        false
+                    }
+                    _ => {
+                        *unfunded_balance = None;
+                        balance >= self.min_balance
+                    }
+                }
+            };
+
+            if signer.funded.swap(funded, Ordering::Relaxed) != funded {
+                if funded {
+                    tracing::info!("finalizer account {address:?} is funded again");
+                } else {
+                    tracing::warn!(
+                        "finalizer account {address:?} balance {balance} is below the minimal one"
+                    );
+                }
+            }
+        }
+
+        // Accounts that are not spent have an infinite runway.
+        let runway = match spend_rate > 0.0 {
+            true => spendable / spend_rate,
+            false => f64::INFINITY,
+        };
+        FINALIZER_METRICS.signers_runway_seconds.set(runway);
+
+        let status = if self.is_funded() {
+            FinalizerStatus::Running
+        } else {
+            FinalizerStatus::OutOfFunds
+        };
+        FinalizerStatus::report(status);
+    }
+
+    /// Whether any of the funded accounts has not spent its gas budget.
+    pub fn within_budget(&self) -> bool {
+        self.signers
+            .iter()
+            .any(|s| s.funded.load(Ordering::Relaxed) && s.within_budget())
+    }
+
+    /// Update the fees accounts have spent over the last 24 hours
+    /// from the finalization transactions recorded in the database.
+    pub async fn refresh_budgets(&self, pool: &PgPool) -> Result<()> {
+        if self.signers.iter().all(|s| s.budget.is_none()) {
+            return Ok(());
+        }
+
+        let fees: HashMap<_, _> = storage::finalization_fees_by_account(pool, BUDGET_PERIOD)
+            .await?
+            .into_iter()
+            .collect();
+
+        for signer in &self.signers {
+            let address = signer.nonce_manager.address();
+            let spent = fees.get(&address).copied().unwrap_or_default();
+
+            *signer
+                .spent
+                .lock()
+                .expect("spent lock is never poisoned; qed") = spent;
+
+            if let Some(budget) = signer.budget {
+                FINALIZER_METRICS.signer_remaining_budget[&format!("{address:?}")]
+                    .set(wei_to_f64(budget.saturating_sub(spent)) / 1e18);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Count the fee of a transaction sent from the account towards its budget
+    /// until the budgets are refreshed.
+    pub fn record_spent(&self, address: Address, fee: U256) {
+        for signer in &self.signers {
+            if signer.nonce_manager.address() == address {
+                let mut spent = signer
+                    .spent
+                    .lock()
+                    .expect("spent lock is never poisoned; qed");
+                *spent = spent.saturating_add(fee);
+            }
+        }
+    }
+
+    /// Exclude the account from sending transactions until it is funded again,
+    /// that is until its balance goes up regardless of the minimal balance.
+    pub fn mark_unfunded(&self, address: Address) {
+        for signer in &self.signers {
+            if signer.nonce_manager.address() == address {
+                let balance = signer
+                    .spending
+                    .lock()
+                    .expect("spending lock is never poisoned; qed")
+                    .last_balance
+                    .map_or(U256::zero(), |(balance, _)| balance);
+
+                *signer
+                    .unfunded_balance
+                    .lock()
+                    .expect("unfunded balance lock is never poisoned; qed") = Some(balance);
+                signer.funded.store(false, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Take the funded account with the least transactions in flight to send
+    /// a transaction from, `None` if there are no funded accounts.
+    ///
+    /// Accounts that have spent their gas budgets are only taken for `urgent` transactions.
+    pub fn lease(self: &Arc<Self>, urgent: bool) -> Option<SignerLease<S>> {
+        let (index, signer) = self
+            .signers
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.funded.load(Ordering::Relaxed) && (urgent || s.within_budget()))
+            .min_by_key(|(_, s)| s.in_flight.load(Ordering::Relaxed))?;
+
+        signer.in_flight.fetch_add(1, Ordering::Relaxed);
+
+        Some(SignerLease {
+            pool: self.clone(),
+            index,
+        })
+    }
+}
+
+/// An account of a [`SignerPool`] taken to send a transaction from.
+///
+/// The account is counted as having a transaction in flight until dropped.
+pub struct SignerLease<S> {
+    pool: Arc<SignerPool<S>>,
+    index: usize,
+}
+
+impl<S> SignerLease<S> {
+    /// The address of the account.
+    pub fn address(&self) -> Address {
+        self.nonce_manager().address()
+    }
+
+    /// The middleware signing transactions with the account.
+    pub fn client(&self) -> Arc<S> {
+        self.pool.signers[self.index].client.clone()
+    }
+
+    /// The [`NonceManager`] of the account.
+    pub fn nonce_manager(&self) -> &NonceManager {
+        &self.pool.signers[self.index].nonce_manager
+    }
+}
+
+impl<S> Drop for SignerLease<S> {
+    fn drop(&mut self) {
+        self.pool.signers[self.index]
+            .in_flight
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn wei_to_f64(wei: U256) -> f64 {
+    ethers::utils::format_units(wei, "wei")
+        .ok()
+        .and_then(|w| w.parse().ok())
+        .unwrap_or(f64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::providers::{JsonRpcError, MockProvider, MockResponse, Provider};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const ETH: u64 = 1_000_000_000_000_000_000;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= b.abs() * 1e-9, "{a} is not close to {b}");
+    }
+
+    #[test]
+    fn spending_averages_spend_rate() {
+        let start = Instant::now();
+        let mut spending = Spending::default();
+
+        spending.update(U256::from(10 * ETH), start);
+        assert_eq!(spending.rate, 0.0);
+
+        // Spending for the whole period moves the average by 1 - 1/e of the way.
+        spending.update(U256::from(9 * ETH), start + SPEND_RATE_PERIOD);
+        let rate = ETH as f64 / SPEND_RATE_PERIOD.as_secs_f64();
+        let alpha = 1.0 - (-1.0f64).exp();
+        assert_close(spending.rate, alpha * rate);
+    }
+
+    #[test]
+    fn spending_ignores_top_ups_and_close_updates() {
+        let start = Instant::now();
+        let mut spending = Spending::default();
+
+        spending.update(U256::from(10 * ETH), start);
+        spending.update(U256::from(9 * ETH), start + SPEND_RATE_PERIOD);
+        let rate = spending.rate;
+
+        // Updates less than a second apart are skipped.
+        spending.update(
+            U256::zero(),
+            start + SPEND_RATE_PERIOD + Duration::from_millis(500),
+        );
+        assert_eq!(spending.rate, rate);
+        assert_eq!(
+            spending.last_balance.map(|(b, _)| b),
+            Some(U256::from(9 * ETH))
+        );
+
+        // A top up decays the rate as if nothing has been spent.
+        spending.update(U256::from(11 * ETH), start + SPEND_RATE_PERIOD * 2);
+        assert_close(spending.rate, rate * (-1.0f64).exp());
+    }
+
+    type TestPool = SignerPool<Provider<MockProvider>>;
+
+    fn signer_pool(accounts: usize, min_balance: u64) -> (Arc<TestPool>, Vec<MockProvider>) {
+        let mocks: Vec<_> = (0..accounts).map(|_| MockProvider::new()).collect();
+        let signers = mocks
+            .iter()
+            .map(|m| (Address::random(), Arc::new(Provider::new(m.clone()))))
+            .collect();
+
+        (
+            Arc::new(SignerPool::new(signers, min_balance.into())),
+            mocks,
+        )
+    }
+
+    async fn refresh(pool: &TestPool, mocks: &[MockProvider], balances: &[u64]) {
+        for (mock, balance) in mocks.iter().zip(balances) {
+            mock.push(U256::from(*balance)).unwrap();
+        }
+        pool.refresh_balances().await;
+    }
+
+    #[test]
+    fn lease_takes_accounts_with_least_transactions_in_flight() {
+        let (pool, _) = signer_pool(2, 0);
+
+        let first = pool.lease(false).unwrap();
+        let second = pool.lease(false).unwrap();
+        assert_ne!(first.address(), second.address());
+
+        // Dropped leases no longer count as transactions in flight.
+        let second_address = second.address();
+        drop(second);
+        assert_eq!(pool.lease(false).unwrap().address(), second_address);
+    }
+
+    #[test]
+    fn lease_takes_accounts_out_of_budget_for_urgent_transactions_only() {
+        let (pool, _) = signer_pool(1, 0);
+        let address = pool.lease(false).unwrap().address();
+
+        let pool = Arc::new(
+            Arc::into_inner(pool)
+                .unwrap()
+                .with_daily_budgets(Some(100.into()), HashMap::new()),
+        );
+        pool.record_spent(address, 100.into());
+
+        assert!(!pool.within_budget());
+        assert!(pool.lease(false).is_none());
+        assert_eq!(pool.lease(true).unwrap().address(), address);
+    }
+
+    #[tokio::test]
+    async fn lease_skips_unfunded_accounts() {
+        let (pool, mocks) = signer_pool(2, 10);
+
+        refresh(&pool, &mocks, &[9, 10]).await;
+        let funded = pool.lease(false).unwrap().address();
+        assert_eq!(pool.lease(false).unwrap().address(), funded);
+
+        pool.mark_unfunded(funded);
+        assert!(!pool.is_funded());
+        assert!(pool.lease(true).is_none());
+    }
+
+    #[tokio::test]
+    async fn marked_unfunded_accounts_stay_unfunded_until_topped_up() {
+        let (pool, mocks) = signer_pool(1, 0);
+
+        refresh(&pool, &mocks, &[100]).await;
+        let address = pool.lease(false).unwrap().address();
+        pool.mark_unfunded(address);
+
+        // Any balance is above the minimal one, yet not enough to send transactions.
+        refresh(&pool, &mocks, &[100]).await;
+        assert!(pool.lease(false).is_none());
+
+        refresh(&pool, &mocks, &[90]).await;
+        assert!(pool.lease(false).is_none());
+
+        // The balance goes up from the lowest one since.
+        refresh(&pool, &mocks, &[95]).await;
+        assert_eq!(pool.lease(false).unwrap().address(), address);
+
+        refresh(&pool, &mocks, &[80]).await;
+        assert!(pool.is_funded());
+    }
+
+    #[tokio::test]
+    async fn refresh_balances_skips_failed_accounts() {
+        let failing = MockProvider::new();
+        let unfunded = MockProvider::new();
+        let (failing_address, unfunded_address) = (Address::random(), Address::random());
+
+        let pool = SignerPool::new(
+            vec![
+                (failing_address, Arc::new(Provider::new(failing.clone()))),
+                (unfunded_address, Arc::new(Provider::new(unfunded.clone()))),
+            ],
+            U256::from(ETH),
+        );
+
+        failing.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "unavailable".into(),
+            data: None,
+        }));
+        unfunded.push(U256::from(ETH - 1)).unwrap();
+
+        pool.refresh_balances().await;
+
+        assert!(pool.is_funded());
+        assert_eq!(pool.unfunded_accounts(), vec![unfunded_address]);
+    }
+}
@@ -5,7 +5,9 @@ import '@typechain/hardhat';
 
 const config = {
     ZKSYNC_ADDRESS: process.env.CONTRACTS_DIAMOND_PROXY_ADDR,
-    ERC20_BRIDGE_ADDRESS: process.env.CONTRACTS_L1_ERC20_BRIDGE_PROXY_ADDR
+    ERC20_BRIDGE_ADDRESS: process.env.CONTRACTS_L1_ERC20_BRIDGE_PROXY_ADDR,
+    // Deployments without a shared bridge only use the legacy finalization flow.
+    SHARED_BRIDGE_ADDRESS: process.env.CONTRACTS_L1_SHARED_BRIDGE_PROXY_ADDR ?? '0x0000000000000000000000000000000000000000'
 };
 
 export default {
@@ -0,0 +1,23 @@
+DROP INDEX fd_l2_block_number_finalization_tx_ix;
+CREATE INDEX fd_l2_block_number_finalization_tx_ix ON finalization_data (l2_block_number, finalization_tx) WHERE finalization_tx IS NULL;
+ALTER TABLE finalization_data DROP COLUMN chain_id;
+
+ALTER TABLE tokens DROP CONSTRAINT tokens_pkey;
+ALTER TABLE tokens DROP COLUMN chain_id;
+ALTER TABLE tokens ADD PRIMARY KEY (l1_token_address, l2_token_address);
+
+ALTER TABLE l2_to_l1_events DROP CONSTRAINT l2_to_l1_events_pkey;
+ALTER TABLE l2_to_l1_events DROP COLUMN chain_id;
+ALTER TABLE l2_to_l1_events ADD PRIMARY KEY (l1_block_number, l2_block_number, tx_number_in_block);
+
+DROP INDEX commit_l1_block_number_not_null;
+CREATE INDEX commit_l1_block_number_not_null ON l2_blocks (commit_l1_block_number) WHERE commit_l1_block_number IS NOT NULL;
+ALTER TABLE l2_blocks DROP CONSTRAINT l2_blocks_pkey;
+ALTER TABLE l2_blocks DROP COLUMN chain_id;
+ALTER TABLE l2_blocks ADD PRIMARY KEY (l2_block_number);
+
+DROP INDEX withdrawals_chain_id_l2_block_number_index;
+CREATE INDEX withdrawals_l2_block_number_index ON withdrawals (l2_block_number);
+ALTER TABLE withdrawals DROP CONSTRAINT withdrawals_pkey;
+ALTER TABLE withdrawals DROP COLUMN chain_id;
+ALTER TABLE withdrawals ADD PRIMARY KEY (tx_hash, event_index_in_tx);
@@ -0,0 +1,30 @@
+-- Rows written before multi-chain support have chain id 0 until
+-- the finalizer assigns them to its primary chain on startup.
+ALTER TABLE withdrawals ADD COLUMN chain_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE withdrawals ALTER COLUMN chain_id DROP DEFAULT;
+ALTER TABLE withdrawals DROP CONSTRAINT withdrawals_pkey;
+ALTER TABLE withdrawals ADD PRIMARY KEY (chain_id, tx_hash, event_index_in_tx);
+DROP INDEX withdrawals_l2_block_number_index;
+CREATE INDEX withdrawals_chain_id_l2_block_number_index ON withdrawals (chain_id, l2_block_number);
+
+ALTER TABLE l2_blocks ADD COLUMN chain_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE l2_blocks ALTER COLUMN chain_id DROP DEFAULT;
+ALTER TABLE l2_blocks DROP CONSTRAINT l2_blocks_pkey;
+ALTER TABLE l2_blocks ADD PRIMARY KEY (chain_id, l2_block_number);
+DROP INDEX commit_l1_block_number_not_null;
+CREATE INDEX commit_l1_block_number_not_null ON l2_blocks (chain_id, commit_l1_block_number) WHERE commit_l1_block_number IS NOT NULL;
+
+ALTER TABLE l2_to_l1_events ADD COLUMN chain_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE l2_to_l1_events ALTER COLUMN chain_id DROP DEFAULT;
+ALTER TABLE l2_to_l1_events DROP CONSTRAINT l2_to_l1_events_pkey;
+ALTER TABLE l2_to_l1_events ADD PRIMARY KEY (chain_id, l1_block_number, l2_block_number, tx_number_in_block);
+
+ALTER TABLE tokens ADD COLUMN chain_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE tokens ALTER COLUMN chain_id DROP DEFAULT;
+ALTER TABLE tokens DROP CONSTRAINT tokens_pkey;
+ALTER TABLE tokens ADD PRIMARY KEY (chain_id, l1_token_address, l2_token_address);
+
+ALTER TABLE finalization_data ADD COLUMN chain_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE finalization_data ALTER COLUMN chain_id DROP DEFAULT;
+DROP INDEX fd_l2_block_number_finalization_tx_ix;
+CREATE INDEX fd_l2_block_number_finalization_tx_ix ON finalization_data (chain_id, l2_block_number, finalization_tx) WHERE finalization_tx IS NULL;
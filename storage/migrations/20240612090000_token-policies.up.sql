@@ -0,0 +1,16 @@
+-- Finalization policies of tokens changeable at runtime.
+CREATE TABLE token_policies
+(
+    chain_id BIGINT NOT NULL,
+    l2_token_address BYTEA NOT NULL,
+    -- TRUE allows, FALSE denies finalization of the token, once any token
+    -- of a chain is allowed only the allowed ones are finalized.
+    allowed BOOLEAN,
+    -- Minimal amount of a finalized withdrawal in the smallest units of the token.
+    threshold NUMERIC(80),
+    -- Withdrawals of tokens with higher priority are finalized first.
+    priority INT NOT NULL DEFAULT 0,
+    paused BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, l2_token_address)
+);
@@ -0,0 +1,3 @@
+ALTER TABLE finalization_data DROP COLUMN IF EXISTS finalization_fee;
+ALTER TABLE finalization_data DROP COLUMN IF EXISTS finalization_gas;
+DROP TABLE IF EXISTS finalization_transactions;
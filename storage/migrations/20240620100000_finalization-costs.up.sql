@@ -0,0 +1,20 @@
+-- Transactions withdrawals have been finalized in and what they have cost.
+CREATE TABLE finalization_transactions
+(
+    chain_id BIGINT NOT NULL,
+    tx_hash BYTEA NOT NULL,
+    -- The account the transaction has been sent from.
+    account BYTEA NOT NULL,
+    gas_used NUMERIC(80) NOT NULL,
+    effective_gas_price NUMERIC(80) NOT NULL,
+    -- Fee paid for the transaction in wei, `gas_used * effective_gas_price`.
+    fee NUMERIC(80) NOT NULL,
+    batch_size INT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (chain_id, tx_hash)
+);
+
+-- The share of the finalization transaction of a withdrawal, apportioned
+-- by the gas predicted to be spent on finalizing the withdrawal.
+ALTER TABLE finalization_data ADD COLUMN finalization_gas NUMERIC(80);
+ALTER TABLE finalization_data ADD COLUMN finalization_fee NUMERIC(80);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS finalization_data_finalization_tx_idx;
+DROP INDEX IF EXISTS finalization_transactions_created_at_idx;
+DROP TABLE IF EXISTS circuit_breaker_trips;
@@ -0,0 +1,18 @@
+-- Trips of the circuit breaker pausing finalization of a token or of all tokens of a chain.
+CREATE TABLE circuit_breaker_trips
+(
+    id BIGSERIAL PRIMARY KEY,
+    chain_id BIGINT NOT NULL,
+    -- L1 address of the paused token, NULL if finalization of all tokens is paused.
+    l1_token BYTEA,
+    reason TEXT NOT NULL,
+    tripped_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    -- Set by an admin to resume finalization.
+    reset_at TIMESTAMP
+);
+
+CREATE UNIQUE INDEX circuit_breaker_trips_active_idx ON circuit_breaker_trips (chain_id, COALESCE(l1_token, ''::BYTEA))
+WHERE reset_at IS NULL;
+
+CREATE INDEX finalization_transactions_created_at_idx ON finalization_transactions (chain_id, created_at);
+CREATE INDEX finalization_data_finalization_tx_idx ON finalization_data (finalization_tx);
@@ -0,0 +1,13 @@
+-- Withdrawals that are finalized only once approved by an operator.
+CREATE TABLE withdrawal_approvals
+(
+    withdrawal_id BIGINT PRIMARY KEY,
+    status TEXT NOT NULL DEFAULT 'approval_required'
+        CHECK (status IN ('approval_required', 'approved', 'rejected')),
+    requested_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    -- Identity of the operator that has approved or rejected the withdrawal.
+    operator TEXT,
+    decided_at TIMESTAMP,
+
+    FOREIGN KEY (withdrawal_id) REFERENCES withdrawals (id)
+);
@@ -0,0 +1,18 @@
+-- Pauses of finalization set by operators, active until resumed.
+CREATE TABLE finalization_pauses
+(
+    id BIGSERIAL PRIMARY KEY,
+    -- The chain finalization is paused on, NULL for all chains.
+    chain_id BIGINT,
+    -- L1 or L2 address of the paused token, NULL for all tokens.
+    token BYTEA,
+    -- The paused L1 recipient, NULL for all recipients.
+    l1_recipient BYTEA,
+    reason TEXT NOT NULL,
+    paused_by TEXT NOT NULL,
+    paused_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    resumed_by TEXT,
+    resumed_at TIMESTAMP
+);
+
+CREATE INDEX finalization_pauses_active_idx ON finalization_pauses (chain_id) WHERE resumed_at IS NULL;
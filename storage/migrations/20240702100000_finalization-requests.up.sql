@@ -0,0 +1,20 @@
+-- Requests to finalize the withdrawals of a transaction regardless of
+-- the filters the finalizer is configured with.
+CREATE TABLE finalization_requests
+(
+    id BIGSERIAL PRIMARY KEY,
+    chain_id BIGINT NOT NULL,
+    tx_hash BYTEA NOT NULL,
+    -- Identity of the operator or the address of the client requesting finalization.
+    requested_by TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'queued', 'failed')),
+    error TEXT,
+    requested_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    UNIQUE (chain_id, tx_hash)
+);
+
+CREATE INDEX finalization_requests_requested_by_idx ON finalization_requests (requested_by, requested_at);
+CREATE INDEX finalization_requests_pending_idx ON finalization_requests (chain_id) WHERE status = 'pending';
@@ -0,0 +1,20 @@
+-- Finalization transactions sent and not yet mined, the cancellation of which
+-- may be requested by operators.
+CREATE TABLE pending_finalization_transactions
+(
+    id BIGSERIAL PRIMARY KEY,
+    chain_id BIGINT NOT NULL,
+    -- The account the transaction is sent from.
+    account BYTEA NOT NULL,
+    -- IDs of the withdrawals finalized in the transaction.
+    withdrawal_ids BIGINT [] NOT NULL,
+    sent_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    cancellation_requested_by TEXT,
+    cancellation_requested_at TIMESTAMP,
+    -- Set once the transaction is mined, cancelled or fails.
+    resolved_at TIMESTAMP,
+    -- Hash of the zero-value transfer the transaction has been cancelled with.
+    cancelled_in_tx BYTEA
+);
+
+CREATE INDEX pending_finalization_transactions_unresolved_idx ON pending_finalization_transactions (chain_id) WHERE resolved_at IS NULL;
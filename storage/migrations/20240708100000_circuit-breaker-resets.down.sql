@@ -0,0 +1 @@
+ALTER TABLE circuit_breaker_trips DROP COLUMN IF EXISTS reset_by;
@@ -0,0 +1,2 @@
+-- The admin that has reset the trip.
+ALTER TABLE circuit_breaker_trips ADD COLUMN reset_by TEXT;
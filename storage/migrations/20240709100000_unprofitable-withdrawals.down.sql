@@ -0,0 +1,2 @@
+ALTER TABLE finalization_data DROP COLUMN IF EXISTS unprofitable_gas_price;
+ALTER TABLE finalization_data DROP COLUMN IF EXISTS unprofitable_prices;
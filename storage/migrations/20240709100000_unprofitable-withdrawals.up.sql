@@ -0,0 +1,4 @@
+-- Fingerprint of the token prices and the gas price a withdrawal
+-- has last been found unprofitable to finalize at.
+ALTER TABLE finalization_data ADD COLUMN unprofitable_prices BIGINT;
+ALTER TABLE finalization_data ADD COLUMN unprofitable_gas_price NUMERIC(80);
@@ -0,0 +1 @@
+ALTER TABLE pending_finalization_transactions DROP COLUMN IF EXISTS outcome;
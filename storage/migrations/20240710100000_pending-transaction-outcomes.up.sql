@@ -0,0 +1,5 @@
+-- How a pending finalization transaction has been resolved: 'mined', 'reverted',
+-- 'cancelled', 'failed' or 'abandoned' when left pending by a previous run.
+ALTER TABLE pending_finalization_transactions ADD COLUMN outcome TEXT;
+
+UPDATE pending_finalization_transactions SET outcome = 'cancelled' WHERE cancelled_in_tx IS NOT NULL;
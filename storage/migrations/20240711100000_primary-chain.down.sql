@@ -0,0 +1 @@
+DROP TABLE IF EXISTS primary_chain;
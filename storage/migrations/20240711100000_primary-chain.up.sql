@@ -0,0 +1,6 @@
+-- Id of the primary chain the rows written before multi-chain support
+-- have been assigned to, it may not change between runs.
+CREATE TABLE primary_chain (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    chain_id BIGINT NOT NULL
+);
@@ -3,6 +3,9 @@
 pub enum Error {
     #[error(transparent)]
     PgError(#[from] sqlx::Error),
+
+    #[error("primary chain id {configured} differs from {recorded} recorded in the database")]
+    PrimaryChainMismatch { configured: u64, recorded: u64 },
 }
 
 /// Crate result type.
@@ -5,13 +5,15 @@
 
 //! Finalizer watcher.storage.operations.
 
+use std::time::Duration;
+
 use ethers::types::{Address, H160, H256, U256};
 use sqlx::{PgConnection, PgPool};
 
 use chain_events::L2TokenInitEvent;
 use client::{
     is_eth, withdrawal_finalizer::codegen::RequestFinalizeWithdrawal, zksync_contract::L2ToL1Event,
-    WithdrawalEvent, WithdrawalKey, WithdrawalParams,
+    WithdrawalEvent, WithdrawalKey, WithdrawalParams, ETH_TOKEN_ADDRESS,
 };
 
 mod error;
@@ -19,9 +21,13 @@ mod macro_utils;
 mod metrics;
 mod utils;
 
+#[cfg(test)]
+mod tests;
+
 use utils::u256_to_big_decimal;
 
 pub use error::{Error, Result};
+pub use sqlx::types::BigDecimal;
 
 use crate::metrics::STORAGE_METRICS;
 
@@ -29,6 +35,9 @@ use crate::metrics::STORAGE_METRICS;
 /// with index in tx and boolean `is_finalized` value
 #[derive(Debug)]
 pub struct StoredWithdrawal {
+    /// Chain id of the ZK chain the withdrawal has happened on
+    pub chain_id: u64,
+
     /// Withdrawal event
     pub event: WithdrawalEvent,
 
@@ -39,6 +48,7 @@ pub struct StoredWithdrawal {
 /// A new batch with a given range has been committed, update statuses of withdrawal records.
 pub async fn committed_new_batch(
     pool: &PgPool,
+    chain_id: u64,
     batch_start: u64,
     batch_end: u64,
     l1_block_number: u64,
@@ -53,20 +63,23 @@ pub async fn committed_new_batch(
         "
         INSERT INTO
           l2_blocks (
+            chain_id,
             l2_block_number,
             commit_l1_block_number
           )
         SELECT
+          $3,
           u.l2_block_number,
           $2
         FROM
-          UNNEST ($1 :: bigint []) AS u(l2_block_number) ON CONFLICT (l2_block_number) DO
+          UNNEST ($1 :: bigint []) AS u(l2_block_number) ON CONFLICT (chain_id, l2_block_number) DO
         UPDATE
         SET
           commit_l1_block_number = $2
         ",
         &range,
         l1_block_number as i64,
+        chain_id as i64,
     )
     .execute(&mut *tx)
     .await?;
@@ -81,6 +94,7 @@ pub async fn committed_new_batch(
 /// Request the number of L1 block this withdrawal was committed in.
 pub async fn withdrawal_committed_in_block(
     conn: &mut PgConnection,
+    chain_id: u64,
     tx_hash: H256,
 ) -> Result<Option<i64>> {
     let latency = STORAGE_METRICS.call[&"withdrawal_committed_in_block"].start();
@@ -91,10 +105,13 @@ pub async fn withdrawal_committed_in_block(
           l2_blocks.commit_l1_block_number
         FROM
           withdrawals
-          JOIN l2_blocks ON l2_blocks.l2_block_number = withdrawals.l2_block_number
+          JOIN l2_blocks ON l2_blocks.chain_id = withdrawals.chain_id
+          AND l2_blocks.l2_block_number = withdrawals.l2_block_number
         WHERE
-          withdrawals.tx_hash = $1
+          withdrawals.chain_id = $1
+          AND withdrawals.tx_hash = $2
         ",
+        chain_id as i64,
         tx_hash.as_bytes(),
     )
     .fetch_optional(conn)
@@ -109,6 +126,7 @@ pub async fn withdrawal_committed_in_block(
 /// Request the number of L1 block this withdrawal was verified in.
 pub async fn withdrawal_verified_in_block(
     conn: &mut PgConnection,
+    chain_id: u64,
     tx_hash: H256,
 ) -> Result<Option<i64>> {
     let latency = STORAGE_METRICS.call[&"withdrawal_verified_in_block"].start();
@@ -119,10 +137,13 @@ pub async fn withdrawal_verified_in_block(
           l2_blocks.verify_l1_block_number
         FROM
           withdrawals
-          JOIN l2_blocks ON l2_blocks.l2_block_number = withdrawals.l2_block_number
+          JOIN l2_blocks ON l2_blocks.chain_id = withdrawals.chain_id
+          AND l2_blocks.l2_block_number = withdrawals.l2_block_number
         WHERE
-          withdrawals.tx_hash = $1
+          withdrawals.chain_id = $1
+          AND withdrawals.tx_hash = $2
         ",
+        chain_id as i64,
         tx_hash.as_bytes(),
     )
     .fetch_optional(conn)
@@ -137,6 +158,7 @@ pub async fn withdrawal_verified_in_block(
 /// Request the number of L1 block this withdrawal was executed in.
 pub async fn withdrawal_executed_in_block(
     conn: &mut PgConnection,
+    chain_id: u64,
     tx_hash: H256,
 ) -> Result<Option<i64>> {
     let latency = STORAGE_METRICS.call[&"withdrawal_executed_in_block"].start();
@@ -146,10 +168,13 @@ pub async fn withdrawal_executed_in_block(
           l2_blocks.execute_l1_block_number
         FROM
           withdrawals
-          JOIN l2_blocks ON l2_blocks.l2_block_number = withdrawals.l2_block_number
+          JOIN l2_blocks ON l2_blocks.chain_id = withdrawals.chain_id
+          AND l2_blocks.l2_block_number = withdrawals.l2_block_number
         WHERE
-          withdrawals.tx_hash = $1
+          withdrawals.chain_id = $1
+          AND withdrawals.tx_hash = $2
         ",
+        chain_id as i64,
         tx_hash.as_bytes(),
     )
     .fetch_optional(conn)
@@ -163,6 +188,7 @@ pub async fn withdrawal_executed_in_block(
 /// A new batch with a given range has been verified, update statuses of withdrawal records.
 pub async fn verified_new_batch(
     pool: &PgPool,
+    chain_id: u64,
     batch_start: u64,
     batch_end: u64,
     l1_block_number: u64,
@@ -175,20 +201,23 @@ pub async fn verified_new_batch(
         "
         INSERT INTO
           l2_blocks (
+            chain_id,
             l2_block_number,
             verify_l1_block_number
           )
         SELECT
+          $3,
           u.l2_block_number,
           $2
         FROM
-          UNNEST ($1 :: bigint []) AS u(l2_block_number) ON CONFLICT (l2_block_number) DO
+          UNNEST ($1 :: bigint []) AS u(l2_block_number) ON CONFLICT (chain_id, l2_block_number) DO
         UPDATE
         SET
           verify_l1_block_number = $2
         ",
         &range,
         l1_block_number as i64,
+        chain_id as i64,
     )
     .execute(&mut *tx)
     .await?;
@@ -202,6 +231,7 @@ pub async fn verified_new_batch(
 /// A new batch with a given range has been executed, update statuses of withdrawal records.
 pub async fn executed_new_batch(
     pool: &PgPool,
+    chain_id: u64,
     batch_start: u64,
     batch_end: u64,
     l1_block_number: u64,
@@ -214,20 +244,23 @@ pub async fn executed_new_batch(
         "
         INSERT INTO
           l2_blocks (
+            chain_id,
             l2_block_number,
             execute_l1_block_number
           )
         SELECT
+          $3,
           u.l2_block_number,
           $2
         FROM
-          UNNEST ($1 :: bigint []) AS u(l2_block_number) ON CONFLICT (l2_block_number) DO
+          UNNEST ($1 :: bigint []) AS u(l2_block_number) ON CONFLICT (chain_id, l2_block_number) DO
         UPDATE
         SET
           execute_l1_block_number = $2
         ",
         &range,
         l1_block_number as i64,
+        chain_id as i64,
     )
     .execute(&mut *tx)
     .await?;
@@ -259,6 +292,7 @@ pub async fn get_withdrawals(pool: &PgPool, ids: &[i64]) -> Result<Vec<StoredWit
     .await?
     .into_iter()
     .map(|r| StoredWithdrawal {
+        chain_id: r.chain_id as u64,
         event: WithdrawalEvent {
             tx_hash: H256::from_slice(&r.tx_hash),
             block_number: r.l2_block_number as u64,
@@ -282,6 +316,7 @@ pub async fn get_withdrawals(pool: &PgPool, ids: &[i64]) -> Result<Vec<StoredWit
 /// * `conn`: Connection to the Postgres DB
 /// * `events`: Withdrawal events grouped with their indices in transaction.
 pub async fn add_withdrawals(pool: &PgPool, events: &[StoredWithdrawal]) -> Result<()> {
+    let mut chain_ids = Vec::with_capacity(events.len());
     let mut tx_hashes = Vec::with_capacity(events.len());
     let mut block_numbers = Vec::with_capacity(events.len());
     let mut tokens = Vec::with_capacity(events.len());
@@ -290,6 +325,7 @@ pub async fn add_withdrawals(pool: &PgPool, events: &[StoredWithdrawal]) -> Resu
     let mut l1_receivers = Vec::with_capacity(events.len());
 
     events.iter().for_each(|sw| {
+        chain_ids.push(sw.chain_id as i64);
         tx_hashes.push(sw.event.tx_hash.0.to_vec());
         block_numbers.push(sw.event.block_number as i64);
         tokens.push(sw.event.token.0.to_vec());
@@ -309,7 +345,8 @@ pub async fn add_withdrawals(pool: &PgPool, events: &[StoredWithdrawal]) -> Resu
             token,
             amount,
             event_index_in_tx,
-            l1_receiver
+            l1_receiver,
+            chain_id
           )
         SELECT
           u.tx_hash,
@@ -317,7 +354,8 @@ pub async fn add_withdrawals(pool: &PgPool, events: &[StoredWithdrawal]) -> Resu
           u.token,
           u.amount,
           u.index_in_tx,
-          u.l1_receiver
+          u.l1_receiver,
+          u.chain_id
         FROM
           unnest(
             $1 :: BYTEA [],
@@ -325,15 +363,18 @@ pub async fn add_withdrawals(pool: &PgPool, events: &[StoredWithdrawal]) -> Resu
             $3 :: BYTEA [],
             $4 :: numeric [],
             $5 :: integer [],
-            $6 :: BYTEA []
+            $6 :: BYTEA [],
+            $7 :: bigint []
           ) AS u(
             tx_hash,
             l2_block_number,
             token,
             amount,
             index_in_tx,
-            l1_receiver
+            l1_receiver,
+            chain_id
           ) ON CONFLICT (
+            chain_id,
             tx_hash,
             event_index_in_tx
           ) DO NOTHING
@@ -344,6 +385,7 @@ pub async fn add_withdrawals(pool: &PgPool, events: &[StoredWithdrawal]) -> Resu
         amounts.as_slice(),
         &indices_in_tx,
         &l1_receivers as &[Option<Vec<u8>>],
+        &chain_ids,
     )
     .execute(pool)
     .await?;
@@ -354,7 +396,7 @@ pub async fn add_withdrawals(pool: &PgPool, events: &[StoredWithdrawal]) -> Resu
 }
 
 /// Get the block number of the last L2 withdrawal the DB has record of.
-pub async fn last_l2_block_seen(conn: &mut PgConnection) -> Result<Option<u64>> {
+pub async fn last_l2_block_seen(conn: &mut PgConnection, chain_id: u64) -> Result<Option<u64>> {
     let latency = STORAGE_METRICS.call[&"last_l2_block_seen"].start();
 
     let res = sqlx::query!(
@@ -363,7 +405,10 @@ pub async fn last_l2_block_seen(conn: &mut PgConnection) -> Result<Option<u64>>
           max(l2_block_number)
         FROM
           withdrawals
-        "
+        WHERE
+          chain_id = $1
+        ",
+        chain_id as i64,
     )
     .fetch_one(conn)
     .await?
@@ -376,7 +421,7 @@ pub async fn last_l2_block_seen(conn: &mut PgConnection) -> Result<Option<u64>>
 }
 
 /// Get the block number of the last L1 block seen.
-pub async fn last_l1_block_seen(conn: &mut PgConnection) -> Result<Option<u64>> {
+pub async fn last_l1_block_seen(conn: &mut PgConnection, chain_id: u64) -> Result<Option<u64>> {
     let latency = STORAGE_METRICS.call[&"last_l1_block_seen"].start();
 
     let res = sqlx::query!(
@@ -385,7 +430,10 @@ pub async fn last_l1_block_seen(conn: &mut PgConnection) -> Result<Option<u64>>
           max(commit_l1_block_number)
         FROM
           l2_blocks
-        "
+        WHERE
+          chain_id = $1
+        ",
+        chain_id as i64,
     )
     .fetch_one(conn)
     .await?
@@ -398,7 +446,10 @@ pub async fn last_l1_block_seen(conn: &mut PgConnection) -> Result<Option<u64>>
 }
 
 /// Get the last block seen for the `l2_to_l1_events` set
-pub async fn last_l2_to_l1_events_block_seen(conn: &mut PgConnection) -> Result<Option<u64>> {
+pub async fn last_l2_to_l1_events_block_seen(
+    conn: &mut PgConnection,
+    chain_id: u64,
+) -> Result<Option<u64>> {
     let latency = STORAGE_METRICS.call[&"last_l2_to_l1_events_block_seen"].start();
 
     let res = sqlx::query!(
@@ -407,7 +458,10 @@ pub async fn last_l2_to_l1_events_block_seen(conn: &mut PgConnection) -> Result<
           max(l1_block_number)
         FROM
           l2_to_l1_events
-        "
+        WHERE
+          chain_id = $1
+        ",
+        chain_id as i64,
     )
     .fetch_one(conn)
     .await?
@@ -424,8 +478,9 @@ pub async fn last_l2_to_l1_events_block_seen(conn: &mut PgConnection) -> Result<
 /// # Arguments
 ///
 /// * `conn`: Connection to the Postgres DB
+/// * `chain_id`: Chain id of the ZK chain the events are of
 /// * `events`: The `L2ToL1Event`s
-pub async fn l2_to_l1_events(pool: &PgPool, events: &[L2ToL1Event]) -> Result<()> {
+pub async fn l2_to_l1_events(pool: &PgPool, chain_id: u64, events: &[L2ToL1Event]) -> Result<()> {
     let mut l1_token_addrs = Vec::with_capacity(events.len());
     let mut to_addrs = Vec::with_capacity(events.len());
     let mut amounts = Vec::with_capacity(events.len());
@@ -453,7 +508,8 @@ pub async fn l2_to_l1_events(pool: &PgPool, events: &[L2ToL1Event]) -> Result<()
             amount,
             l1_block_number,
             l2_block_number,
-            tx_number_in_block
+            tx_number_in_block,
+            chain_id
           )
         SELECT
           u.l1_token_addr,
@@ -461,7 +517,8 @@ pub async fn l2_to_l1_events(pool: &PgPool, events: &[L2ToL1Event]) -> Result<()
           u.amount,
           u.l1_block_number,
           u.l2_block_number,
-          u.tx_number_in_block
+          u.tx_number_in_block,
+          $7
         FROM
           unnest(
             $1 :: BYTEA [],
@@ -478,6 +535,7 @@ pub async fn l2_to_l1_events(pool: &PgPool, events: &[L2ToL1Event]) -> Result<()
             l2_block_number,
             tx_number_in_block
           ) ON CONFLICT (
+            chain_id,
             l1_block_number,
             l2_block_number,
             tx_number_in_block
@@ -489,6 +547,7 @@ pub async fn l2_to_l1_events(pool: &PgPool, events: &[L2ToL1Event]) -> Result<()
         &l1_block_numbers,
         &l2_block_numbers,
         &tx_numbers_in_block,
+        chain_id as i64,
     )
     .execute(pool)
     .await?;
@@ -499,7 +558,7 @@ pub async fn l2_to_l1_events(pool: &PgPool, events: &[L2ToL1Event]) -> Result<()
 }
 
 /// Get addresses of known tokens on L2 and the last seen block.
-pub async fn get_tokens(pool: &PgPool) -> Result<(Vec<Address>, u64)> {
+pub async fn get_tokens(pool: &PgPool, chain_id: u64) -> Result<(Vec<Address>, u64)> {
     let latency = STORAGE_METRICS.call[&"get_tokens"].start();
     let last_l2_block_seen = sqlx::query!(
         "
@@ -507,7 +566,10 @@ pub async fn get_tokens(pool: &PgPool) -> Result<(Vec<Address>, u64)> {
           max(l2_block_number)
         FROM
           tokens
+        WHERE
+          chain_id = $1
         ",
+        chain_id as i64,
     )
     .fetch_one(pool)
     .await?
@@ -520,7 +582,10 @@ pub async fn get_tokens(pool: &PgPool) -> Result<(Vec<Address>, u64)> {
           l2_token_address
         FROM
           tokens
-        "
+        WHERE
+          chain_id = $1
+        ",
+        chain_id as i64,
     )
     .fetch_all(pool)
     .await?
@@ -533,7 +598,7 @@ pub async fn get_tokens(pool: &PgPool) -> Result<(Vec<Address>, u64)> {
 }
 
 /// Insert a token initialization event into the DB.
-pub async fn add_token(pool: &PgPool, token: &L2TokenInitEvent) -> Result<()> {
+pub async fn add_token(pool: &PgPool, chain_id: u64, token: &L2TokenInitEvent) -> Result<()> {
     let latency = STORAGE_METRICS.call[&"add_token"].start();
 
     sqlx::query!(
@@ -546,10 +611,11 @@ pub async fn add_token(pool: &PgPool, token: &L2TokenInitEvent) -> Result<()> {
             symbol,
             decimals,
             l2_block_number,
-            initialization_transaction
+            initialization_transaction,
+            chain_id
           )
         VALUES
-          ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (l1_token_address, l2_token_address) DO NOTHING
+          ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (chain_id, l1_token_address, l2_token_address) DO NOTHING
         ",
         token.l1_token_address.0.to_vec(),
         token.l2_token_address.0.to_vec(),
@@ -558,6 +624,125 @@ pub async fn add_token(pool: &PgPool, token: &L2TokenInitEvent) -> Result<()> {
         token.decimals as i64,
         token.l2_block_number as i64,
         token.initialization_transaction.0.to_vec(),
+        chain_id as i64,
+    )
+    .execute(pool)
+    .await?;
+
+    latency.observe();
+
+    Ok(())
+}
+
+/// A finalization policy of a token.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenPolicy {
+    /// L2 address of the token
+    pub l2_token_address: Address,
+
+    /// Whether the token is explicitly allowed or denied to be finalized.
+    ///
+    /// Once any token of a chain is allowed only the allowed tokens are finalized.
+    pub allowed: Option<bool>,
+
+    /// Minimal amount of a finalized withdrawal in the smallest units of the token
+    pub threshold: Option<U256>,
+
+    /// Withdrawals of tokens with higher priority are finalized first
+    pub priority: i32,
+
+    /// Finalization of the token is paused
+    pub paused: bool,
+}
+
+/// A minimal amount of finalized withdrawals of a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenThreshold {
+    /// L1 or L2 address of the token
+    pub token: Address,
+
+    /// The amount in units of the token, e.g. `100` for 100 USDC, converted
+    /// into the smallest units with the decimals of the token in `tokens` table
+    pub amount: BigDecimal,
+}
+
+// Splits thresholds into arrays of addresses and amounts to bind to queries.
+fn unzip_thresholds(thresholds: &[TokenThreshold]) -> (Vec<Vec<u8>>, Vec<BigDecimal>) {
+    thresholds
+        .iter()
+        .map(|t| (t.token.as_bytes().to_vec(), t.amount.clone()))
+        .unzip()
+}
+
+/// Get finalization policies of tokens of a chain.
+pub async fn token_policies(pool: &PgPool, chain_id: u64) -> Result<Vec<TokenPolicy>> {
+    let latency = STORAGE_METRICS.call[&"token_policies"].start();
+
+    let policies = sqlx::query!(
+        "
+        SELECT
+          l2_token_address,
+          allowed,
+          threshold,
+          priority,
+          paused
+        FROM
+          token_policies
+        WHERE
+          chain_id = $1
+        ",
+        chain_id as i64,
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| TokenPolicy {
+        l2_token_address: Address::from_slice(&r.l2_token_address),
+        allowed: r.allowed,
+        threshold: r.threshold.map(utils::bigdecimal_to_u256),
+        priority: r.priority,
+        paused: r.paused,
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(policies)
+}
+
+/// Insert or replace a finalization policy of a token.
+///
+/// The finalizer applies policies on the next query of withdrawals to finalize.
+pub async fn set_token_policy(pool: &PgPool, chain_id: u64, policy: &TokenPolicy) -> Result<()> {
+    let latency = STORAGE_METRICS.call[&"set_token_policy"].start();
+
+    sqlx::query!(
+        "
+        INSERT INTO
+          token_policies (
+            chain_id,
+            l2_token_address,
+            allowed,
+            threshold,
+            priority,
+            paused
+          )
+        VALUES
+          ($1, $2, $3, $4, $5, $6) ON CONFLICT (chain_id, l2_token_address) DO
+        UPDATE
+        SET
+          allowed = $3,
+          threshold = $4,
+          priority = $5,
+          paused = $6,
+          updated_at = NOW()
+        ",
+        chain_id as i64,
+        policy.l2_token_address.as_bytes(),
+        policy.allowed,
+        policy.threshold.map(u256_to_big_decimal),
+        policy.priority,
+        policy.paused,
     )
     .execute(pool)
     .await?;
@@ -567,6 +752,61 @@ pub async fn add_token(pool: &PgPool, token: &L2TokenInitEvent) -> Result<()> {
     Ok(())
 }
 
+/// Explicitly allow finalization of exactly these tokens of a chain keeping the rest
+/// of their policies, the tokens allowed before and not anymore are no longer allowed.
+pub async fn sync_allowed_tokens(pool: &PgPool, chain_id: u64, tokens: &[Address]) -> Result<()> {
+    let latency = STORAGE_METRICS.call[&"sync_allowed_tokens"].start();
+
+    let tokens: Vec<_> = tokens.iter().map(|t| t.as_bytes().to_vec()).collect();
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "
+        UPDATE
+          token_policies
+        SET
+          allowed = NULL,
+          updated_at = NOW()
+        WHERE
+          chain_id = $1
+          AND allowed
+          AND NOT l2_token_address = ANY ($2 :: BYTEA [])
+        ",
+        chain_id as i64,
+        &tokens,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "
+        INSERT INTO
+          token_policies (chain_id, l2_token_address, allowed)
+        SELECT
+          $1,
+          u.l2_token_address,
+          TRUE
+        FROM
+          UNNEST ($2 :: BYTEA []) AS u(l2_token_address) ON CONFLICT (chain_id, l2_token_address) DO
+        UPDATE
+        SET
+          allowed = TRUE,
+          updated_at = NOW()
+        ",
+        chain_id as i64,
+        &tokens,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    latency.observe();
+
+    Ok(())
+}
+
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub struct WithdrawalWithBlock {
@@ -578,6 +818,7 @@ pub struct WithdrawalWithBlock {
 /// Adds withdrawal information to the `finalization_data` table.
 pub async fn add_withdrawals_data(pool: &PgPool, wd: &[WithdrawalParams]) -> Result<()> {
     let mut ids = Vec::with_capacity(wd.len());
+    let mut chain_ids = Vec::with_capacity(wd.len());
     let mut l2_block_number = Vec::with_capacity(wd.len());
     let mut l1_batch_number = Vec::with_capacity(wd.len());
     let mut l2_message_index = Vec::with_capacity(wd.len());
@@ -588,6 +829,7 @@ pub async fn add_withdrawals_data(pool: &PgPool, wd: &[WithdrawalParams]) -> Res
 
     wd.iter().for_each(|d| {
         ids.push(d.id as i64);
+        chain_ids.push(d.chain_id as i64);
         l2_block_number.push(d.l2_block_number as i64);
         l1_batch_number.push(d.l1_batch_number.as_u64() as i64);
         l2_message_index.push(d.l2_message_index as i32);
@@ -610,7 +852,8 @@ pub async fn add_withdrawals_data(pool: &PgPool, wd: &[WithdrawalParams]) -> Res
             l2_tx_number_in_block,
             message,
             sender,
-            proof
+            proof,
+            chain_id
           )
         SELECT
           u.id,
@@ -620,7 +863,8 @@ pub async fn add_withdrawals_data(pool: &PgPool, wd: &[WithdrawalParams]) -> Res
           u.l2_tx_number_in_block,
           u.message,
           u.sender,
-          u.proof
+          u.proof,
+          u.chain_id
         FROM
           UNNEST (
             $1 :: bigint [],
@@ -630,7 +874,8 @@ pub async fn add_withdrawals_data(pool: &PgPool, wd: &[WithdrawalParams]) -> Res
             $5 :: integer [],
             $6 :: BYTEA [],
             $7 :: BYTEA [],
-            $8 :: BYTEA []
+            $8 :: BYTEA [],
+            $9 :: bigint []
           ) AS u(
             id,
             l2_block_number,
@@ -639,7 +884,8 @@ pub async fn add_withdrawals_data(pool: &PgPool, wd: &[WithdrawalParams]) -> Res
             l2_tx_number_in_block,
             message,
             sender,
-            proof
+            proof,
+            chain_id
           ) ON CONFLICT (withdrawal_id) DO NOTHING
         ",
         &ids,
@@ -649,7 +895,8 @@ pub async fn add_withdrawals_data(pool: &PgPool, wd: &[WithdrawalParams]) -> Res
         &l2_tx_number_in_block,
         &message,
         &sender,
-        &proof
+        &proof,
+        &chain_ids,
     )
     .execute(pool)
     .await?;
@@ -660,8 +907,12 @@ pub async fn add_withdrawals_data(pool: &PgPool, wd: &[WithdrawalParams]) -> Res
 }
 
 /// Returns all previously unseen executed events after a given block
+///
+/// Withdrawals of transactions requested to be finalized are left out,
+/// their parameters are fetched on request.
 pub async fn get_withdrawals_with_no_data(
     pool: &PgPool,
+    chain_id: u64,
     limit_by: u64,
 ) -> Result<Vec<WithdrawalWithBlock>> {
     let latency = STORAGE_METRICS.call[&"get_withdrawals_no_data"].start();
@@ -676,14 +927,16 @@ pub async fn get_withdrawals_with_no_data(
         FROM
           withdrawals
         WHERE
-          l2_block_number <= COALESCE(
+          chain_id = $2
+          AND l2_block_number <= COALESCE(
             (
               SELECT
                 MAX(l2_block_number)
               FROM
                 l2_blocks
               WHERE
-                commit_l1_block_number IS NOT NULL
+                chain_id = $2
+                AND commit_l1_block_number IS NOT NULL
             ),
             1
           )
@@ -692,23 +945,46 @@ pub async fn get_withdrawals_with_no_data(
               SELECT
                 MAX(withdrawal_id)
               FROM
-                finalization_data
+                finalization_data fd
+              WHERE
+                chain_id = $2
+                AND NOT EXISTS (
+                  SELECT
+                    1
+                  FROM
+                    withdrawals rw
+                    JOIN finalization_requests r ON r.chain_id = rw.chain_id
+                    AND r.tx_hash = rw.tx_hash
+                  WHERE
+                    rw.id = fd.withdrawal_id
+                )
             ),
             1
           )
           AND finalizable = TRUE
+          AND NOT EXISTS (
+            SELECT
+              1
+            FROM
+              finalization_requests r
+            WHERE
+              r.chain_id = withdrawals.chain_id
+              AND r.tx_hash = withdrawals.tx_hash
+          )
         ORDER BY
           l2_block_number
         LIMIT
           $1
         ",
         limit_by as i64,
+        chain_id as i64,
     )
     .fetch_all(pool)
     .await?
     .into_iter()
     .map(|r| WithdrawalWithBlock {
         key: WithdrawalKey {
+            chain_id,
             tx_hash: H256::from_slice(&r.tx_hash),
             event_index_in_tx: r.event_index_in_tx as u32,
         },
@@ -725,6 +1001,7 @@ pub async fn get_withdrawals_with_no_data(
 /// Set a withdrawals as unfinalizable since we have failed to request parameters
 pub async fn set_withdrawal_unfinalizable(
     pool: &PgPool,
+    chain_id: u64,
     tx_hash: H256,
     event_index_in_tx: usize,
 ) -> Result<()> {
@@ -735,12 +1012,49 @@ pub async fn set_withdrawal_unfinalizable(
             UPDATE withdrawals
             SET finalizable = false 
             WHERE
+              chain_id = $3
+              AND
               tx_hash = $1
               AND
               event_index_in_tx = $2
         ",
         tx_hash.as_bytes(),
         event_index_in_tx as i32,
+        chain_id as i64,
+    )
+    .execute(pool)
+    .await?;
+
+    latency.observe();
+
+    Ok(())
+}
+
+/// Remember that withdrawals are not worth finalizing at the given prices
+/// of tokens identified by their fingerprint and at the given gas price.
+pub async fn set_withdrawals_unprofitable(
+    pool: &PgPool,
+    withdrawal_ids: &[u64],
+    prices: u64,
+    gas_price: U256,
+) -> Result<()> {
+    let withdrawal_ids: Vec<_> = withdrawal_ids.iter().map(|id| *id as i64).collect();
+
+    let latency = STORAGE_METRICS.call[&"set_withdrawals_unprofitable"].start();
+
+    sqlx::query!(
+        "
+        UPDATE
+          finalization_data
+        SET
+          unprofitable_prices = $2,
+          unprofitable_gas_price = $3
+        WHERE
+          withdrawal_id = ANY($1)
+        ",
+        &withdrawal_ids,
+        prices as i64,
+        u256_to_big_decimal(gas_price),
     )
     .execute(pool)
     .await?;
@@ -750,16 +1064,168 @@ pub async fn set_withdrawal_unfinalizable(
     Ok(())
 }
 
-/// Get the earliest withdrawals never attempted to be finalized before
+/// Criteria of selection and ordering of withdrawals to finalize.
+#[derive(Debug, Clone, Default)]
+pub struct FinalizationCriteria {
+    /// Minimal amount of finalized withdrawals of the base token
+    pub base_token_threshold: Option<U256>,
+
+    /// Decimals of the base token
+    pub base_token_decimals: u32,
+
+    /// Minimal amounts of finalized withdrawals of other tokens
+    pub token_thresholds: Vec<TokenThreshold>,
+
+    /// Withdrawals below thresholds executed in L1 blocks up to this one
+    /// are finalized nonetheless
+    pub below_threshold_executed_up_to: Option<u64>,
+
+    /// The current L1 block to measure the age of withdrawals from
+    pub current_l1_block: u64,
+
+    /// Time between L1 blocks to measure the age of withdrawals in
+    pub l1_block_time: Duration,
+
+    /// Withdrawals to these L1 recipients are prioritized
+    pub priority_l1_recipients: Vec<Address>,
+
+    /// Only finalize withdrawals to these L1 recipients
+    pub only_l1_recipients: Option<Vec<Address>>,
+
+    /// Do not finalize withdrawals of these tokens by their L1 or L2 addresses
+    pub paused_tokens: Vec<Address>,
+
+    /// Do not finalize withdrawals to these L1 recipients
+    pub paused_l1_recipients: Vec<Address>,
+
+    /// Do not finalize withdrawals of these tokens by their L1 or L2 addresses
+    /// to these L1 recipients
+    pub paused_token_l1_recipients: Vec<(Address, Address)>,
+
+    /// Withdrawals of the base token of at least this amount are only
+    /// finalized once approved
+    pub approval_base_token_threshold: Option<U256>,
+
+    /// Withdrawals of other tokens of at least these amounts are only
+    /// finalized once approved
+    pub approval_token_thresholds: Vec<TokenThreshold>,
+
+    /// Withdrawals executed in L1 blocks up to this one are close to
+    /// their finalization deadlines and go first
+    pub urgent_executed_up_to: Option<u64>,
+
+    /// Withdrawals found unprofitable at the token prices with this fingerprint
+    /// and at a gas price of at most this one are skipped
+    pub unprofitable_at: Option<(u64, U256)>,
+}
+
+/// A withdrawal returned by [`withdrawals_to_finalize`].
+#[derive(Debug, Clone)]
+pub struct FinalizationCandidate {
+    /// Parameters to finalize the withdrawal with
+    pub params: WithdrawalParams,
+
+    /// The amount of the withdrawal is below the threshold of its token but
+    /// it has been executed long enough ago to be finalized nonetheless
+    pub below_threshold: bool,
+
+    /// The L1 block the withdrawal has been executed in
+    pub executed_l1_block: Option<u64>,
+
+    /// Address of the withdrawn token on L2
+    pub l2_token: Address,
+
+    /// Address of the withdrawn token on L1, `None` for the base token
+    /// and tokens not known yet
+    pub l1_token: Option<Address>,
+
+    /// The amount of the withdrawal in the smallest units of the token
+    pub amount: U256,
+
+    /// Number of decimals of the token
+    pub decimals: u32,
+
+    /// The withdrawal has been requested to be finalized
+    pub requested: bool,
+
+    /// The withdrawal is close to its finalization deadline
+    pub urgent: bool,
+}
+
+/// Withdrawals returned by [`withdrawals_to_finalize`].
+#[derive(Debug, Default)]
+pub struct FinalizationCandidates {
+    /// The withdrawals in the order of finalization
+    pub candidates: Vec<FinalizationCandidate>,
+
+    /// The L1 block the oldest of all withdrawals matching the criteria
+    /// has been executed in, not only of the returned ones
+    pub oldest_executed_l1_block: Option<u64>,
+}
+
+/// Get the withdrawals to finalize in the order they should be finalized in.
+///
+/// Withdrawals of the base token of the chain are only returned
+/// if their amount is at least the base token threshold, of other tokens
+/// if it is at least their [`TokenThreshold`], unless they have been executed
+/// long enough ago. Withdrawals of tokens are also filtered according to
+/// [`TokenPolicy`] of the tokens. Withdrawals above approval thresholds are
+/// only returned once approved with [`decide_withdrawal_approval`].
+///
+/// Withdrawals of transactions requested to be finalized and queued by the finalizer
+/// are returned regardless of thresholds, allowed tokens and `only_l1_recipients`,
+/// subject to pauses, approvals and the rest of the criteria.
+///
+/// The withdrawals close to their finalization deadlines go first, then the
+/// requested ones, then the ones above thresholds, then ones
+/// of tokens with higher [`TokenPolicy`] priority and then ones with higher
+/// priority score. The score
+/// is measured in hours of waiting for finalization:
+/// * every hour since the execution of the withdrawal adds a point,
+/// * every tenfold of the amount in units of the token adds a point,
+/// * a withdrawal to one of priority recipients gets 24 points,
+/// * every previous failed attempt to finalize the withdrawal takes 24 points.
+///
+/// The ties are broken by the order of the withdrawals.
 pub async fn withdrawals_to_finalize(
     pool: &PgPool,
+    chain_id: u64,
     limit_by: u64,
-    eth_threshold: Option<U256>,
-    only_l1_recipients: Option<&[Address]>,
-) -> Result<Vec<WithdrawalParams>> {
+    criteria: &FinalizationCriteria,
+) -> Result<FinalizationCandidates> {
     let latency = STORAGE_METRICS.call[&"withdrawals_to_finalize"].start();
-    // if no threshold, query _all_ ethereum withdrawals since all of them are >= 0.
-    let eth_threshold = eth_threshold.unwrap_or(U256::zero());
+    // if no threshold, query _all_ base token withdrawals since all of them are >= 0.
+    let base_token_threshold = criteria.base_token_threshold.unwrap_or(U256::zero());
+    let (threshold_tokens, threshold_amounts) = unzip_thresholds(&criteria.token_thresholds);
+    let (approval_threshold_tokens, approval_threshold_amounts) =
+        unzip_thresholds(&criteria.approval_token_thresholds);
+    let approval_base_token_threshold = criteria
+        .approval_base_token_threshold
+        .map(u256_to_big_decimal);
+    let priority_l1_recipients: Vec<_> = criteria
+        .priority_l1_recipients
+        .iter()
+        .map(|r| r.as_bytes().to_vec())
+        .collect();
+    let paused_tokens: Vec<_> = criteria
+        .paused_tokens
+        .iter()
+        .map(|t| t.as_bytes().to_vec())
+        .collect();
+    let paused_l1_recipients: Vec<_> = criteria
+        .paused_l1_recipients
+        .iter()
+        .map(|r| r.as_bytes().to_vec())
+        .collect();
+    let (paused_pair_tokens, paused_pair_l1_recipients): (Vec<_>, Vec<_>) = criteria
+        .paused_token_l1_recipients
+        .iter()
+        .map(|(t, r)| (t.as_bytes().to_vec(), r.as_bytes().to_vec()))
+        .unzip();
+    let unprofitable_prices = criteria.unprofitable_at.map(|(prices, _)| prices as i64);
+    let unprofitable_gas_price = criteria
+        .unprofitable_at
+        .map(|(_, gas_price)| u256_to_big_decimal(gas_price));
     struct WithdrawalParamsInner {
         tx_hash: Vec<u8>,
         event_index_in_tx: i32,
@@ -771,6 +1237,15 @@ pub async fn withdrawals_to_finalize(
         message: Vec<u8>,
         sender: Vec<u8>,
         proof: Vec<u8>,
+        below_threshold: bool,
+        executed_l1_block: Option<i64>,
+        oldest_executed_l1_block: Option<i64>,
+        l2_token: Vec<u8>,
+        l1_token: Option<Vec<u8>>,
+        amount: BigDecimal,
+        decimals: i32,
+        requested: bool,
+        urgent: bool,
     }
 
     let query = match_query_as!(
@@ -787,11 +1262,103 @@ pub async fn withdrawals_to_finalize(
                 l2_tx_number_in_block,
                 message,
                 sender,
-                proof
+                proof,
+                NOT (th.meets_threshold OR rq.requested) AS "below_threshold!",
+                b.execute_l1_block_number AS "executed_l1_block?",
+                MIN(b.execute_l1_block_number) OVER () AS oldest_executed_l1_block,
+                w.token AS l2_token,
+                CASE WHEN w.token = $4 THEN NULL ELSE tk.l1_token_address END AS "l1_token?",
+                w.amount,
+                COALESCE(CASE WHEN w.token = $4 THEN $9 :: INT END, tk.decimals, 18) AS "decimals!",
+                rq.requested AS "requested!",
+                COALESCE(b.execute_l1_block_number <= $16, FALSE) AS "urgent!"
             FROM
                 finalization_data
             JOIN withdrawals w ON finalization_data.withdrawal_id = w.id
+            LEFT JOIN token_policies p ON p.chain_id = w.chain_id AND p.l2_token_address = w.token
+            LEFT JOIN l2_blocks b ON b.chain_id = w.chain_id
+                AND b.l2_block_number = finalization_data.l2_block_number
+            LEFT JOIN LATERAL (
+                SELECT
+                    t.l1_token_address,
+                    t.decimals
+                FROM
+                    tokens t
+                WHERE
+                    t.chain_id = $3
+                    AND t.l2_token_address = w.token
+                ORDER BY
+                    t.decimals DESC
+                LIMIT 1
+            ) tk ON TRUE
+            CROSS JOIN LATERAL (
+                SELECT
+                    (
+                        CASE WHEN w.token = $4 THEN w.amount >= $2
+                        ELSE TRUE
+                        END
+                    )
+                    AND
+                    w.amount >= COALESCE(p.threshold, 0)
+                    AND
+                    w.amount >= COALESCE(
+                        (
+                            SELECT
+                                MAX(u.amount * POWER(10 :: NUMERIC, t.decimals))
+                            FROM
+                                UNNEST ($5 :: BYTEA [], $6 :: NUMERIC []) AS u(token, amount)
+                                JOIN tokens t ON u.token IN (t.l1_token_address, t.l2_token_address)
+                            WHERE
+                                t.chain_id = $3
+                                AND t.l2_token_address = w.token
+                        ),
+                        0
+                    ) AS meets_threshold
+            ) th
+            CROSS JOIN LATERAL (
+                SELECT
+                    COALESCE(w.token = $4 AND w.amount >= $13, FALSE)
+                    OR
+                    COALESCE(
+                        w.amount >= (
+                            SELECT
+                                MIN(u.amount * POWER(
+                                    10 :: NUMERIC,
+                                    COALESCE(CASE WHEN w.token = $4 THEN $9 :: INT END, tk.decimals, 18)
+                                ))
+                            FROM
+                                UNNEST ($14 :: BYTEA [], $15 :: NUMERIC []) AS u(token, amount)
+                            WHERE
+                                u.token IN (w.token, tk.l1_token_address)
+                        ),
+                        FALSE
+                    ) AS requires_approval
+            ) ap
+            CROSS JOIN LATERAL (
+                SELECT
+                    EXISTS (
+                        SELECT 1 FROM finalization_requests r
+                        WHERE r.chain_id = w.chain_id AND r.tx_hash = w.tx_hash AND r.status = 'queued'
+                    ) AS requested
+            ) rq
+            CROSS JOIN LATERAL (
+                SELECT
+                    GREATEST($8 :: BIGINT - COALESCE(b.execute_l1_block_number, $8 :: BIGINT), 0) * $17 :: BIGINT / 3600.0
+                    +
+                    LOG(
+                        1 + w.amount / POWER(
+                            10 :: NUMERIC,
+                            COALESCE(CASE WHEN w.token = $4 THEN $9 :: INT END, tk.decimals, 18)
+                        )
+                    )
+                    +
+                    CASE WHEN w.l1_receiver = ANY($10 :: BYTEA []) THEN 24 ELSE 0 END
+                    -
+                    24 * COALESCE(failed_finalization_attempts, 0) AS score
+            ) s
             WHERE
+                finalization_data.chain_id = $3
+                AND
                 finalization_tx IS NULL
                 AND
                 failed_finalization_attempts < 3
@@ -803,6 +1370,8 @@ pub async fn withdrawals_to_finalize(
                         FROM
                         l2_blocks
                             WHERE
+                        chain_id = $3
+                        AND
                         execute_l1_block_number IS NOT NULL
                     ),
                     1
@@ -814,62 +1383,181 @@ pub async fn withdrawals_to_finalize(
                     last_finalization_attempt < NOW() - INTERVAL '1 minutes'
                 )
                 AND
+                NOT COALESCE(p.paused, FALSE)
+                AND
+                NOT EXISTS (
+                    SELECT 1 FROM withdrawal_approvals a
+                    WHERE a.withdrawal_id = w.id AND a.status <> 'approved'
+                )
+                AND
+                (
+                    NOT ap.requires_approval
+                    OR
+                    EXISTS (
+                        SELECT 1 FROM withdrawal_approvals a
+                        WHERE a.withdrawal_id = w.id AND a.status = 'approved'
+                    )
+                )
+                AND
+                NOT (
+                    w.token = ANY($11 :: BYTEA [])
+                    OR
+                    COALESCE(tk.l1_token_address = ANY($11 :: BYTEA []), FALSE)
+                )
+                AND
+                NOT COALESCE(w.l1_receiver = ANY($12 :: BYTEA []), FALSE)
+                AND
+                NOT EXISTS (
+                    SELECT 1 FROM UNNEST ($18 :: BYTEA [], $19 :: BYTEA []) AS u(token, l1_recipient)
+                    WHERE u.token IN (w.token, tk.l1_token_address) AND u.l1_recipient = w.l1_receiver
+                )
+                AND
+                NOT COALESCE(
+                    finalization_data.unprofitable_prices = $20
+                    AND
+                    finalization_data.unprofitable_gas_price <= $21,
+                    FALSE
+                )
+                AND
+                (
+                    rq.requested
+                    OR
+                    COALESCE(
+                        p.allowed,
+                        NOT EXISTS (
+                            SELECT 1 FROM token_policies WHERE chain_id = $3 AND allowed
+                        )
+                    )
+                )
+                AND
                 (
-                    CASE WHEN token = decode('000000000000000000000000000000000000800A', 'hex') THEN amount >= $2
-                    ELSE TRUE
-                    END
+                    th.meets_threshold
+                    OR
+                    rq.requested
+                    OR
+                    b.execute_l1_block_number <= $7
                 )
           "#,
           _ // Maybe filter by l1 receiver
+          ,
+          r#"
+            ORDER BY
+                NOT COALESCE(b.execute_l1_block_number <= $16, FALSE),
+                NOT rq.requested,
+                NOT th.meets_threshold,
+                COALESCE(p.priority, 0) DESC,
+                s.score DESC,
+                withdrawal_id
+            LIMIT $1
+          "#
         ],
-        match (only_l1_recipients) {
+        match (&criteria.only_l1_recipients) {
             Some(receivers) => (
-                "AND l1_receiver = ANY($3) limit $1";
+                "AND (l1_receiver = ANY($22) OR rq.requested)";
                 limit_by as i64,
-                u256_to_big_decimal(eth_threshold),
+                u256_to_big_decimal(base_token_threshold),
+                chain_id as i64,
+                ETH_TOKEN_ADDRESS.as_bytes(),
+                &threshold_tokens,
+                &threshold_amounts,
+                criteria.below_threshold_executed_up_to.map(|b| b as i64),
+                criteria.current_l1_block as i64,
+                criteria.base_token_decimals as i32,
+                &priority_l1_recipients,
+                &paused_tokens,
+                &paused_l1_recipients,
+                approval_base_token_threshold,
+                &approval_threshold_tokens,
+                &approval_threshold_amounts,
+                criteria.urgent_executed_up_to.map(|b| b as i64),
+                criteria.l1_block_time.as_secs() as i64,
+                &paused_pair_tokens,
+                &paused_pair_l1_recipients,
+                unprofitable_prices,
+                unprofitable_gas_price,
                 &receivers.iter()
                     .map(Address::as_bytes)
                     .collect::<Vec<_>>() as &[&[u8]]
             ),
             None => (
-                "limit $1";
+                "";
                 limit_by as i64,
-                u256_to_big_decimal(eth_threshold)
+                u256_to_big_decimal(base_token_threshold),
+                chain_id as i64,
+                ETH_TOKEN_ADDRESS.as_bytes(),
+                &threshold_tokens,
+                &threshold_amounts,
+                criteria.below_threshold_executed_up_to.map(|b| b as i64),
+                criteria.current_l1_block as i64,
+                criteria.base_token_decimals as i32,
+                &priority_l1_recipients,
+                &paused_tokens,
+                &paused_l1_recipients,
+                approval_base_token_threshold,
+                &approval_threshold_tokens,
+                &approval_threshold_amounts,
+                criteria.urgent_executed_up_to.map(|b| b as i64),
+                criteria.l1_block_time.as_secs() as i64,
+                &paused_pair_tokens,
+                &paused_pair_l1_recipients,
+                unprofitable_prices,
+                unprofitable_gas_price
             ),
         }
     );
 
-    let data = query
+    let mut oldest_executed_l1_block = None;
+
+    let candidates = query
         .fetch_all(pool)
         .await?
         .into_iter()
-        .map(|record| WithdrawalParams {
-            tx_hash: H256::from_slice(&record.tx_hash),
-            event_index_in_tx: record.event_index_in_tx as u32,
-            id: record.withdrawal_id as u64,
-            l2_block_number: record.l2_block_number as u64,
-            l1_batch_number: record.l1_batch_number.into(),
-            l2_message_index: record.l2_message_index as u32,
-            l2_tx_number_in_block: record.l2_tx_number_in_block as u16,
-            message: record.message.into(),
-            sender: Address::from_slice(&record.sender),
-            proof: bincode::deserialize(&record.proof)
-                .expect("storage contains data correctly serialized by bincode; qed"),
+        .map(|record| {
+            oldest_executed_l1_block = record.oldest_executed_l1_block.map(|b| b as u64);
+
+            FinalizationCandidate {
+                params: WithdrawalParams {
+                    chain_id,
+                    tx_hash: H256::from_slice(&record.tx_hash),
+                    event_index_in_tx: record.event_index_in_tx as u32,
+                    id: record.withdrawal_id as u64,
+                    l2_block_number: record.l2_block_number as u64,
+                    l1_batch_number: record.l1_batch_number.into(),
+                    l2_message_index: record.l2_message_index as u32,
+                    l2_tx_number_in_block: record.l2_tx_number_in_block as u16,
+                    message: record.message.into(),
+                    sender: Address::from_slice(&record.sender),
+                    proof: bincode::deserialize(&record.proof)
+                        .expect("storage contains data correctly serialized by bincode; qed"),
+                },
+                below_threshold: record.below_threshold,
+                executed_l1_block: record.executed_l1_block.map(|b| b as u64),
+                l2_token: Address::from_slice(&record.l2_token),
+                l1_token: record.l1_token.map(|t| Address::from_slice(&t)),
+                amount: utils::bigdecimal_to_u256(record.amount),
+                decimals: record.decimals as u32,
+                requested: record.requested,
+                urgent: record.urgent,
+            }
         })
         .collect();
 
     latency.observe();
 
-    Ok(data)
+    Ok(FinalizationCandidates {
+        candidates,
+        oldest_executed_l1_block,
+    })
 }
 
-/// Get the number of ETH withdrawals not yet executed and finalized and above some threshold
+/// Get the number of base token withdrawals not yet executed and finalized and above some threshold
 pub async fn get_unexecuted_withdrawals_count(
     pool: &PgPool,
-    eth_threshold: Option<U256>,
+    chain_id: u64,
+    base_token_threshold: Option<U256>,
 ) -> Result<i64> {
-    // if no threshold, query _all_ ethereum withdrawals since all of them are >= 0.
-    let eth_threshold = eth_threshold.unwrap_or(U256::zero());
+    // if no threshold, query _all_ base token withdrawals since all of them are >= 0.
+    let base_token_threshold = base_token_threshold.unwrap_or(U256::zero());
 
     let count = sqlx::query!(
         "
@@ -879,7 +1567,8 @@ pub async fn get_unexecuted_withdrawals_count(
           finalization_data
           JOIN withdrawals w ON finalization_data.withdrawal_id = w.id
         WHERE
-          finalization_tx IS NULL
+          finalization_data.chain_id = $2
+          AND finalization_tx IS NULL
           AND finalization_data.l2_block_number > COALESCE(
             (
               SELECT
@@ -887,14 +1576,17 @@ pub async fn get_unexecuted_withdrawals_count(
               FROM
                 l2_blocks
               WHERE
-                execute_l1_block_number IS NOT NULL
+                chain_id = $2
+                AND execute_l1_block_number IS NOT NULL
             ),
             1
           )
-          AND token = decode('000000000000000000000000000000000000800A', 'hex') 
+          AND token = $3
           AND amount >= $1
         ",
-        u256_to_big_decimal(eth_threshold),
+        u256_to_big_decimal(base_token_threshold),
+        chain_id as i64,
+        ETH_TOKEN_ADDRESS.as_bytes(),
     )
     .fetch_one(pool)
     .await?;
@@ -902,8 +1594,11 @@ pub async fn get_unexecuted_withdrawals_count(
     Ok(count.count.unwrap_or(0))
 }
 
-/// Get the number of ETH withdrawals executed but not finalized
-pub async fn get_executed_and_not_finalized_withdrawals_count(pool: &PgPool) -> Result<i64> {
+/// Get the number of base token withdrawals executed but not finalized
+pub async fn get_executed_and_not_finalized_withdrawals_count(
+    pool: &PgPool,
+    chain_id: u64,
+) -> Result<i64> {
     let count = sqlx::query!(
         "
         SELECT
@@ -912,7 +1607,8 @@ pub async fn get_executed_and_not_finalized_withdrawals_count(pool: &PgPool) ->
           finalization_data
           JOIN withdrawals w ON finalization_data.withdrawal_id = w.id
         WHERE
-          finalization_tx IS NULL
+          finalization_data.chain_id = $1
+          AND finalization_tx IS NULL
           AND failed_finalization_attempts = 0
           AND finalization_data.l2_block_number <= COALESCE(
             (
@@ -921,12 +1617,15 @@ pub async fn get_executed_and_not_finalized_withdrawals_count(pool: &PgPool) ->
               FROM
                 l2_blocks
               WHERE
-                execute_l1_block_number IS NOT NULL
+                chain_id = $1
+                AND execute_l1_block_number IS NOT NULL
             ),
             1
           )
-          AND token = decode('000000000000000000000000000000000000800A', 'hex') 
+          AND token = $2
         ",
+        chain_id as i64,
+        ETH_TOKEN_ADDRESS.as_bytes(),
     )
     .fetch_one(pool)
     .await?;
@@ -934,6 +1633,89 @@ pub async fn get_executed_and_not_finalized_withdrawals_count(pool: &PgPool) ->
     Ok(count.count.unwrap_or(0))
 }
 
+/// Numbers of unfinalized withdrawals of a token at or above its threshold.
+#[derive(Debug)]
+pub struct UnfinalizedWithdrawalsCount {
+    /// L2 address of the token
+    pub token: Address,
+
+    /// Withdrawals executed but not finalized
+    pub executed: i64,
+
+    /// Withdrawals not yet executed
+    pub unexecuted: i64,
+}
+
+/// Get the numbers of unfinalized withdrawals of tokens with a [`TokenThreshold`]
+/// with amounts at or above the threshold.
+pub async fn get_unfinalized_withdrawals_count_per_token(
+    pool: &PgPool,
+    chain_id: u64,
+    token_thresholds: &[TokenThreshold],
+) -> Result<Vec<UnfinalizedWithdrawalsCount>> {
+    let (threshold_tokens, threshold_amounts) = unzip_thresholds(token_thresholds);
+
+    let counts = sqlx::query!(
+        "
+        SELECT
+          w.token,
+          COUNT(*) FILTER (
+            WHERE
+              finalization_data.l2_block_number <= e.last_executed
+              AND failed_finalization_attempts = 0
+          ) AS \"executed!\",
+          COUNT(*) FILTER (
+            WHERE
+              finalization_data.l2_block_number > e.last_executed
+          ) AS \"unexecuted!\"
+        FROM
+          finalization_data
+          JOIN withdrawals w ON finalization_data.withdrawal_id = w.id
+          JOIN (
+            SELECT
+              t.l2_token_address,
+              MAX(th.amount * POWER(10 :: NUMERIC, t.decimals)) AS threshold
+            FROM
+              UNNEST ($2 :: BYTEA [], $3 :: NUMERIC []) AS th(token, amount)
+              JOIN tokens t ON th.token IN (t.l1_token_address, t.l2_token_address)
+            WHERE
+              t.chain_id = $1
+            GROUP BY
+              t.l2_token_address
+          ) th ON th.l2_token_address = w.token
+          CROSS JOIN (
+            SELECT
+              COALESCE(MAX(l2_block_number), 1) AS last_executed
+            FROM
+              l2_blocks
+            WHERE
+              chain_id = $1
+              AND execute_l1_block_number IS NOT NULL
+          ) e
+        WHERE
+          finalization_data.chain_id = $1
+          AND finalization_tx IS NULL
+          AND w.amount >= th.threshold
+        GROUP BY
+          w.token
+        ",
+        chain_id as i64,
+        &threshold_tokens,
+        &threshold_amounts,
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| UnfinalizedWithdrawalsCount {
+        token: Address::from_slice(&r.token),
+        executed: r.executed,
+        unexecuted: r.unexecuted,
+    })
+    .collect();
+
+    Ok(counts)
+}
+
 /// Fetch finalization parameters for some withdrawal
 pub async fn get_finalize_withdrawal_params(
     pool: &PgPool,
@@ -979,10 +1761,12 @@ pub async fn finalization_data_set_finalized_in_tx(
     withdrawals: &[WithdrawalKey],
     tx_hash: H256,
 ) -> Result<()> {
+    let mut chain_ids = Vec::with_capacity(withdrawals.len());
     let mut tx_hashes = Vec::with_capacity(withdrawals.len());
     let mut event_index_in_tx = Vec::with_capacity(withdrawals.len());
 
     withdrawals.iter().for_each(|w| {
+        chain_ids.push(w.chain_id as i64);
         tx_hashes.push(w.tx_hash.0.to_vec());
         event_index_in_tx.push(w.event_index_in_tx as i32);
     });
@@ -999,7 +1783,8 @@ pub async fn finalization_data_set_finalized_in_tx(
           (
             SELECT
               UNNEST ($2 :: BYTEA []) AS tx_hash,
-              UNNEST ($3 :: integer []) AS event_index_in_tx
+              UNNEST ($3 :: integer []) AS event_index_in_tx,
+              UNNEST ($4 :: bigint []) AS chain_id
           ) AS u
         WHERE
           finalization_data.withdrawal_id = (
@@ -1008,13 +1793,15 @@ pub async fn finalization_data_set_finalized_in_tx(
             FROM
               withdrawals
             WHERE
-              tx_hash = u.tx_hash
+              chain_id = u.chain_id
+              AND tx_hash = u.tx_hash
               AND event_index_in_tx = u.event_index_in_tx
           )
         ",
         &tx_hash.0.as_ref(),
         &tx_hashes,
         &event_index_in_tx,
+        &chain_ids,
     )
     .execute(pool)
     .await?;
@@ -1024,21 +1811,1528 @@ pub async fn finalization_data_set_finalized_in_tx(
     Ok(())
 }
 
-/// Increment unsuccessful transaction attempt count for a set
-/// of withdrawals
-pub async fn inc_unsuccessful_finalization_attempts(
-    pool: &PgPool,
-    withdrawals: &[WithdrawalKey],
-) -> Result<()> {
-    let mut tx_hashes = Vec::with_capacity(withdrawals.len());
-    let mut event_index_in_tx = Vec::with_capacity(withdrawals.len());
+/// A transaction withdrawals have been finalized in.
+#[derive(Debug, Clone)]
+pub struct FinalizationTransaction {
+    /// Chain id of the ZK chain the withdrawals have happened on.
+    pub chain_id: u64,
 
-    withdrawals.iter().for_each(|w| {
-        tx_hashes.push(w.tx_hash.0.to_vec());
-        event_index_in_tx.push(w.event_index_in_tx as i32);
-    });
+    /// Hash of the transaction.
+    pub tx_hash: H256,
 
-    let latency = STORAGE_METRICS.call[&"inc_unsuccessful_finalization_attempts"].start();
+    /// The account the transaction has been sent from.
+    pub account: Address,
+
+    /// Gas used by the transaction.
+    pub gas_used: U256,
+
+    /// The price of gas paid by the transaction.
+    pub effective_gas_price: U256,
+
+    /// Number of withdrawals finalized in the transaction.
+    pub batch_size: usize,
+}
+
+/// The share of a finalization transaction of one withdrawal.
+#[derive(Debug, Clone)]
+pub struct WithdrawalFinalizationCost {
+    /// ID of the withdrawal.
+    pub withdrawal_id: u64,
+
+    /// Gas of the transaction apportioned to the withdrawal.
+    pub gas: U256,
+
+    /// Fee in wei of the transaction apportioned to the withdrawal.
+    pub fee: U256,
+}
+
+/// Record the cost of a finalization transaction and the shares of it of
+/// the withdrawals finalized in it.
+///
+/// The shares add up with the ones of previous transactions of the withdrawals,
+/// i.e. reverted ones.
+///
+/// Returns the fees in wei spent on finalizing withdrawals of each L2 token.
+pub async fn add_finalization_transaction(
+    pool: &PgPool,
+    transaction: &FinalizationTransaction,
+    costs: &[WithdrawalFinalizationCost],
+) -> Result<Vec<(Address, U256)>> {
+    let mut ids = Vec::with_capacity(costs.len());
+    let mut gas = Vec::with_capacity(costs.len());
+    let mut fees = Vec::with_capacity(costs.len());
+
+    costs.iter().for_each(|c| {
+        ids.push(c.withdrawal_id as i64);
+        gas.push(u256_to_big_decimal(c.gas));
+        fees.push(u256_to_big_decimal(c.fee));
+    });
+
+    let latency = STORAGE_METRICS.call[&"add_finalization_transaction"].start();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "
+        INSERT INTO
+          finalization_transactions (
+            chain_id,
+            tx_hash,
+            account,
+            gas_used,
+            effective_gas_price,
+            fee,
+            batch_size
+          )
+        VALUES
+          ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (chain_id, tx_hash) DO NOTHING
+        ",
+        transaction.chain_id as i64,
+        transaction.tx_hash.as_bytes(),
+        transaction.account.as_bytes(),
+        u256_to_big_decimal(transaction.gas_used),
+        u256_to_big_decimal(transaction.effective_gas_price),
+        u256_to_big_decimal(
+            transaction
+                .gas_used
+                .saturating_mul(transaction.effective_gas_price)
+        ),
+        transaction.batch_size as i32,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let fees_by_token = sqlx::query!(
+        "
+        WITH costs AS (
+          UPDATE
+            finalization_data
+          SET
+            finalization_gas = COALESCE(finalization_data.finalization_gas, 0) + u.gas,
+            finalization_fee = COALESCE(finalization_data.finalization_fee, 0) + u.fee
+          FROM
+            (
+              SELECT
+                UNNEST ($1 :: bigint []) AS withdrawal_id,
+                UNNEST ($2 :: NUMERIC []) AS gas,
+                UNNEST ($3 :: NUMERIC []) AS fee
+            ) AS u
+          WHERE
+            finalization_data.withdrawal_id = u.withdrawal_id
+          RETURNING
+            finalization_data.withdrawal_id,
+            u.fee
+        )
+        SELECT
+          w.token,
+          SUM(costs.fee) AS \"fee!\"
+        FROM
+          costs
+          JOIN withdrawals w ON w.id = costs.withdrawal_id
+        GROUP BY
+          w.token
+        ",
+        &ids,
+        &gas,
+        &fees,
+    )
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .map(|r| {
+        (
+            Address::from_slice(&r.token),
+            utils::bigdecimal_to_u256(r.fee),
+        )
+    })
+    .collect();
+
+    tx.commit().await?;
+    latency.observe();
+
+    Ok(fees_by_token)
+}
+
+/// Get the fees in wei of finalization transactions of all chains sent from
+/// each account within the last `period`.
+pub async fn finalization_fees_by_account(
+    pool: &PgPool,
+    period: Duration,
+) -> Result<Vec<(Address, U256)>> {
+    let latency = STORAGE_METRICS.call[&"finalization_fees_by_account"].start();
+
+    let fees = sqlx::query!(
+        "
+        SELECT
+          account,
+          SUM(fee) AS \"fee!\"
+        FROM
+          finalization_transactions
+        WHERE
+          created_at > NOW() - $1 :: DOUBLE PRECISION * INTERVAL '1 second'
+        GROUP BY
+          account
+        ",
+        period.as_secs_f64(),
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| {
+        (
+            Address::from_slice(&r.account),
+            utils::bigdecimal_to_u256(r.fee),
+        )
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(fees)
+}
+
+/// Volume of withdrawals of a token finalized recently.
+#[derive(Debug, Clone)]
+pub struct FinalizedVolume {
+    /// L1 address of the token, the L2 one if the token is not known yet.
+    pub l1_token: Address,
+
+    /// Volume in units of the token finalized within the window since the last reset
+    /// of the circuit breaker of the token.
+    pub volume: f64,
+
+    /// Number of withdrawals finalized within the window since the last reset
+    /// of the circuit breaker of the token.
+    pub withdrawals: u64,
+
+    /// Volume in units of the token finalized within the baseline period before the window.
+    pub baseline_volume: f64,
+
+    /// Number of withdrawals finalized within the baseline period before the window.
+    pub baseline_withdrawals: u64,
+}
+
+/// Get the volumes of withdrawals of each token finalized within the last
+/// `window` and the `baseline_period` before it.
+///
+/// # Arguments
+///
+/// * `base_token`: The L1 address of the base token of the chain
+/// * `base_token_decimals`: The number of decimals of the base token
+pub async fn finalized_volumes(
+    pool: &PgPool,
+    chain_id: u64,
+    base_token: Address,
+    base_token_decimals: u32,
+    window: Duration,
+    baseline_period: Duration,
+) -> Result<Vec<FinalizedVolume>> {
+    let latency = STORAGE_METRICS.call[&"finalized_volumes"].start();
+
+    let volumes = sqlx::query!(
+        "
+        WITH finalized AS (
+          SELECT
+            CASE
+              WHEN w.token = $2 THEN $3
+              ELSE COALESCE(tk.l1_token_address, w.token)
+            END AS l1_token,
+            w.amount / POWER(
+              10 :: NUMERIC,
+              COALESCE(CASE WHEN w.token = $2 THEN $4 :: INT END, tk.decimals, 18)
+            ) AS amount,
+            ft.created_at
+          FROM
+            finalization_transactions ft
+            JOIN finalization_data fd ON fd.chain_id = ft.chain_id
+            AND fd.finalization_tx = ft.tx_hash
+            JOIN withdrawals w ON w.id = fd.withdrawal_id
+            LEFT JOIN LATERAL (
+              SELECT
+                t.l1_token_address,
+                t.decimals
+              FROM
+                tokens t
+              WHERE
+                t.chain_id = $1
+                AND t.l2_token_address = w.token
+              ORDER BY
+                t.decimals DESC
+              LIMIT
+                1
+            ) tk ON TRUE
+          WHERE
+            ft.chain_id = $1
+            AND ft.created_at > NOW() - ($5 :: DOUBLE PRECISION + $6 :: DOUBLE PRECISION) * INTERVAL '1 second'
+        )
+        SELECT
+          f.l1_token AS \"l1_token!\",
+          COALESCE(SUM(f.amount) FILTER (WHERE in_window), 0) :: DOUBLE PRECISION AS \"volume!\",
+          COUNT(*) FILTER (WHERE in_window) AS \"withdrawals!\",
+          COALESCE(SUM(f.amount) FILTER (WHERE in_baseline), 0) :: DOUBLE PRECISION AS \"baseline_volume!\",
+          COUNT(*) FILTER (WHERE in_baseline) AS \"baseline_withdrawals!\"
+        FROM
+          finalized f
+          CROSS JOIN LATERAL (
+            SELECT
+              MAX(c.reset_at) AS reset_at
+            FROM
+              circuit_breaker_trips c
+            WHERE
+              c.chain_id = $1
+              AND (c.l1_token = f.l1_token OR c.l1_token IS NULL)
+          ) r
+          CROSS JOIN LATERAL (
+            SELECT
+              f.created_at > GREATEST(NOW() - $5 :: DOUBLE PRECISION * INTERVAL '1 second', r.reset_at) AS in_window,
+              f.created_at <= NOW() - $5 :: DOUBLE PRECISION * INTERVAL '1 second' AS in_baseline
+          ) p
+        GROUP BY
+          f.l1_token
+        ",
+        chain_id as i64,
+        ETH_TOKEN_ADDRESS.as_bytes(),
+        base_token.as_bytes(),
+        base_token_decimals as i32,
+        window.as_secs_f64(),
+        baseline_period.as_secs_f64(),
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| FinalizedVolume {
+        l1_token: Address::from_slice(&r.l1_token),
+        volume: r.volume,
+        withdrawals: r.withdrawals as u64,
+        baseline_volume: r.baseline_volume,
+        baseline_withdrawals: r.baseline_withdrawals as u64,
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(volumes)
+}
+
+/// Pause finalization of a token by its L1 address, or of all tokens if `None`,
+/// until the trip is reset with [`reset_circuit_breaker_trip`].
+///
+/// Does nothing if there is an active trip of the token already.
+pub async fn trip_circuit_breaker(
+    pool: &PgPool,
+    chain_id: u64,
+    l1_token: Option<Address>,
+    reason: &str,
+) -> Result<()> {
+    let latency = STORAGE_METRICS.call[&"trip_circuit_breaker"].start();
+
+    sqlx::query!(
+        "
+        INSERT INTO
+          circuit_breaker_trips (chain_id, l1_token, reason)
+        VALUES
+          ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        ",
+        chain_id as i64,
+        l1_token.as_ref().map(Address::as_bytes),
+        reason,
+    )
+    .execute(pool)
+    .await?;
+
+    latency.observe();
+
+    Ok(())
+}
+
+/// Get the tokens of a chain finalization of which is paused by the circuit breaker
+/// by their L1 addresses, `None` if finalization of all tokens is paused.
+pub async fn active_circuit_breaker_trips(
+    pool: &PgPool,
+    chain_id: u64,
+) -> Result<Vec<Option<Address>>> {
+    let latency = STORAGE_METRICS.call[&"active_circuit_breaker_trips"].start();
+
+    let trips = sqlx::query!(
+        "
+        SELECT
+          l1_token
+        FROM
+          circuit_breaker_trips
+        WHERE
+          chain_id = $1
+          AND reset_at IS NULL
+        ",
+        chain_id as i64,
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| r.l1_token.map(|t| Address::from_slice(&t)))
+    .collect();
+
+    latency.observe();
+
+    Ok(trips)
+}
+
+/// An active trip of the circuit breaker.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerTrip {
+    /// ID of the trip.
+    pub id: u64,
+
+    /// The chain finalization is paused on.
+    pub chain_id: u64,
+
+    /// L1 address of the paused token, `None` for all tokens.
+    pub l1_token: Option<Address>,
+
+    /// The reason of the trip.
+    pub reason: String,
+
+    /// Number of seconds since the circuit breaker has tripped.
+    pub tripped_secs: u64,
+}
+
+/// Get the active trips of the circuit breaker of a chain, of all chains
+/// if `chain_id` is `None`.
+pub async fn circuit_breaker_trips(
+    pool: &PgPool,
+    chain_id: Option<u64>,
+) -> Result<Vec<CircuitBreakerTrip>> {
+    let latency = STORAGE_METRICS.call[&"circuit_breaker_trips"].start();
+
+    let trips = sqlx::query!(
+        "
+        SELECT
+          id,
+          chain_id,
+          l1_token,
+          reason,
+          EXTRACT(
+            EPOCH
+            FROM
+              NOW() - tripped_at
+          ) :: BIGINT AS \"tripped_secs!\"
+        FROM
+          circuit_breaker_trips
+        WHERE
+          reset_at IS NULL
+          AND (
+            $1 :: BIGINT IS NULL
+            OR chain_id = $1
+          )
+        ORDER BY
+          id
+        ",
+        chain_id.map(|c| c as i64),
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| CircuitBreakerTrip {
+        id: r.id as u64,
+        chain_id: r.chain_id as u64,
+        l1_token: r.l1_token.map(|t| Address::from_slice(&t)),
+        reason: r.reason,
+        tripped_secs: r.tripped_secs as u64,
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(trips)
+}
+
+/// Reset the trip of the circuit breaker with the given ID resuming finalization.
+///
+/// Returns `false` if there is no such active trip.
+pub async fn reset_circuit_breaker_trip(pool: &PgPool, id: u64, reset_by: &str) -> Result<bool> {
+    let latency = STORAGE_METRICS.call[&"reset_circuit_breaker_trip"].start();
+
+    let reset = sqlx::query!(
+        "
+        UPDATE
+          circuit_breaker_trips
+        SET
+          reset_by = $2,
+          reset_at = NOW()
+        WHERE
+          id = $1
+          AND reset_at IS NULL
+        ",
+        id as i64,
+        reset_by,
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    latency.observe();
+
+    Ok(reset > 0)
+}
+
+/// Status of a withdrawal that is finalized only once approved by an operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalStatus {
+    /// The withdrawal awaits a decision of an operator.
+    ApprovalRequired,
+
+    /// The withdrawal has been approved for finalization.
+    Approved,
+
+    /// The withdrawal has been rejected and is never finalized automatically.
+    Rejected,
+}
+
+impl ApprovalStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::ApprovalRequired => "approval_required",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    fn from_db(s: &str) -> Self {
+        match s {
+            "approved" => Self::Approved,
+            "rejected" => Self::Rejected,
+            _ => Self::ApprovalRequired,
+        }
+    }
+}
+
+/// A withdrawal that is finalized only once approved by an operator.
+#[derive(Debug, Clone)]
+pub struct WithdrawalApproval {
+    /// ID of the withdrawal.
+    pub withdrawal_id: u64,
+
+    /// Chain id of the ZK chain the withdrawal has happened on.
+    pub chain_id: u64,
+
+    /// Hash of the withdrawal transaction.
+    pub tx_hash: H256,
+
+    /// Address of the withdrawn token on L2.
+    pub token: Address,
+
+    /// The amount of the withdrawal in the smallest units of the token.
+    pub amount: U256,
+
+    /// The recipient of the withdrawal on L1 if known.
+    pub l1_receiver: Option<Address>,
+
+    /// The status of the approval.
+    pub status: ApprovalStatus,
+
+    /// The operator that has approved or rejected the withdrawal.
+    pub operator: Option<String>,
+
+    /// Number of seconds since the approval has been required.
+    pub waiting_secs: u64,
+}
+
+/// Require approval of an operator to finalize withdrawals of a chain of the
+/// base token of at least `base_token_threshold` or of other tokens of at least
+/// their [`TokenThreshold`], the amounts of tokens not known yet are in units
+/// of 18 decimals.
+///
+/// [`withdrawals_to_finalize`] evaluates the thresholds on its own, this only
+/// records the withdrawals for operators to decide on.
+///
+/// Returns the number of withdrawals approval of which has been newly required.
+pub async fn request_withdrawal_approvals(
+    pool: &PgPool,
+    chain_id: u64,
+    base_token_threshold: Option<U256>,
+    token_thresholds: &[TokenThreshold],
+) -> Result<u64> {
+    let (threshold_tokens, threshold_amounts) = unzip_thresholds(token_thresholds);
+
+    let latency = STORAGE_METRICS.call[&"request_withdrawal_approvals"].start();
+
+    let requested = sqlx::query!(
+        "
+        INSERT INTO
+          withdrawal_approvals (withdrawal_id)
+        SELECT
+          fd.withdrawal_id
+        FROM
+          finalization_data fd
+          JOIN withdrawals w ON w.id = fd.withdrawal_id
+        WHERE
+          fd.chain_id = $1
+          AND fd.finalization_tx IS NULL
+          AND (
+            (
+              w.token = $2
+              AND w.amount >= $3
+            )
+            OR w.amount >= (
+              SELECT
+                MIN(u.amount * POWER(10 :: NUMERIC, COALESCE(t.decimals, 18)))
+              FROM
+                UNNEST ($4 :: BYTEA [], $5 :: NUMERIC []) AS u(token, amount)
+                LEFT JOIN tokens t ON t.chain_id = $1
+                AND t.l2_token_address = w.token
+              WHERE
+                u.token IN (w.token, t.l1_token_address)
+            )
+          )
+        ON CONFLICT (withdrawal_id) DO NOTHING
+        ",
+        chain_id as i64,
+        ETH_TOKEN_ADDRESS.as_bytes(),
+        base_token_threshold.map(u256_to_big_decimal),
+        &threshold_tokens,
+        &threshold_amounts,
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    latency.observe();
+
+    Ok(requested)
+}
+
+/// Get the withdrawals with the given approval status, of all chains if `chain_id` is `None`.
+pub async fn withdrawal_approvals(
+    pool: &PgPool,
+    chain_id: Option<u64>,
+    status: ApprovalStatus,
+) -> Result<Vec<WithdrawalApproval>> {
+    let latency = STORAGE_METRICS.call[&"withdrawal_approvals"].start();
+
+    let approvals = sqlx::query!(
+        "
+        SELECT
+          a.withdrawal_id,
+          w.chain_id,
+          w.tx_hash,
+          w.token,
+          w.amount,
+          w.l1_receiver,
+          a.status,
+          a.operator,
+          EXTRACT(
+            EPOCH
+            FROM
+              NOW() - a.requested_at
+          ) :: BIGINT AS \"waiting_secs!\"
+        FROM
+          withdrawal_approvals a
+          JOIN withdrawals w ON w.id = a.withdrawal_id
+        WHERE
+          a.status = $1
+          AND ($2 :: BIGINT IS NULL OR w.chain_id = $2)
+        ORDER BY
+          a.withdrawal_id
+        ",
+        status.as_str(),
+        chain_id.map(|c| c as i64),
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| WithdrawalApproval {
+        withdrawal_id: r.withdrawal_id as u64,
+        chain_id: r.chain_id as u64,
+        tx_hash: H256::from_slice(&r.tx_hash),
+        token: Address::from_slice(&r.token),
+        amount: utils::bigdecimal_to_u256(r.amount),
+        l1_receiver: r.l1_receiver.map(|a| Address::from_slice(&a)),
+        status: ApprovalStatus::from_db(&r.status),
+        operator: r.operator,
+        waiting_secs: r.waiting_secs as u64,
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(approvals)
+}
+
+/// Approve or reject finalization of a withdrawal awaiting approval
+/// recording the identity of the operator.
+///
+/// Returns `false` if the withdrawal does not await approval.
+pub async fn decide_withdrawal_approval(
+    pool: &PgPool,
+    withdrawal_id: u64,
+    approve: bool,
+    operator: &str,
+) -> Result<bool> {
+    let status = if approve {
+        ApprovalStatus::Approved
+    } else {
+        ApprovalStatus::Rejected
+    };
+
+    let latency = STORAGE_METRICS.call[&"decide_withdrawal_approval"].start();
+
+    let decided = sqlx::query!(
+        "
+        UPDATE
+          withdrawal_approvals
+        SET
+          status = $2,
+          operator = $3,
+          decided_at = NOW()
+        WHERE
+          withdrawal_id = $1
+          AND status = 'approval_required'
+        ",
+        withdrawal_id as i64,
+        status.as_str(),
+        operator,
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    latency.observe();
+
+    Ok(decided > 0)
+}
+
+/// A pause of finalization set by an operator.
+#[derive(Debug, Clone)]
+pub struct FinalizationPause {
+    /// ID of the pause.
+    pub id: u64,
+
+    /// The chain finalization is paused on, `None` for all chains.
+    pub chain_id: Option<u64>,
+
+    /// L1 or L2 address of the paused token, `None` for all tokens.
+    pub token: Option<Address>,
+
+    /// The paused L1 recipient, `None` for all recipients.
+    pub l1_recipient: Option<Address>,
+
+    /// The reason of the pause.
+    pub reason: String,
+
+    /// The operator that has paused finalization.
+    pub paused_by: String,
+
+    /// Number of seconds since finalization has been paused.
+    pub paused_secs: u64,
+}
+
+impl FinalizationPause {
+    /// Whether finalization of all withdrawals is paused.
+    pub fn is_global(&self) -> bool {
+        self.token.is_none() && self.l1_recipient.is_none()
+    }
+}
+
+/// Pause finalization of withdrawals of a chain or of all chains if `chain_id` is `None`,
+/// only of the given token and to the given L1 recipient if any.
+///
+/// Returns the ID of the pause to resume finalization with.
+pub async fn pause_finalization(
+    pool: &PgPool,
+    chain_id: Option<u64>,
+    token: Option<Address>,
+    l1_recipient: Option<Address>,
+    reason: &str,
+    paused_by: &str,
+) -> Result<u64> {
+    let latency = STORAGE_METRICS.call[&"pause_finalization"].start();
+
+    let id = sqlx::query!(
+        "
+        INSERT INTO
+          finalization_pauses (chain_id, token, l1_recipient, reason, paused_by)
+        VALUES
+          ($1, $2, $3, $4, $5)
+        RETURNING
+          id
+        ",
+        chain_id.map(|c| c as i64),
+        token.as_ref().map(Address::as_bytes),
+        l1_recipient.as_ref().map(Address::as_bytes),
+        reason,
+        paused_by,
+    )
+    .fetch_one(pool)
+    .await?
+    .id;
+
+    latency.observe();
+
+    Ok(id as u64)
+}
+
+/// Resume finalization paused by the pause with the given ID.
+///
+/// Returns `false` if there is no such active pause.
+pub async fn resume_finalization(pool: &PgPool, id: u64, resumed_by: &str) -> Result<bool> {
+    let latency = STORAGE_METRICS.call[&"resume_finalization"].start();
+
+    let resumed = sqlx::query!(
+        "
+        UPDATE
+          finalization_pauses
+        SET
+          resumed_by = $2,
+          resumed_at = NOW()
+        WHERE
+          id = $1
+          AND resumed_at IS NULL
+        ",
+        id as i64,
+        resumed_by,
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    latency.observe();
+
+    Ok(resumed > 0)
+}
+
+/// Get the active pauses of finalization of a chain including the ones of all chains,
+/// of all chains if `chain_id` is `None`.
+pub async fn active_finalization_pauses(
+    pool: &PgPool,
+    chain_id: Option<u64>,
+) -> Result<Vec<FinalizationPause>> {
+    let latency = STORAGE_METRICS.call[&"active_finalization_pauses"].start();
+
+    let pauses = sqlx::query!(
+        "
+        SELECT
+          id,
+          chain_id,
+          token,
+          l1_recipient,
+          reason,
+          paused_by,
+          EXTRACT(
+            EPOCH
+            FROM
+              NOW() - paused_at
+          ) :: BIGINT AS \"paused_secs!\"
+        FROM
+          finalization_pauses
+        WHERE
+          resumed_at IS NULL
+          AND (
+            $1 :: BIGINT IS NULL
+            OR chain_id IS NULL
+            OR chain_id = $1
+          )
+        ORDER BY
+          id
+        ",
+        chain_id.map(|c| c as i64),
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| FinalizationPause {
+        id: r.id as u64,
+        chain_id: r.chain_id.map(|c| c as u64),
+        token: r.token.map(|t| Address::from_slice(&t)),
+        l1_recipient: r.l1_recipient.map(|a| Address::from_slice(&a)),
+        reason: r.reason,
+        paused_by: r.paused_by,
+        paused_secs: r.paused_secs as u64,
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(pauses)
+}
+
+/// Status of a request to finalize the withdrawals of a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalizationRequestStatus {
+    /// The transaction awaits indexing and parameters of its withdrawals.
+    Pending,
+
+    /// The withdrawals of the transaction are queued for finalization.
+    Queued,
+
+    /// All of the withdrawals of the transaction have been finalized.
+    Finalized,
+
+    /// The withdrawals of the transaction can not be finalized.
+    Failed,
+}
+
+impl FinalizationRequestStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Queued => "queued",
+            Self::Finalized => "finalized",
+            Self::Failed => "failed",
+        }
+    }
+
+    fn from_db(s: &str) -> Self {
+        match s {
+            "queued" => Self::Queued,
+            "finalized" => Self::Finalized,
+            "failed" => Self::Failed,
+            _ => Self::Pending,
+        }
+    }
+}
+
+impl std::fmt::Display for FinalizationRequestStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A request to finalize the withdrawals of a transaction.
+#[derive(Debug, Clone)]
+pub struct FinalizationRequest {
+    /// ID of the request.
+    pub id: u64,
+
+    /// Chain id of the ZK chain the transaction has happened on.
+    pub chain_id: u64,
+
+    /// Hash of the withdrawal transaction.
+    pub tx_hash: H256,
+
+    /// The operator or the client that has requested finalization.
+    pub requested_by: String,
+
+    /// The status of the request.
+    pub status: FinalizationRequestStatus,
+
+    /// The reason the request has failed with.
+    pub error: Option<String>,
+
+    /// Number of seconds since finalization has been requested.
+    pub waiting_secs: u64,
+}
+
+/// The outcome of [`request_finalization`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalizationRequestOutcome {
+    /// A new request with the given ID has been added.
+    Added(u64),
+
+    /// The transaction has already been requested to be finalized by the request with the given ID.
+    Duplicate(u64),
+
+    /// The requester has made too many requests recently.
+    RateLimited,
+}
+
+/// Request finalization of the withdrawals of a transaction.
+///
+/// Requesting a transaction that has already been requested returns the existing
+/// request unless it has failed, in which case the request is retried. Requesters
+/// may make at most `max_requests_per_hour` requests within an hour if any.
+pub async fn request_finalization(
+    pool: &PgPool,
+    chain_id: u64,
+    tx_hash: H256,
+    requested_by: &str,
+    max_requests_per_hour: Option<u64>,
+) -> Result<FinalizationRequestOutcome> {
+    let latency = STORAGE_METRICS.call[&"request_finalization"].start();
+
+    let mut tx = pool.begin().await?;
+
+    let added = sqlx::query!(
+        "
+        INSERT INTO
+          finalization_requests (chain_id, tx_hash, requested_by)
+        SELECT
+          $1,
+          $2,
+          $3
+        WHERE
+          $4 :: BIGINT IS NULL
+          OR (
+            SELECT
+              COUNT(*)
+            FROM
+              finalization_requests
+            WHERE
+              requested_by = $3
+              AND requested_at > NOW() - INTERVAL '1 hour'
+          ) < $4
+        ON CONFLICT (chain_id, tx_hash) DO UPDATE
+        SET
+          requested_by = EXCLUDED.requested_by,
+          status = 'pending',
+          error = NULL,
+          requested_at = NOW(),
+          updated_at = NOW()
+        WHERE
+          finalization_requests.status = 'failed'
+        RETURNING
+          id
+        ",
+        chain_id as i64,
+        tx_hash.as_bytes(),
+        requested_by,
+        max_requests_per_hour.map(|m| m as i64),
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let outcome = match added {
+        Some(r) => FinalizationRequestOutcome::Added(r.id as u64),
+        None => sqlx::query!(
+            "
+            SELECT
+              id
+            FROM
+              finalization_requests
+            WHERE
+              chain_id = $1
+              AND tx_hash = $2
+            ",
+            chain_id as i64,
+            tx_hash.as_bytes(),
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .map_or(FinalizationRequestOutcome::RateLimited, |r| {
+            FinalizationRequestOutcome::Duplicate(r.id as u64)
+        }),
+    };
+
+    tx.commit().await?;
+
+    latency.observe();
+
+    Ok(outcome)
+}
+
+/// Get the requests to finalize withdrawals, of all chains if `chain_id` is `None`
+/// and only the ones of the given transaction if `tx_hash` is given.
+///
+/// Requests of which all withdrawals have been finalized are reported as
+/// [`FinalizationRequestStatus::Finalized`].
+pub async fn finalization_requests(
+    pool: &PgPool,
+    chain_id: Option<u64>,
+    tx_hash: Option<H256>,
+) -> Result<Vec<FinalizationRequest>> {
+    let latency = STORAGE_METRICS.call[&"finalization_requests"].start();
+
+    let requests = sqlx::query!(
+        "
+        SELECT
+          r.id,
+          r.chain_id,
+          r.tx_hash,
+          r.requested_by,
+          CASE
+            WHEN r.status = 'queued'
+            AND NOT EXISTS (
+              SELECT
+                1
+              FROM
+                withdrawals w
+                LEFT JOIN finalization_data fd ON fd.withdrawal_id = w.id
+              WHERE
+                w.chain_id = r.chain_id
+                AND w.tx_hash = r.tx_hash
+                AND fd.finalization_tx IS NULL
+            ) THEN 'finalized'
+            ELSE r.status
+          END AS \"status!\",
+          r.error,
+          EXTRACT(
+            EPOCH
+            FROM
+              NOW() - r.requested_at
+          ) :: BIGINT AS \"waiting_secs!\"
+        FROM
+          finalization_requests r
+        WHERE
+          ($1 :: BIGINT IS NULL OR r.chain_id = $1)
+          AND ($2 :: BYTEA IS NULL OR r.tx_hash = $2)
+        ORDER BY
+          r.id
+        ",
+        chain_id.map(|c| c as i64),
+        tx_hash.as_ref().map(H256::as_bytes),
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| FinalizationRequest {
+        id: r.id as u64,
+        chain_id: r.chain_id as u64,
+        tx_hash: H256::from_slice(&r.tx_hash),
+        requested_by: r.requested_by,
+        status: FinalizationRequestStatus::from_db(&r.status),
+        error: r.error,
+        waiting_secs: r.waiting_secs as u64,
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(requests)
+}
+
+/// Get the pending requests to finalize withdrawals of a chain.
+pub async fn pending_finalization_requests(
+    pool: &PgPool,
+    chain_id: u64,
+) -> Result<Vec<FinalizationRequest>> {
+    let latency = STORAGE_METRICS.call[&"pending_finalization_requests"].start();
+
+    let requests = sqlx::query!(
+        "
+        SELECT
+          id,
+          tx_hash,
+          requested_by,
+          error,
+          EXTRACT(
+            EPOCH
+            FROM
+              NOW() - requested_at
+          ) :: BIGINT AS \"waiting_secs!\"
+        FROM
+          finalization_requests
+        WHERE
+          chain_id = $1
+          AND status = 'pending'
+        ORDER BY
+          id
+        ",
+        chain_id as i64,
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| FinalizationRequest {
+        id: r.id as u64,
+        chain_id,
+        tx_hash: H256::from_slice(&r.tx_hash),
+        requested_by: r.requested_by,
+        status: FinalizationRequestStatus::Pending,
+        error: r.error,
+        waiting_secs: r.waiting_secs as u64,
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(requests)
+}
+
+/// Set the status of a request to finalize withdrawals with the reason it has failed with if any.
+///
+/// A request can not be set to [`FinalizationRequestStatus::Finalized`] as this status
+/// is derived from the withdrawals of the request.
+pub async fn set_finalization_request_status(
+    pool: &PgPool,
+    id: u64,
+    status: FinalizationRequestStatus,
+    error: Option<&str>,
+) -> Result<()> {
+    let latency = STORAGE_METRICS.call[&"set_finalization_request_status"].start();
+
+    sqlx::query!(
+        "
+        UPDATE
+          finalization_requests
+        SET
+          status = $2,
+          error = $3,
+          updated_at = NOW()
+        WHERE
+          id = $1
+        ",
+        id as i64,
+        status.as_str(),
+        error,
+    )
+    .execute(pool)
+    .await?;
+
+    latency.observe();
+
+    Ok(())
+}
+
+/// Get the withdrawals of a transaction that have no finalization parameters yet.
+pub async fn get_withdrawals_of_tx_with_no_data(
+    pool: &PgPool,
+    chain_id: u64,
+    tx_hash: H256,
+) -> Result<Vec<WithdrawalWithBlock>> {
+    let latency = STORAGE_METRICS.call[&"get_withdrawals_of_tx_with_no_data"].start();
+
+    let withdrawals = sqlx::query!(
+        "
+        SELECT
+          w.event_index_in_tx,
+          w.id,
+          w.l2_block_number
+        FROM
+          withdrawals w
+        WHERE
+          w.chain_id = $1
+          AND w.tx_hash = $2
+          AND NOT EXISTS (
+            SELECT
+              1
+            FROM
+              finalization_data fd
+            WHERE
+              fd.withdrawal_id = w.id
+          )
+        ORDER BY
+          w.event_index_in_tx
+        ",
+        chain_id as i64,
+        tx_hash.as_bytes(),
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| WithdrawalWithBlock {
+        key: WithdrawalKey {
+            chain_id,
+            tx_hash,
+            event_index_in_tx: r.event_index_in_tx as u32,
+        },
+        id: r.id as u64,
+        l2_block_number: r.l2_block_number as u64,
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(withdrawals)
+}
+
+/// A finalization transaction sent and not yet mined.
+#[derive(Debug, Clone)]
+pub struct PendingFinalizationTransaction {
+    /// ID of the pending transaction.
+    pub id: u64,
+
+    /// Chain id of the ZK chain the withdrawals have happened on.
+    pub chain_id: u64,
+
+    /// The account the transaction is sent from.
+    pub account: Address,
+
+    /// IDs of the withdrawals finalized in the transaction.
+    pub withdrawal_ids: Vec<u64>,
+
+    /// Number of seconds since the transaction has been sent.
+    pub pending_secs: u64,
+
+    /// The operator that has requested the cancellation of the transaction, if any.
+    pub cancellation_requested_by: Option<String>,
+}
+
+/// Record a finalization transaction of withdrawals with the given IDs sent from `account`.
+///
+/// Returns the ID of the pending transaction to resolve it with once it is mined.
+pub async fn add_pending_finalization_transaction(
+    pool: &PgPool,
+    chain_id: u64,
+    account: Address,
+    withdrawal_ids: &[i64],
+) -> Result<u64> {
+    let latency = STORAGE_METRICS.call[&"add_pending_finalization_transaction"].start();
+
+    let id = sqlx::query!(
+        "
+        INSERT INTO
+          pending_finalization_transactions (chain_id, account, withdrawal_ids)
+        VALUES
+          ($1, $2, $3)
+        RETURNING
+          id
+        ",
+        chain_id as i64,
+        account.as_bytes(),
+        withdrawal_ids,
+    )
+    .fetch_one(pool)
+    .await?
+    .id;
+
+    latency.observe();
+
+    Ok(id as u64)
+}
+
+/// How a pending finalization transaction has been resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingTransactionOutcome {
+    /// The transaction has been mined successfully.
+    Mined,
+
+    /// The transaction has been mined and reverted.
+    Reverted,
+
+    /// The transaction has been replaced with a zero-value transfer.
+    Cancelled,
+
+    /// Sending the transaction or waiting for it has failed.
+    Failed,
+
+    /// The transaction has been left pending by a previous run of the finalizer.
+    Abandoned,
+}
+
+impl PendingTransactionOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mined => "mined",
+            Self::Reverted => "reverted",
+            Self::Cancelled => "cancelled",
+            Self::Failed => "failed",
+            Self::Abandoned => "abandoned",
+        }
+    }
+}
+
+impl std::fmt::Display for PendingTransactionOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Resolve a pending finalization transaction with its outcome once it is mined or has failed,
+/// `cancelled_in_tx` is the hash of the transfer it has been cancelled with if any.
+pub async fn resolve_pending_finalization_transaction(
+    pool: &PgPool,
+    id: u64,
+    outcome: PendingTransactionOutcome,
+    cancelled_in_tx: Option<H256>,
+) -> Result<()> {
+    let latency = STORAGE_METRICS.call[&"resolve_pending_finalization_transaction"].start();
+
+    sqlx::query!(
+        "
+        UPDATE
+          pending_finalization_transactions
+        SET
+          resolved_at = NOW(),
+          outcome = $2,
+          cancelled_in_tx = $3
+        WHERE
+          id = $1
+        ",
+        id as i64,
+        outcome.as_str(),
+        cancelled_in_tx.as_ref().map(H256::as_bytes),
+    )
+    .execute(pool)
+    .await?;
+
+    latency.observe();
+
+    Ok(())
+}
+
+/// Resolve the pending finalization transactions of a chain left pending by a previous
+/// run of the finalizer as abandoned.
+///
+/// Returns the abandoned transactions, their withdrawals are found out to be finalized
+/// once they are predicted to fail.
+pub async fn abandon_pending_finalization_transactions(
+    pool: &PgPool,
+    chain_id: u64,
+) -> Result<Vec<PendingFinalizationTransaction>> {
+    let latency = STORAGE_METRICS.call[&"abandon_pending_finalization_transactions"].start();
+
+    let transactions = sqlx::query!(
+        "
+        UPDATE
+          pending_finalization_transactions
+        SET
+          resolved_at = NOW(),
+          outcome = $2
+        WHERE
+          chain_id = $1
+          AND resolved_at IS NULL
+        RETURNING
+          id,
+          chain_id,
+          account,
+          withdrawal_ids,
+          cancellation_requested_by,
+          EXTRACT(
+            EPOCH
+            FROM
+              NOW() - sent_at
+          ) :: BIGINT AS \"pending_secs!\"
+        ",
+        chain_id as i64,
+        PendingTransactionOutcome::Abandoned.as_str(),
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| PendingFinalizationTransaction {
+        id: r.id as u64,
+        chain_id: r.chain_id as u64,
+        account: Address::from_slice(&r.account),
+        withdrawal_ids: r.withdrawal_ids.into_iter().map(|id| id as u64).collect(),
+        pending_secs: r.pending_secs as u64,
+        cancellation_requested_by: r.cancellation_requested_by,
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(transactions)
+}
+
+/// Get the pending finalization transactions of a chain, of all chains if `chain_id` is `None`.
+pub async fn pending_finalization_transactions(
+    pool: &PgPool,
+    chain_id: Option<u64>,
+) -> Result<Vec<PendingFinalizationTransaction>> {
+    let latency = STORAGE_METRICS.call[&"pending_finalization_transactions"].start();
+
+    let transactions = sqlx::query!(
+        "
+        SELECT
+          id,
+          chain_id,
+          account,
+          withdrawal_ids,
+          cancellation_requested_by,
+          EXTRACT(
+            EPOCH
+            FROM
+              NOW() - sent_at
+          ) :: BIGINT AS \"pending_secs!\"
+        FROM
+          pending_finalization_transactions
+        WHERE
+          resolved_at IS NULL
+          AND (
+            $1 :: BIGINT IS NULL
+            OR chain_id = $1
+          )
+        ORDER BY
+          id
+        ",
+        chain_id.map(|c| c as i64),
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| PendingFinalizationTransaction {
+        id: r.id as u64,
+        chain_id: r.chain_id as u64,
+        account: Address::from_slice(&r.account),
+        withdrawal_ids: r.withdrawal_ids.into_iter().map(|id| id as u64).collect(),
+        pending_secs: r.pending_secs as u64,
+        cancellation_requested_by: r.cancellation_requested_by,
+    })
+    .collect();
+
+    latency.observe();
+
+    Ok(transactions)
+}
+
+/// Request the cancellation of a pending finalization transaction.
+///
+/// Returns `false` if there is no such pending transaction or its
+/// cancellation has already been requested.
+pub async fn request_finalization_transaction_cancellation(
+    pool: &PgPool,
+    id: u64,
+    requested_by: &str,
+) -> Result<bool> {
+    let latency = STORAGE_METRICS.call[&"request_finalization_transaction_cancellation"].start();
+
+    let requested = sqlx::query!(
+        "
+        UPDATE
+          pending_finalization_transactions
+        SET
+          cancellation_requested_by = $2,
+          cancellation_requested_at = NOW()
+        WHERE
+          id = $1
+          AND resolved_at IS NULL
+          AND cancellation_requested_at IS NULL
+        ",
+        id as i64,
+        requested_by,
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    latency.observe();
+
+    Ok(requested > 0)
+}
+
+/// Whether the cancellation of a pending finalization transaction has been requested.
+pub async fn finalization_transaction_cancellation_requested(
+    pool: &PgPool,
+    id: u64,
+) -> Result<bool> {
+    let latency = STORAGE_METRICS.call[&"finalization_transaction_cancellation_requested"].start();
+
+    let requested = sqlx::query!(
+        "
+        SELECT
+          cancellation_requested_at IS NOT NULL AS \"requested!\"
+        FROM
+          pending_finalization_transactions
+        WHERE
+          id = $1
+        ",
+        id as i64,
+    )
+    .fetch_optional(pool)
+    .await?
+    .is_some_and(|r| r.requested);
+
+    latency.observe();
+
+    Ok(requested)
+}
+
+/// Increment unsuccessful transaction attempt count for a set
+/// of withdrawals
+pub async fn inc_unsuccessful_finalization_attempts(
+    pool: &PgPool,
+    withdrawals: &[WithdrawalKey],
+) -> Result<()> {
+    let mut chain_ids = Vec::with_capacity(withdrawals.len());
+    let mut tx_hashes = Vec::with_capacity(withdrawals.len());
+    let mut event_index_in_tx = Vec::with_capacity(withdrawals.len());
+
+    withdrawals.iter().for_each(|w| {
+        chain_ids.push(w.chain_id as i64);
+        tx_hashes.push(w.tx_hash.0.to_vec());
+        event_index_in_tx.push(w.event_index_in_tx as i32);
+    });
+
+    let latency = STORAGE_METRICS.call[&"inc_unsuccessful_finalization_attempts"].start();
 
     sqlx::query!(
         "
@@ -1051,7 +3345,8 @@ pub async fn inc_unsuccessful_finalization_attempts(
           (
             SELECT
               UNNEST ($1 :: BYTEA []) AS tx_hash,
-              UNNEST ($2 :: integer []) AS event_index_in_tx
+              UNNEST ($2 :: integer []) AS event_index_in_tx,
+              UNNEST ($3 :: bigint []) AS chain_id
           ) AS u
         WHERE
           finalization_data.withdrawal_id = (
@@ -1060,12 +3355,14 @@ pub async fn inc_unsuccessful_finalization_attempts(
             FROM
               withdrawals
             WHERE
-              tx_hash = u.tx_hash
+              chain_id = u.chain_id
+              AND tx_hash = u.tx_hash
               AND event_index_in_tx = u.event_index_in_tx
           )
         ",
         &tx_hashes,
         &event_index_in_tx,
+        &chain_ids,
     )
     .execute(pool)
     .await?;
@@ -1080,9 +3377,11 @@ pub async fn inc_unsuccessful_finalization_attempts(
 /// # Arguments
 ///
 /// * `pool` - `PgPool`
+/// * `chain_id` - Chain id of the ZK chain the token is on.
 /// * `token` - L2 token address.
 pub async fn token_decimals_and_l1_address(
     pool: &PgPool,
+    chain_id: u64,
     token: Address,
 ) -> Result<Option<(u32, Address)>> {
     let latency = STORAGE_METRICS.call[&"token_decimals_and_l1_address"].start();
@@ -1095,9 +3394,11 @@ pub async fn token_decimals_and_l1_address(
         FROM
             tokens
         WHERE
-            l2_token_address = $1
+            chain_id = $2
+            AND l2_token_address = $1
         ",
         token.as_bytes(),
+        chain_id as i64,
     )
     .fetch_optional(pool)
     .await?
@@ -1107,6 +3408,75 @@ pub async fn token_decimals_and_l1_address(
     Ok(result)
 }
 
+/// Assign rows written before the support of multiple chains to the primary chain.
+///
+/// Such rows have the chain id of zero. The id of the primary chain is recorded
+/// on the first call and [`Error::PrimaryChainMismatch`] is returned if a later
+/// call passes a different one.
+pub async fn set_chain_id_of_legacy_rows(pool: &PgPool, chain_id: u64) -> Result<()> {
+    let latency = STORAGE_METRICS.call[&"set_chain_id_of_legacy_rows"].start();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO primary_chain (chain_id) VALUES ($1) ON CONFLICT (id) DO NOTHING",
+        chain_id as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let recorded = sqlx::query!("SELECT chain_id FROM primary_chain")
+        .fetch_one(&mut *tx)
+        .await?
+        .chain_id as u64;
+
+    if recorded != chain_id {
+        return Err(Error::PrimaryChainMismatch {
+            configured: chain_id,
+            recorded,
+        });
+    }
+
+    sqlx::query!(
+        "UPDATE withdrawals SET chain_id = $1 WHERE chain_id = 0",
+        chain_id as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE l2_blocks SET chain_id = $1 WHERE chain_id = 0",
+        chain_id as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE l2_to_l1_events SET chain_id = $1 WHERE chain_id = 0",
+        chain_id as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE tokens SET chain_id = $1 WHERE chain_id = 0",
+        chain_id as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE finalization_data SET chain_id = $1 WHERE chain_id = 0",
+        chain_id as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    latency.observe();
+
+    Ok(())
+}
+
 async fn wipe_finalization_data(pool: &PgPool, delete_batch_size: usize) -> Result<()> {
     loop {
         let deleted_ids = sqlx::query!(
@@ -1197,6 +3567,14 @@ async fn wipe_tokens(pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
+async fn wipe_withdrawal_approvals(pool: &PgPool) -> Result<()> {
+    sqlx::query!("DELETE FROM withdrawal_approvals")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
 async fn wipe_withdrawals(pool: &PgPool, delete_batch_size: usize) -> Result<()> {
     loop {
         let deleted_ids = sqlx::query!(
@@ -1234,6 +3612,8 @@ pub async fn delete_db_content(pool: &PgPool, delete_batch_size: usize) -> Resul
 
     wipe_tokens(pool).await?;
 
+    wipe_withdrawal_approvals(pool).await?;
+
     wipe_withdrawals(pool, delete_batch_size).await?;
 
     Ok(())
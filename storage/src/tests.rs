@@ -0,0 +1,660 @@
+use ethers::types::{Address, H256, U256};
+use pretty_assertions::assert_eq;
+use sqlx::PgPool;
+
+use client::{WithdrawalEvent, WithdrawalParams, ETH_TOKEN_ADDRESS};
+
+use crate::*;
+
+const CHAIN_ID: u64 = 324;
+
+// The L1 block all of the withdrawals are executed in.
+const EXECUTED_L1_BLOCK: u64 = 1_000;
+
+// A withdrawal of `amount` of `token` to `l1_receiver` in L2 block `block`
+// executed on L1 with its finalization parameters fetched.
+async fn add_withdrawal(
+    pool: &PgPool,
+    block: u64,
+    token: Address,
+    amount: U256,
+    l1_receiver: Address,
+) -> u64 {
+    let tx_hash = H256::random();
+
+    add_withdrawals(
+        pool,
+        &[StoredWithdrawal {
+            chain_id: CHAIN_ID,
+            event: WithdrawalEvent {
+                tx_hash,
+                block_number: block,
+                token,
+                amount,
+                l1_receiver: Some(l1_receiver),
+            },
+            index_in_tx: 0,
+        }],
+    )
+    .await
+    .unwrap();
+
+    let id: i64 = sqlx::query_scalar("SELECT id FROM withdrawals WHERE tx_hash = $1")
+        .bind(tx_hash.as_bytes())
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+    add_withdrawals_data(
+        pool,
+        &[WithdrawalParams {
+            chain_id: CHAIN_ID,
+            tx_hash,
+            event_index_in_tx: 0,
+            id: id as u64,
+            l2_block_number: block,
+            l1_batch_number: block.into(),
+            l2_message_index: 0,
+            l2_tx_number_in_block: 0,
+            message: Default::default(),
+            sender: Address::random(),
+            proof: vec![],
+        }],
+    )
+    .await
+    .unwrap();
+
+    executed_new_batch(pool, CHAIN_ID, block, block, EXECUTED_L1_BLOCK)
+        .await
+        .unwrap();
+
+    id as u64
+}
+
+async fn add_eth_withdrawal(pool: &PgPool, block: u64, amount: u64) -> u64 {
+    add_withdrawal(
+        pool,
+        block,
+        ETH_TOKEN_ADDRESS,
+        eth(amount),
+        Address::random(),
+    )
+    .await
+}
+
+fn eth(amount: u64) -> U256 {
+    U256::from(amount) * U256::exp10(18)
+}
+
+async fn tx_hash_of(pool: &PgPool, id: u64) -> H256 {
+    let tx_hash: Vec<u8> = sqlx::query_scalar("SELECT tx_hash FROM withdrawals WHERE id = $1")
+        .bind(id as i64)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+    H256::from_slice(&tx_hash)
+}
+
+fn criteria() -> FinalizationCriteria {
+    FinalizationCriteria {
+        base_token_decimals: 18,
+        current_l1_block: EXECUTED_L1_BLOCK,
+        l1_block_time: Duration::from_secs(12),
+        ..Default::default()
+    }
+}
+
+async fn to_finalize(pool: &PgPool, criteria: &FinalizationCriteria) -> Vec<u64> {
+    withdrawals_to_finalize(pool, CHAIN_ID, 100, criteria)
+        .await
+        .unwrap()
+        .candidates
+        .into_iter()
+        .map(|c| c.params.id)
+        .collect()
+}
+
+#[sqlx::test]
+async fn finalizes_withdrawals_in_order_of_priority(pool: PgPool) {
+    let small = add_eth_withdrawal(&pool, 1, 1).await;
+    let large = add_eth_withdrawal(&pool, 2, 1_000).await;
+    let below_threshold = add_eth_withdrawal(&pool, 3, 0).await;
+    let priority_recipient = Address::random();
+    let prioritized = add_withdrawal(&pool, 4, ETH_TOKEN_ADDRESS, eth(1), priority_recipient).await;
+
+    let criteria = FinalizationCriteria {
+        base_token_threshold: Some(eth(1) / 2),
+        below_threshold_executed_up_to: Some(EXECUTED_L1_BLOCK),
+        priority_l1_recipients: vec![priority_recipient],
+        ..criteria()
+    };
+
+    assert_eq!(
+        to_finalize(&pool, &criteria).await,
+        vec![prioritized, large, small, below_threshold]
+    );
+}
+
+#[sqlx::test]
+async fn finalizes_withdrawals_close_to_deadlines_first(pool: PgPool) {
+    add_eth_withdrawal(&pool, 1, 1_000).await;
+    let old = add_eth_withdrawal(&pool, 2, 1).await;
+
+    sqlx::query(
+        "UPDATE l2_blocks SET execute_l1_block_number = 900 \
+         WHERE chain_id = $1 AND l2_block_number = 2",
+    )
+    .bind(CHAIN_ID as i64)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let criteria = FinalizationCriteria {
+        urgent_executed_up_to: Some(950),
+        ..criteria()
+    };
+
+    // Only the first page of withdrawals is returned.
+    let candidates = withdrawals_to_finalize(&pool, CHAIN_ID, 1, &criteria)
+        .await
+        .unwrap()
+        .candidates;
+
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].params.id, old);
+    assert!(candidates[0].urgent);
+}
+
+#[sqlx::test]
+async fn finalizes_requested_withdrawals_first(pool: PgPool) {
+    let large = add_eth_withdrawal(&pool, 1, 1_000).await;
+    let requested = add_eth_withdrawal(&pool, 2, 1).await;
+
+    let tx_hash = tx_hash_of(&pool, requested).await;
+    let FinalizationRequestOutcome::Added(request) =
+        request_finalization(&pool, CHAIN_ID, tx_hash, "test", None)
+            .await
+            .unwrap()
+    else {
+        panic!("finalization has not been requested");
+    };
+    set_finalization_request_status(&pool, request, FinalizationRequestStatus::Queued, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        to_finalize(&pool, &criteria()).await,
+        vec![requested, large]
+    );
+}
+
+#[sqlx::test]
+async fn only_prioritizes_queued_requests(pool: PgPool) {
+    let large = add_eth_withdrawal(&pool, 1, 1_000).await;
+    let requested = add_eth_withdrawal(&pool, 2, 1).await;
+
+    let tx_hash = tx_hash_of(&pool, requested).await;
+    request_finalization(&pool, CHAIN_ID, tx_hash, "test", None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        to_finalize(&pool, &criteria()).await,
+        vec![large, requested]
+    );
+}
+
+#[sqlx::test]
+async fn finalizes_requested_withdrawals_regardless_of_allow_lists(pool: PgPool) {
+    let token = Address::random();
+    let paused_token = Address::random();
+    let recipient = Address::random();
+    let eth_withdrawal = add_withdrawal(&pool, 1, ETH_TOKEN_ADDRESS, eth(1), recipient).await;
+    let not_allowed = add_withdrawal(&pool, 2, token, 1.into(), recipient).await;
+    let other_recipient = add_eth_withdrawal(&pool, 3, 1).await;
+    let paused = add_withdrawal(&pool, 4, paused_token, 1.into(), recipient).await;
+    add_withdrawal(&pool, 5, token, 1.into(), recipient).await;
+
+    for id in [not_allowed, other_recipient, paused] {
+        let tx_hash = tx_hash_of(&pool, id).await;
+        let FinalizationRequestOutcome::Added(request) =
+            request_finalization(&pool, CHAIN_ID, tx_hash, "test", None)
+                .await
+                .unwrap()
+        else {
+            panic!("finalization has not been requested");
+        };
+        set_finalization_request_status(&pool, request, FinalizationRequestStatus::Queued, None)
+            .await
+            .unwrap();
+    }
+
+    sync_allowed_tokens(&pool, CHAIN_ID, &[ETH_TOKEN_ADDRESS])
+        .await
+        .unwrap();
+
+    // Pauses apply to the requested withdrawals nonetheless.
+    let criteria = FinalizationCriteria {
+        only_l1_recipients: Some(vec![recipient]),
+        paused_tokens: vec![paused_token],
+        ..criteria()
+    };
+    let mut to_finalize = to_finalize(&pool, &criteria).await;
+    to_finalize.sort();
+    assert_eq!(
+        to_finalize,
+        vec![eth_withdrawal, not_allowed, other_recipient]
+    );
+}
+
+#[sqlx::test]
+async fn skips_withdrawals_below_thresholds(pool: PgPool) {
+    let small = add_eth_withdrawal(&pool, 1, 1).await;
+    let large = add_eth_withdrawal(&pool, 2, 10).await;
+
+    let criteria = FinalizationCriteria {
+        base_token_threshold: Some(eth(5)),
+        ..criteria()
+    };
+    assert_eq!(to_finalize(&pool, &criteria).await, vec![large]);
+
+    // Old enough withdrawals below thresholds are finalized last.
+    let criteria = FinalizationCriteria {
+        below_threshold_executed_up_to: Some(EXECUTED_L1_BLOCK),
+        ..criteria
+    };
+    assert_eq!(to_finalize(&pool, &criteria).await, vec![large, small]);
+}
+
+#[sqlx::test]
+async fn only_finalizes_allowed_tokens(pool: PgPool) {
+    let token = Address::random();
+    let other_token = Address::random();
+    let eth_withdrawal = add_eth_withdrawal(&pool, 1, 1).await;
+    let allowed = add_withdrawal(&pool, 2, token, 1.into(), Address::random()).await;
+    add_withdrawal(&pool, 3, other_token, 1.into(), Address::random()).await;
+
+    sync_allowed_tokens(&pool, CHAIN_ID, &[ETH_TOKEN_ADDRESS, token])
+        .await
+        .unwrap();
+
+    let mut ids = to_finalize(&pool, &criteria()).await;
+    ids.sort();
+    assert_eq!(ids, vec![eth_withdrawal, allowed]);
+
+    set_token_policy(
+        &pool,
+        CHAIN_ID,
+        &TokenPolicy {
+            l2_token_address: token,
+            allowed: Some(true),
+            priority: 1,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    // Tokens removed from the list are no longer allowed.
+    sync_allowed_tokens(&pool, CHAIN_ID, &[ETH_TOKEN_ADDRESS])
+        .await
+        .unwrap();
+    assert_eq!(to_finalize(&pool, &criteria()).await, vec![eth_withdrawal]);
+
+    let mut policies = token_policies(&pool, CHAIN_ID).await.unwrap();
+    policies.sort_by_key(|p| p.l2_token_address);
+    let mut expected = vec![
+        TokenPolicy {
+            l2_token_address: ETH_TOKEN_ADDRESS,
+            allowed: Some(true),
+            ..Default::default()
+        },
+        TokenPolicy {
+            l2_token_address: token,
+            priority: 1,
+            ..Default::default()
+        },
+    ];
+    expected.sort_by_key(|p| p.l2_token_address);
+    assert_eq!(policies, expected);
+}
+
+#[sqlx::test]
+async fn applies_token_policies(pool: PgPool) {
+    let token = Address::random();
+    let paused_token = Address::random();
+    let eth_withdrawal = add_eth_withdrawal(&pool, 1, 1).await;
+    let prioritized = add_withdrawal(&pool, 2, token, 1.into(), Address::random()).await;
+    add_withdrawal(&pool, 3, paused_token, 1.into(), Address::random()).await;
+
+    set_token_policy(
+        &pool,
+        CHAIN_ID,
+        &TokenPolicy {
+            l2_token_address: token,
+            priority: 1,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    set_token_policy(
+        &pool,
+        CHAIN_ID,
+        &TokenPolicy {
+            l2_token_address: paused_token,
+            paused: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        to_finalize(&pool, &criteria()).await,
+        vec![prioritized, eth_withdrawal]
+    );
+}
+
+#[sqlx::test]
+async fn only_finalizes_approved_withdrawals_above_approval_thresholds(pool: PgPool) {
+    let untracked_token = Address::random();
+    let small = add_eth_withdrawal(&pool, 1, 1).await;
+    let large = add_eth_withdrawal(&pool, 2, 1_000).await;
+    let untracked = add_withdrawal(&pool, 3, untracked_token, eth(50), Address::random()).await;
+
+    let criteria = FinalizationCriteria {
+        approval_base_token_threshold: Some(eth(100)),
+        approval_token_thresholds: vec![TokenThreshold {
+            token: untracked_token,
+            amount: 10.into(),
+        }],
+        ..criteria()
+    };
+
+    // Withdrawals above approval thresholds await approval before it is requested.
+    assert_eq!(to_finalize(&pool, &criteria).await, vec![small]);
+
+    let requested = request_withdrawal_approvals(
+        &pool,
+        CHAIN_ID,
+        criteria.approval_base_token_threshold,
+        &criteria.approval_token_thresholds,
+    )
+    .await
+    .unwrap();
+    assert_eq!(requested, 2);
+    assert_eq!(to_finalize(&pool, &criteria).await, vec![small]);
+
+    let approvals = withdrawal_approvals(&pool, Some(CHAIN_ID), ApprovalStatus::ApprovalRequired)
+        .await
+        .unwrap();
+    assert_eq!(
+        approvals
+            .iter()
+            .map(|a| a.withdrawal_id)
+            .collect::<Vec<_>>(),
+        vec![large, untracked]
+    );
+
+    assert!(decide_withdrawal_approval(&pool, large, true, "operator")
+        .await
+        .unwrap());
+    assert!(
+        decide_withdrawal_approval(&pool, untracked, false, "operator")
+            .await
+            .unwrap()
+    );
+    assert!(
+        !decide_withdrawal_approval(&pool, untracked, true, "operator")
+            .await
+            .unwrap()
+    );
+    assert_eq!(to_finalize(&pool, &criteria).await, vec![large, small]);
+}
+
+#[sqlx::test]
+async fn skips_paused_withdrawals(pool: PgPool) {
+    let token = Address::random();
+    let paused_recipient = Address::random();
+    let eth_withdrawal = add_eth_withdrawal(&pool, 1, 1).await;
+    add_withdrawal(&pool, 2, token, 1.into(), Address::random()).await;
+    add_withdrawal(&pool, 3, ETH_TOKEN_ADDRESS, eth(1), paused_recipient).await;
+
+    let pause = pause_finalization(&pool, Some(CHAIN_ID), Some(token), None, "test", "operator")
+        .await
+        .unwrap();
+    pause_finalization(
+        &pool,
+        Some(CHAIN_ID),
+        None,
+        Some(paused_recipient),
+        "test",
+        "operator",
+    )
+    .await
+    .unwrap();
+
+    let pauses = active_finalization_pauses(&pool, Some(CHAIN_ID))
+        .await
+        .unwrap();
+    assert_eq!(pauses.len(), 2);
+
+    let criteria = FinalizationCriteria {
+        paused_tokens: vec![token],
+        paused_l1_recipients: vec![paused_recipient],
+        ..criteria()
+    };
+    assert_eq!(to_finalize(&pool, &criteria).await, vec![eth_withdrawal]);
+
+    assert!(resume_finalization(&pool, pause, "operator").await.unwrap());
+    assert!(!resume_finalization(&pool, pause, "operator").await.unwrap());
+    assert_eq!(
+        active_finalization_pauses(&pool, Some(CHAIN_ID))
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[sqlx::test]
+async fn skips_withdrawals_of_paused_tokens_to_paused_recipients(pool: PgPool) {
+    let token = Address::random();
+    let recipient = Address::random();
+    add_withdrawal(&pool, 1, token, 1.into(), recipient).await;
+    let other_recipient = add_withdrawal(&pool, 2, token, 1.into(), Address::random()).await;
+    let other_token = add_withdrawal(&pool, 3, ETH_TOKEN_ADDRESS, eth(1), recipient).await;
+
+    let criteria = FinalizationCriteria {
+        paused_token_l1_recipients: vec![(token, recipient)],
+        ..criteria()
+    };
+
+    let mut to_finalize = to_finalize(&pool, &criteria).await;
+    to_finalize.sort();
+    assert_eq!(to_finalize, vec![other_recipient, other_token]);
+}
+
+#[sqlx::test]
+async fn skips_unprofitable_withdrawals_until_prices_change(pool: PgPool) {
+    let unprofitable = add_eth_withdrawal(&pool, 1, 1).await;
+    let other = add_eth_withdrawal(&pool, 2, 1).await;
+
+    set_withdrawals_unprofitable(&pool, &[unprofitable], 1, 10.into())
+        .await
+        .unwrap();
+
+    let at = |prices: u64, gas_price: u64| FinalizationCriteria {
+        unprofitable_at: Some((prices, gas_price.into())),
+        ..criteria()
+    };
+
+    assert_eq!(to_finalize(&pool, &at(1, 10)).await, vec![other]);
+    assert_eq!(to_finalize(&pool, &at(1, 20)).await, vec![other]);
+
+    // Cheaper gas or other prices may make the withdrawal worth finalizing.
+    let mut to_finalize_at_cheaper_gas = to_finalize(&pool, &at(1, 5)).await;
+    to_finalize_at_cheaper_gas.sort();
+    assert_eq!(to_finalize_at_cheaper_gas, vec![unprofitable, other]);
+
+    let mut to_finalize_at_other_prices = to_finalize(&pool, &at(2, 10)).await;
+    to_finalize_at_other_prices.sort();
+    assert_eq!(to_finalize_at_other_prices, vec![unprofitable, other]);
+}
+
+#[sqlx::test]
+async fn deduplicates_and_rate_limits_finalization_requests(pool: PgPool) {
+    let tx_hash = H256::random();
+
+    assert!(matches!(
+        request_finalization(&pool, CHAIN_ID, tx_hash, "client", Some(2))
+            .await
+            .unwrap(),
+        FinalizationRequestOutcome::Added(_)
+    ));
+    assert!(matches!(
+        request_finalization(&pool, CHAIN_ID, tx_hash, "client", Some(2))
+            .await
+            .unwrap(),
+        FinalizationRequestOutcome::Duplicate(_)
+    ));
+    assert!(matches!(
+        request_finalization(&pool, CHAIN_ID, H256::random(), "client", Some(2))
+            .await
+            .unwrap(),
+        FinalizationRequestOutcome::Added(_)
+    ));
+    assert!(matches!(
+        request_finalization(&pool, CHAIN_ID, H256::random(), "client", Some(2))
+            .await
+            .unwrap(),
+        FinalizationRequestOutcome::RateLimited
+    ));
+
+    let pending = pending_finalization_requests(&pool, CHAIN_ID)
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 2);
+}
+
+#[sqlx::test]
+async fn tracks_pending_finalization_transactions(pool: PgPool) {
+    let account = Address::random();
+
+    let id = add_pending_finalization_transaction(&pool, CHAIN_ID, account, &[1, 2])
+        .await
+        .unwrap();
+
+    let pending = pending_finalization_transactions(&pool, Some(CHAIN_ID))
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].account, account);
+    assert_eq!(pending[0].withdrawal_ids, vec![1, 2]);
+    assert!(!finalization_transaction_cancellation_requested(&pool, id)
+        .await
+        .unwrap());
+
+    assert!(
+        request_finalization_transaction_cancellation(&pool, id, "operator")
+            .await
+            .unwrap()
+    );
+    assert!(
+        !request_finalization_transaction_cancellation(&pool, id, "operator")
+            .await
+            .unwrap()
+    );
+    assert!(finalization_transaction_cancellation_requested(&pool, id)
+        .await
+        .unwrap());
+
+    resolve_pending_finalization_transaction(
+        &pool,
+        id,
+        PendingTransactionOutcome::Cancelled,
+        Some(H256::random()),
+    )
+    .await
+    .unwrap();
+    assert!(pending_finalization_transactions(&pool, Some(CHAIN_ID))
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(
+        !request_finalization_transaction_cancellation(&pool, id, "operator")
+            .await
+            .unwrap()
+    );
+}
+
+#[sqlx::test]
+async fn abandons_transactions_left_pending_by_previous_runs(pool: PgPool) {
+    let account = Address::random();
+    let pending = add_pending_finalization_transaction(&pool, CHAIN_ID, account, &[1])
+        .await
+        .unwrap();
+    let mined = add_pending_finalization_transaction(&pool, CHAIN_ID, account, &[2])
+        .await
+        .unwrap();
+    let other_chain = add_pending_finalization_transaction(&pool, CHAIN_ID + 1, account, &[3])
+        .await
+        .unwrap();
+    resolve_pending_finalization_transaction(&pool, mined, PendingTransactionOutcome::Mined, None)
+        .await
+        .unwrap();
+
+    let abandoned = abandon_pending_finalization_transactions(&pool, CHAIN_ID)
+        .await
+        .unwrap();
+    assert_eq!(abandoned.len(), 1);
+    assert_eq!(abandoned[0].id, pending);
+    assert_eq!(abandoned[0].withdrawal_ids, vec![1]);
+
+    let outcomes: Vec<(i64, Option<String>)> =
+        sqlx::query_as("SELECT id, outcome FROM pending_finalization_transactions ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        outcomes,
+        vec![
+            (pending as i64, Some("abandoned".into())),
+            (mined as i64, Some("mined".into())),
+            (other_chain as i64, None),
+        ]
+    );
+}
+
+#[sqlx::test]
+async fn keeps_legacy_rows_on_primary_chain(pool: PgPool) {
+    let id = add_eth_withdrawal(&pool, 1, 1).await;
+    sqlx::query("UPDATE withdrawals SET chain_id = 0 WHERE id = $1")
+        .bind(id as i64)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    set_chain_id_of_legacy_rows(&pool, CHAIN_ID).await.unwrap();
+    set_chain_id_of_legacy_rows(&pool, CHAIN_ID).await.unwrap();
+
+    let chain_id: i64 = sqlx::query_scalar("SELECT chain_id FROM withdrawals WHERE id = $1")
+        .bind(id as i64)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(chain_id as u64, CHAIN_ID);
+
+    let err = set_chain_id_of_legacy_rows(&pool, CHAIN_ID + 1)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::PrimaryChainMismatch {
+            configured,
+            recorded: CHAIN_ID,
+        } if configured == CHAIN_ID + 1
+    ));
+}
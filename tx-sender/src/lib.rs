@@ -5,22 +5,54 @@
 
 //! Wrapper for transaction sending with adjusting a gas price on retries.
 
-use std::time::Duration;
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
 
 use ethers::{
     providers::{Middleware, MiddlewareError, ProviderError},
     types::{
-        transaction::eip2718::TypedTransaction, BlockNumber, Eip2930TransactionRequest,
-        TransactionReceipt, U256,
+        transaction::{eip2718::TypedTransaction, eip2930::AccessList},
+        BlockNumber, Bytes, Eip2930TransactionRequest, TransactionReceipt, H256, U256,
     },
 };
 
 use crate::metrics::TX_SENDER_METRICS;
 
 mod metrics;
+mod nonce_manager;
+
+pub use nonce_manager::NonceManager;
 
 const RETRY_BUMP_FEES_PERCENT: u8 = 15;
 
+/// Gas limit of a zero-value transfer a transaction is cancelled with.
+const CANCELLATION_GAS_LIMIT: u64 = 21_000;
+
+/// Interval between retries of failed requests about a pending transaction.
+const ERROR_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Outcome of a transaction sent with [`send_tx_with_nonce_manager_cancellable`].
+#[derive(Debug, Clone)]
+pub enum TxOutcome {
+    /// The transaction has been mined.
+    Mined(TransactionReceipt),
+
+    /// The transaction has been replaced by a zero-value transfer
+    /// to its sender at the same nonce, the receipt is of the transfer.
+    Cancelled(TransactionReceipt),
+}
+
+impl TxOutcome {
+    /// The receipt of the mined transaction.
+    pub fn receipt(&self) -> &TransactionReceipt {
+        match self {
+            TxOutcome::Mined(receipt) | TxOutcome::Cancelled(receipt) => receipt,
+        }
+    }
+}
+
 /// Bump prices of a `TypedTransaction` depending on its type.
 ///
 /// For non-`eip1559` txs the `gas_price` is bumped by the given percentage.
@@ -106,31 +138,278 @@ where
     submit_tx.set_nonce(nonce);
     submit_tx.set_gas(gas_limit);
 
-    for retry_num in 0..usize::MAX {
-        if retry_num > 0 {
-            bump_predicted_fees(&mut submit_tx, RETRY_BUMP_FEES_PERCENT, &m).await?;
-            submit_tx.set_nonce(nonce);
+    let sent_tx_hash = m.send_transaction(submit_tx.clone(), None).await?.tx_hash();
+
+    wait_for_tx_adjust_gas(m, submit_tx, sent_tx_hash, retry_timeout, never_cancel)
+        .await
+        .map(|outcome| Some(outcome.receipt().clone()))
+}
+
+/// Send a transaction with a nonce from a [`NonceManager`] with specified number of retries.
+///
+/// Unlike [`send_tx_adjust_gas`] transactions sent from the same account
+/// may be in flight concurrently. If the transaction fails to be sent its nonce
+/// is returned to the `nonces`, or filled with a zero-value transfer if the
+/// transactions with later nonces are already in flight.
+///
+/// # Arguments
+///
+/// * `m`: [`Middleware`] to perform request with
+/// * `tx`: Transaction to be sent
+/// * `retry_timeout`: A period after which to retry transaction.
+/// * `nonces`: The [`NonceManager`] of the account sending the transaction.
+/// * `gas_limit`: The gas limit of the transaction.
+pub async fn send_tx_with_nonce_manager<M, T>(
+    m: M,
+    tx: T,
+    retry_timeout: Duration,
+    nonces: &NonceManager,
+    gas_limit: U256,
+) -> Result<Option<TransactionReceipt>, <M as Middleware>::Error>
+where
+    M: Middleware,
+    T: Into<TypedTransaction> + Send + Sync + Clone,
+{
+    send_tx_with_nonce_manager_cancellable(m, tx, retry_timeout, nonces, gas_limit, never_cancel)
+        .await
+        .map(|outcome| Some(outcome.receipt().clone()))
+}
+
+/// Send a transaction like [`send_tx_with_nonce_manager`] that may be cancelled while pending.
+///
+/// Whenever the transaction is not mined within `retry_timeout` the `should_cancel` check
+/// is run, once it returns `true` the transaction is replaced by a zero-value transfer
+/// to its sender at the same nonce instead of being sent again with bumped fees.
+///
+/// # Arguments
+///
+/// * `m`: [`Middleware`] to perform request with
+/// * `tx`: Transaction to be sent
+/// * `retry_timeout`: A period after which to retry transaction.
+/// * `nonces`: The [`NonceManager`] of the account sending the transaction.
+/// * `gas_limit`: The gas limit of the transaction.
+/// * `should_cancel`: Whether the pending transaction should be cancelled.
+pub async fn send_tx_with_nonce_manager_cancellable<M, T, F, Fut>(
+    m: M,
+    tx: T,
+    retry_timeout: Duration,
+    nonces: &NonceManager,
+    gas_limit: U256,
+    should_cancel: F,
+) -> Result<TxOutcome, <M as Middleware>::Error>
+where
+    M: Middleware,
+    T: Into<TypedTransaction> + Send + Sync + Clone,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let mut submit_tx = tx.into();
+    submit_tx.set_from(nonces.address());
+    submit_tx.set_gas(gas_limit);
+
+    let nonce = nonces.next(&m).await?;
+    submit_tx.set_nonce(nonce);
+
+    let sent_tx_hash = match m.fill_transaction(&mut submit_tx, None).await {
+        Ok(()) => m
+            .send_transaction(submit_tx.clone(), None)
+            .await
+            .map(|sent_tx| sent_tx.tx_hash()),
+        Err(e) => Err(e),
+    };
+
+    let sent_tx_hash = match sent_tx_hash {
+        Ok(sent_tx_hash) => sent_tx_hash,
+        Err(e) => {
+            if is_nonce_too_low::<M>(&e) {
+                nonces.resync(&m).await?;
+            } else if !nonces.roll_back(nonce).await && !fill_nonce_gap(&m, submit_tx).await {
+                nonces.release(nonce).await;
+            }
+
+            return Err(e);
         }
+    };
 
-        let sent_tx = m.send_transaction(submit_tx.clone(), None).await?;
+    wait_for_tx_adjust_gas(m, submit_tx, sent_tx_hash, retry_timeout, should_cancel).await
+}
 
-        let tx_hash = sent_tx.tx_hash();
+fn never_cancel() -> std::future::Ready<bool> {
+    std::future::ready(false)
+}
 
-        let result = tokio::time::timeout(retry_timeout, sent_tx).await;
+// Turn a transaction into a zero-value transfer to its sender keeping its nonce and fees.
+fn into_cancellation(tx: &mut TypedTransaction) {
+    let from = *tx.from().expect("sender is set before sending; qed");
 
-        match result {
-            Ok(res) => {
-                let res = res.map_err(MiddlewareError::from_provider_err)?;
-                return Ok(res);
+    tx.set_to(from);
+    tx.set_value(U256::zero());
+    tx.set_data(Bytes::default());
+    tx.set_access_list(AccessList::default());
+    tx.set_gas(CANCELLATION_GAS_LIMIT);
+}
+
+// Wait for a sent transaction to be mined, sending it again with bumped
+// fees every `retry_timeout` and if it gets dropped from the mempool.
+//
+// Once `should_cancel` returns `true` the transaction is replaced with
+// a cancellation which is then waited for in the same way.
+//
+// Failures to reach the node are retried, only if the node rejects the transaction
+// for good the nonce is filled with a zero-value transfer.
+async fn wait_for_tx_adjust_gas<M, F, Fut>(
+    m: M,
+    mut submit_tx: TypedTransaction,
+    sent_tx_hash: H256,
+    retry_timeout: Duration,
+    mut should_cancel: F,
+) -> Result<TxOutcome, <M as Middleware>::Error>
+where
+    M: Middleware,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let nonce = *submit_tx.nonce().expect("nonce is set before sending; qed");
+    let mut sent_tx_hashes = vec![sent_tx_hash];
+    // Index of the first of `sent_tx_hashes` that is a cancellation.
+    let mut cancelled_from = None;
+    // When the transaction is due to be sent again.
+    let mut retry_at = Instant::now() + retry_timeout;
+
+    loop {
+        let tx_hash = *sent_tx_hashes.last().expect("at least one tx is sent; qed");
+        let pending_tx = ethers::providers::PendingTransaction::new(tx_hash, m.provider());
+        let remaining = retry_at.saturating_duration_since(Instant::now());
+
+        match tokio::time::timeout(remaining, pending_tx).await {
+            Ok(Ok(Some(receipt))) => {
+                return Ok(outcome(receipt, sent_tx_hashes.len() - 1, cancelled_from))
+            }
+            Ok(Ok(None)) => {
+                tracing::info!("transaction {tx_hash:?} was dropped from the mempool");
+                TX_SENDER_METRICS.dropped_transactions.inc();
+            }
+            Ok(Err(e)) => {
+                tracing::warn!("failed to get the status of transaction {tx_hash:?}: {e}");
+                TX_SENDER_METRICS.retried_errors.inc();
+
+                tokio::time::sleep(ERROR_RETRY_INTERVAL.min(remaining)).await;
+                if Instant::now() < retry_at {
+                    continue;
+                }
             }
             Err(_e) => {
                 tracing::info!("waiting for mined transaction {tx_hash:?} timed out",);
                 TX_SENDER_METRICS.timedout_transactions.inc();
             }
         }
+
+        if cancelled_from.is_none() && should_cancel().await {
+            tracing::info!("cancelling transaction {tx_hash:?} with nonce {nonce}");
+            TX_SENDER_METRICS.cancelled_transactions.inc();
+
+            into_cancellation(&mut submit_tx);
+            cancelled_from = Some(sent_tx_hashes.len());
+        }
+
+        retry_at = Instant::now() + retry_timeout;
+
+        // The transaction is sent again once it is due to be next time.
+        if let Err(e) = bump_predicted_fees(&mut submit_tx, RETRY_BUMP_FEES_PERCENT, &m).await {
+            tracing::warn!("failed to bump the fees of transaction {tx_hash:?}: {e}");
+            TX_SENDER_METRICS.retried_errors.inc();
+            continue;
+        }
+        submit_tx.set_nonce(nonce);
+
+        match m.send_transaction(submit_tx.clone(), None).await {
+            Ok(sent_tx) => sent_tx_hashes.push(sent_tx.tx_hash()),
+            // One of the sent transactions may have been mined in the meantime.
+            Err(e) if is_nonce_too_low::<M>(&e) => {
+                for (index, tx_hash) in sent_tx_hashes.iter().enumerate().rev() {
+                    if let Some(receipt) = m.get_transaction_receipt(*tx_hash).await? {
+                        return Ok(outcome(receipt, index, cancelled_from));
+                    }
+                }
+
+                return Err(e);
+            }
+            Err(e) if is_rejected::<M>(&e) => {
+                fill_nonce_gap(&m, submit_tx).await;
+                return Err(e);
+            }
+            Err(e) => {
+                tracing::warn!("failed to send transaction with nonce {nonce} again: {e}");
+                TX_SENDER_METRICS.retried_errors.inc();
+            }
+        }
     }
+}
+
+// Replace the transactions sent at the nonce of `submit_tx` that are given up on
+// with a zero-value transfer to their sender. Otherwise the transactions of the
+// following nonces get stuck behind the nonce if none of them is ever mined.
+//
+// Returns `false` if the transfer fails to be sent.
+async fn fill_nonce_gap<M: Middleware>(m: &M, mut submit_tx: TypedTransaction) -> bool {
+    let nonce = *submit_tx.nonce().expect("nonce is set before sending; qed");
+
+    into_cancellation(&mut submit_tx);
 
-    panic!("going to usize::MAX with delays is too far away in the future; qed");
+    // The transfer replaces the pending transactions only if its fees are higher.
+    if let Err(e) = bump_predicted_fees(&mut submit_tx, RETRY_BUMP_FEES_PERCENT, m).await {
+        tracing::warn!("failed to bump the fees of the transfer at nonce {nonce}: {e}");
+    }
+    submit_tx.set_nonce(nonce);
+
+    match m.send_transaction(submit_tx, None).await {
+        Ok(sent_tx) => {
+            tracing::info!(
+                "filling nonce {nonce} with transfer {:?}",
+                sent_tx.tx_hash()
+            );
+            TX_SENDER_METRICS.filled_nonce_gaps.inc();
+            true
+        }
+        // One of the sent transactions has been mined.
+        Err(e) if is_nonce_too_low::<M>(&e) => true,
+        Err(e) => {
+            tracing::error!("failed to fill nonce {nonce}: {e}");
+            false
+        }
+    }
+}
+
+// Outcome of the transaction with the `index` of the sent ones that has been mined.
+fn outcome(receipt: TransactionReceipt, index: usize, cancelled_from: Option<usize>) -> TxOutcome {
+    match cancelled_from {
+        Some(cancelled_from) if index >= cancelled_from => TxOutcome::Cancelled(receipt),
+        _ => TxOutcome::Mined(receipt),
+    }
+}
+
+// Whether the node has rejected a transaction for good rather than failed to be reached,
+// limited the rate of requests or asked for higher fees of a replacement.
+fn is_rejected<M: Middleware>(e: &<M as Middleware>::Error) -> bool {
+    e.as_error_response().is_some_and(|e| {
+        let message = e.message.to_lowercase();
+
+        e.code != 429
+            && e.code != -32005
+            && ![
+                "rate limit",
+                "underpriced",
+                "already known",
+                "known transaction",
+            ]
+            .iter()
+            .any(|m| message.contains(m))
+    })
+}
+
+fn is_nonce_too_low<M: Middleware>(e: &<M as Middleware>::Error) -> bool {
+    e.as_error_response()
+        .is_some_and(|e| e.message.to_lowercase().contains("nonce too low"))
 }
 
 #[cfg(test)]
@@ -138,16 +417,324 @@ mod tests {
     use std::{sync::Arc, time::Duration};
 
     use ethers::{
-        providers::{Middleware, Provider, ProviderExt},
+        providers::{JsonRpcError, Middleware, MockProvider, MockResponse, Provider, ProviderExt},
         types::{
-            transaction::eip2718::TypedTransaction, Eip1559TransactionRequest, TransactionRequest,
+            transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes,
+            Eip1559TransactionRequest, Transaction, TransactionReceipt, TransactionRequest, H256,
             U256,
         },
         utils::Anvil,
     };
     use pretty_assertions::assert_eq;
 
-    use crate::{inc_u256_percent, send_tx_adjust_gas, RETRY_BUMP_FEES_PERCENT};
+    use crate::{
+        fill_nonce_gap, inc_u256_percent, into_cancellation, outcome, send_tx_adjust_gas,
+        send_tx_with_nonce_manager_cancellable, wait_for_tx_adjust_gas, NonceManager, TxOutcome,
+        CANCELLATION_GAS_LIMIT, RETRY_BUMP_FEES_PERCENT,
+    };
+
+    fn mocked_provider(mock: &MockProvider) -> Provider<MockProvider> {
+        Provider::new(mock.clone()).interval(Duration::from_millis(1))
+    }
+
+    fn node_error(code: i64, message: &str) -> MockResponse {
+        MockResponse::Error(JsonRpcError {
+            code,
+            message: message.into(),
+            data: None,
+        })
+    }
+
+    fn legacy_tx(from: Address, nonce: u64) -> TypedTransaction {
+        TransactionRequest::new()
+            .to(Address::random())
+            .value(1000)
+            .from(from)
+            .nonce(nonce)
+            .gas(100_000)
+            .gas_price(100)
+            .into()
+    }
+
+    // Mock the transaction with the given hash being mined in the next poll.
+    fn push_mined(mock: &MockProvider, tx_hash: H256) {
+        mock.push(TransactionReceipt {
+            transaction_hash: tx_hash,
+            block_number: Some(1.into()),
+            status: Some(1.into()),
+            ..Default::default()
+        })
+        .unwrap();
+        mock.push(Transaction {
+            hash: tx_hash,
+            block_number: Some(1.into()),
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn cancellation_is_transfer_to_sender() {
+        let from = Address::random();
+
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(Address::random())
+            .value(1000)
+            .data(vec![1, 2, 3])
+            .from(from)
+            .nonce(7)
+            .gas(6000000)
+            .max_fee_per_gas(100)
+            .max_priority_fee_per_gas(10)
+            .into();
+
+        into_cancellation(&mut tx);
+
+        assert_eq!(tx.to_addr(), Some(&from));
+        assert_eq!(tx.value(), Some(&U256::zero()));
+        assert_eq!(tx.data().map(|d| d.len()), Some(0));
+        assert_eq!(tx.gas(), Some(&CANCELLATION_GAS_LIMIT.into()));
+        assert_eq!(tx.nonce(), Some(&7.into()));
+
+        let TypedTransaction::Eip1559(tx) = tx else {
+            panic!("expected eip1559 tx");
+        };
+        assert_eq!(tx.max_fee_per_gas, Some(100.into()));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(10.into()));
+    }
+
+    #[test]
+    fn transactions_sent_from_cancellation_on_are_cancelled() {
+        let is_cancelled = |index, cancelled_from| {
+            matches!(
+                outcome(Default::default(), index, cancelled_from),
+                TxOutcome::Cancelled(_)
+            )
+        };
+
+        assert!(!is_cancelled(0, None));
+        assert!(!is_cancelled(3, None));
+
+        // The first two transactions have been sent before the cancellation.
+        assert!(!is_cancelled(0, Some(2)));
+        assert!(!is_cancelled(1, Some(2)));
+        assert!(is_cancelled(2, Some(2)));
+        assert!(is_cancelled(3, Some(2)));
+
+        // The first transaction may already be a cancellation.
+        assert!(is_cancelled(0, Some(0)));
+    }
+
+    #[tokio::test]
+    async fn nonce_gap_is_filled_with_transfer_to_sender() {
+        let mock = MockProvider::new();
+        let provider = Provider::new(mock.clone());
+        let from = Address::random();
+
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(Address::random())
+            .value(1000)
+            .data(vec![1, 2, 3])
+            .from(from)
+            .nonce(5)
+            .gas(6000000)
+            .gas_price(100)
+            .into();
+
+        mock.push(H256::random()).unwrap();
+        fill_nonce_gap(&provider, tx).await;
+
+        let expected: TypedTransaction = TransactionRequest::new()
+            .to(from)
+            .value(0)
+            .data(Bytes::default())
+            .from(from)
+            .nonce(5)
+            .gas(CANCELLATION_GAS_LIMIT)
+            .gas_price(115)
+            .into();
+        mock.assert_request("eth_sendTransaction", [expected])
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_send_fills_nonce_if_later_nonce_is_in_flight() {
+        let mock = MockProvider::new();
+        let provider = mocked_provider(&mock);
+        let from = Address::random();
+        let nonces = NonceManager::new(from);
+
+        // Nonce 5 is handed out again while nonce 6 is in flight.
+        mock.push(U256::from(5)).unwrap();
+        assert_eq!(nonces.next(&provider).await.unwrap(), 5.into());
+        assert_eq!(nonces.next(&provider).await.unwrap(), 6.into());
+        nonces.release(5.into()).await;
+        mock.assert_request("eth_getTransactionCount", (from, BlockNumber::Pending))
+            .unwrap();
+
+        mock.push(H256::random()).unwrap();
+        mock.push_response(node_error(
+            -32000,
+            "insufficient funds for gas * price + value",
+        ));
+
+        let tx = legacy_tx(from, 5);
+        let res = send_tx_with_nonce_manager_cancellable(
+            &provider,
+            tx.clone(),
+            Duration::from_secs(60),
+            &nonces,
+            100_000.into(),
+            || async { false },
+        )
+        .await;
+        assert!(res.is_err());
+
+        mock.assert_request("eth_sendTransaction", [tx.clone()])
+            .unwrap();
+        let mut transfer = tx;
+        into_cancellation(&mut transfer);
+        transfer.set_gas_price(115);
+        mock.assert_request("eth_sendTransaction", [transfer])
+            .unwrap();
+
+        // The nonce is used by the transfer.
+        assert_eq!(nonces.next(&provider).await.unwrap(), 7.into());
+    }
+
+    #[tokio::test]
+    async fn failed_send_rolls_back_last_nonce() {
+        let mock = MockProvider::new();
+        let provider = mocked_provider(&mock);
+        let from = Address::random();
+        let nonces = NonceManager::new(from);
+
+        mock.push_response(node_error(
+            -32000,
+            "insufficient funds for gas * price + value",
+        ));
+        mock.push(U256::from(5)).unwrap();
+
+        let res = send_tx_with_nonce_manager_cancellable(
+            &provider,
+            legacy_tx(from, 5),
+            Duration::from_secs(60),
+            &nonces,
+            100_000.into(),
+            || async { false },
+        )
+        .await;
+        assert!(res.is_err());
+
+        // No transfer is sent and the nonce is handed out again.
+        assert_eq!(nonces.next(&provider).await.unwrap(), 5.into());
+    }
+
+    #[tokio::test]
+    async fn failed_fee_bumps_are_retried() {
+        let mock = MockProvider::new();
+        let provider = mocked_provider(&mock);
+        let tx_hash = H256::random();
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(Address::random())
+            .from(Address::random())
+            .nonce(5)
+            .max_fee_per_gas(100)
+            .max_priority_fee_per_gas(10)
+            .into();
+
+        // Polls of the transaction fail until it is due to be sent again,
+        // then the latest block fails to be fetched and the transaction is mined.
+        let should_cancel = || {
+            push_mined(&mock, tx_hash);
+            mock.push_response(node_error(-32603, "internal error"));
+            async { false }
+        };
+
+        let outcome = wait_for_tx_adjust_gas(
+            &provider,
+            tx,
+            tx_hash,
+            Duration::from_millis(50),
+            should_cancel,
+        )
+        .await
+        .unwrap();
+
+        let TxOutcome::Mined(receipt) = outcome else {
+            panic!("expected mined transaction");
+        };
+        assert_eq!(receipt.transaction_hash, tx_hash);
+    }
+
+    #[tokio::test]
+    async fn transient_send_errors_are_retried() {
+        let mock = MockProvider::new();
+        let provider = mocked_provider(&mock);
+        let tx_hash = H256::random();
+
+        let should_cancel = || {
+            push_mined(&mock, tx_hash);
+            mock.push_response(node_error(-32005, "limit exceeded"));
+            async { false }
+        };
+
+        let outcome = wait_for_tx_adjust_gas(
+            &provider,
+            legacy_tx(Address::random(), 5),
+            tx_hash,
+            Duration::from_millis(50),
+            should_cancel,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(outcome.receipt().transaction_hash, tx_hash);
+    }
+
+    #[tokio::test]
+    async fn rejected_transactions_fill_nonce() {
+        let mock = MockProvider::new();
+        let provider = mocked_provider(&mock);
+        let tx_hash = H256::random();
+        let tx = legacy_tx(Address::random(), 5);
+
+        let should_cancel = || {
+            // Only the transaction has been polled so far.
+            while mock
+                .assert_request("eth_getTransactionByHash", [tx_hash])
+                .is_ok()
+            {}
+
+            mock.push(H256::random()).unwrap();
+            mock.push_response(node_error(
+                -32000,
+                "insufficient funds for gas * price + value",
+            ));
+            async { false }
+        };
+
+        let res = wait_for_tx_adjust_gas(
+            &provider,
+            tx.clone(),
+            tx_hash,
+            Duration::from_millis(50),
+            should_cancel,
+        )
+        .await;
+        assert!(res.is_err());
+
+        // The transaction is sent again with bumped fees and replaced by a transfer once rejected.
+        let mut bumped = tx;
+        bumped.set_gas_price(115);
+        mock.assert_request("eth_sendTransaction", [bumped.clone()])
+            .unwrap();
+        let mut transfer = bumped;
+        into_cancellation(&mut transfer);
+        transfer.set_gas_price(132);
+        mock.assert_request("eth_sendTransaction", [transfer])
+            .unwrap();
+    }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn retry_sending_single_tx() {
@@ -10,6 +10,21 @@ use vise::{Counter, Metrics};
 pub(super) struct TxSenderMetrics {
     /// Timedout transactions count.
     pub timedout_transactions: Counter,
+
+    /// Transactions dropped from the mempool and sent again.
+    pub dropped_transactions: Counter,
+
+    /// Nonces of transactions failed to be sent handed out again.
+    pub reused_nonces: Counter,
+
+    /// Pending transactions replaced by zero-value transfers to their senders.
+    pub cancelled_transactions: Counter,
+
+    /// Nonces of transactions given up on filled with zero-value transfers to their senders.
+    pub filled_nonce_gaps: Counter,
+
+    /// Errors waiting for pending transactions that are retried.
+    pub retried_errors: Counter,
 }
 
 #[vise::register]
@@ -0,0 +1,182 @@
+//! Local management of nonces of transactions sent from one account.
+
+use std::collections::BTreeSet;
+
+use ethers::{
+    providers::Middleware,
+    types::{Address, BlockNumber, U256},
+};
+use tokio::sync::Mutex;
+
+use crate::metrics::TX_SENDER_METRICS;
+
+#[derive(Debug, Default)]
+struct State {
+    // The nonce of the next new transaction, `None` until synced with the node.
+    next: Option<U256>,
+    // Nonces handed out but never used by a sent transaction.
+    released: BTreeSet<U256>,
+}
+
+/// Hands out nonces of transactions of an account that may be
+/// in flight concurrently without waiting for each other to be mined.
+///
+/// Nonces are tracked locally after being synced from the `pending`
+/// transaction count of the account on the first use. Nonces of
+/// transactions that have failed to be sent are handed out again
+/// first so that there are no gaps blocking the later transactions.
+#[derive(Debug)]
+pub struct NonceManager {
+    address: Address,
+    state: Mutex<State>,
+}
+
+impl NonceManager {
+    /// Create a new [`NonceManager`] of the account with the given `address`.
+    pub fn new(address: Address) -> Self {
+        Self {
+            address,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// The address of the account nonces are managed of.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Get the nonce of the next transaction.
+    pub async fn next<M: Middleware>(&self, m: &M) -> Result<U256, M::Error> {
+        let mut state = self.state.lock().await;
+
+        if let Some(nonce) = state.released.pop_first() {
+            TX_SENDER_METRICS.reused_nonces.inc();
+            return Ok(nonce);
+        }
+
+        let nonce = match state.next {
+            Some(nonce) => nonce,
+            None => self.pending_nonce(m).await?,
+        };
+
+        state.next = Some(nonce + 1);
+
+        Ok(nonce)
+    }
+
+    /// Return a nonce no transaction has been sent with to be used again.
+    pub async fn release(&self, nonce: U256) {
+        let mut state = self.state.lock().await;
+
+        if state.next.is_some_and(|next| nonce < next) {
+            state.released.insert(nonce);
+        }
+    }
+
+    /// Take back a nonce no transaction has been sent with if it is the last one handed out.
+    ///
+    /// Returns `false` if nonces after it have been handed out, the transactions sent
+    /// with them are then stuck behind the nonce until a transaction is sent with it.
+    pub async fn roll_back(&self, nonce: U256) -> bool {
+        let mut state = self.state.lock().await;
+
+        if state.next != Some(nonce + 1) {
+            return false;
+        }
+
+        state.next = Some(nonce);
+
+        true
+    }
+
+    /// Sync with the `pending` transaction count of the account after
+    /// its transactions have been sent bypassing the manager.
+    pub async fn resync<M: Middleware>(&self, m: &M) -> Result<(), M::Error> {
+        let mut state = self.state.lock().await;
+
+        let pending = self.pending_nonce(m).await?;
+
+        state.released = state.released.split_off(&pending);
+        state.next = Some(state.next.map_or(pending, |next| next.max(pending)));
+
+        Ok(())
+    }
+
+    async fn pending_nonce<M: Middleware>(&self, m: &M) -> Result<U256, M::Error> {
+        m.get_transaction_count(self.address, Some(BlockNumber::Pending.into()))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::providers::{MockProvider, Provider};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn fills_gaps_of_released_nonces() {
+        let mock = MockProvider::new();
+        mock.push(U256::from(5)).unwrap();
+        let provider = Provider::new(mock.clone());
+        let nonces = NonceManager::new(Address::zero());
+
+        assert_eq!(nonces.next(&provider).await.unwrap(), 5.into());
+        assert_eq!(nonces.next(&provider).await.unwrap(), 6.into());
+        assert_eq!(nonces.next(&provider).await.unwrap(), 7.into());
+
+        nonces.release(6.into()).await;
+        nonces.release(10.into()).await;
+
+        assert_eq!(nonces.next(&provider).await.unwrap(), 6.into());
+        assert_eq!(nonces.next(&provider).await.unwrap(), 8.into());
+
+        // The nonce has been synced only once.
+        assert!(mock
+            .assert_request(
+                "eth_getTransactionCount",
+                (Address::zero(), BlockNumber::Pending)
+            )
+            .is_ok());
+        assert!(mock
+            .assert_request(
+                "eth_getTransactionCount",
+                (Address::zero(), BlockNumber::Pending)
+            )
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn resyncs_with_pending_nonce() {
+        let mock = MockProvider::new();
+        mock.push(U256::from(9)).unwrap();
+        mock.push(U256::from(1)).unwrap();
+        let provider = Provider::new(mock);
+        let nonces = NonceManager::new(Address::zero());
+
+        assert_eq!(nonces.next(&provider).await.unwrap(), 1.into());
+        assert_eq!(nonces.next(&provider).await.unwrap(), 2.into());
+        nonces.release(1.into()).await;
+
+        nonces.resync(&provider).await.unwrap();
+
+        assert_eq!(nonces.next(&provider).await.unwrap(), 9.into());
+    }
+
+    #[tokio::test]
+    async fn rolls_back_only_last_nonce() {
+        let mock = MockProvider::new();
+        mock.push(U256::from(5)).unwrap();
+        let provider = Provider::new(mock);
+        let nonces = NonceManager::new(Address::zero());
+
+        assert_eq!(nonces.next(&provider).await.unwrap(), 5.into());
+        assert_eq!(nonces.next(&provider).await.unwrap(), 6.into());
+
+        assert!(!nonces.roll_back(5.into()).await);
+        assert!(nonces.roll_back(6.into()).await);
+
+        assert_eq!(nonces.next(&provider).await.unwrap(), 6.into());
+    }
+}
@@ -10,7 +10,9 @@ use sqlx::PgPool;
 use storage::StoredWithdrawal;
 use tokio::pin;
 
-use client::{zksync_contract::L2ToL1Event, BlockEvent, WithdrawalEvent, ZksyncMiddleware};
+use client::{
+    zksync_contract::L2ToL1Event, BaseToken, BlockEvent, WithdrawalEvent, ZksyncMiddleware,
+};
 use withdrawals_meterer::{MeteringComponent, WithdrawalsMeter};
 
 use crate::metrics::WATCHER_METRICS;
@@ -32,6 +34,7 @@ pub enum Error {
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub struct Watcher<M2> {
+    chain_id: u64,
     l2_provider: Arc<M2>,
     pgpool: PgPool,
     withdrawals_meterer: Option<WithdrawalsMeter>,
@@ -42,13 +45,21 @@ where
     M2: ZksyncMiddleware + 'static,
     <M2 as Middleware>::Provider: JsonRpcClient,
 {
-    pub fn new(l2_provider: Arc<M2>, pgpool: PgPool, meter_withdrawals: bool) -> Self {
+    pub fn new(
+        chain_id: u64,
+        l2_provider: Arc<M2>,
+        pgpool: PgPool,
+        meter_withdrawals: bool,
+        base_token: BaseToken,
+    ) -> Self {
         let withdrawals_meterer = meter_withdrawals.then_some(WithdrawalsMeter::new(
             pgpool.clone(),
             MeteringComponent::RequestedWithdrawals,
+            base_token,
         ));
 
         Self {
+            chain_id,
             l2_provider,
             pgpool,
             withdrawals_meterer,
@@ -66,6 +77,7 @@ where
         WE: Stream<Item = L2Event> + Send + 'static,
     {
         let Watcher {
+            chain_id,
             l2_provider,
             pgpool,
             withdrawals_meterer,
@@ -88,12 +100,14 @@ where
 
         let l1_loop_handler = tokio::spawn(run_l1_events_loop(
             pgpool.clone(),
+            chain_id,
             block_events,
             l2_provider,
         ));
         let l2_loop_handler = tokio::spawn(async move {
             run_l2_events_loop(
                 pgpool,
+                chain_id,
                 withdrawal_events,
                 from_l2_block,
                 withdrawals_meterer,
@@ -118,8 +132,12 @@ where
     }
 }
 
-async fn process_l2_to_l1_events(pool: &PgPool, events: Vec<L2ToL1Event>) -> Result<()> {
-    storage::l2_to_l1_events(pool, &events).await?;
+async fn process_l2_to_l1_events(
+    pool: &PgPool,
+    chain_id: u64,
+    events: Vec<L2ToL1Event>,
+) -> Result<()> {
+    storage::l2_to_l1_events(pool, chain_id, &events).await?;
 
     Ok(())
 }
@@ -146,14 +164,15 @@ enum BlockRangesParams {
 }
 
 impl BlockRangesParams {
-    async fn write_to_storage(self, pool: &PgPool) -> Result<()> {
+    async fn write_to_storage(self, pool: &PgPool, chain_id: u64) -> Result<()> {
         match self {
             BlockRangesParams::Commit {
                 range_begin,
                 range_end,
                 block_number,
             } => {
-                storage::committed_new_batch(pool, range_begin, range_end, block_number).await?;
+                storage::committed_new_batch(pool, chain_id, range_begin, range_end, block_number)
+                    .await?;
 
                 tracing::info!(
                     "Changed withdrawals status to committed for range {range_begin}-{range_end}"
@@ -164,7 +183,8 @@ impl BlockRangesParams {
                 range_end,
                 block_number,
             } => {
-                storage::verified_new_batch(pool, range_begin, range_end, block_number).await?;
+                storage::verified_new_batch(pool, chain_id, range_begin, range_end, block_number)
+                    .await?;
                 tracing::info!(
                     "Changed withdrawals status to verified for range {range_begin}-{range_end}"
                 );
@@ -174,14 +194,15 @@ impl BlockRangesParams {
                 range_end,
                 block_number,
             } => {
-                storage::executed_new_batch(pool, range_begin, range_end, block_number).await?;
+                storage::executed_new_batch(pool, chain_id, range_begin, range_end, block_number)
+                    .await?;
 
                 tracing::info!(
                     "Changed withdrawals status to executed for range {range_begin}-{range_end}"
                 );
             }
             BlockRangesParams::L2ToL1Events { events } => {
-                process_l2_to_l1_events(pool, events).await?;
+                process_l2_to_l1_events(pool, chain_id, events).await?;
             }
         }
         Ok(())
@@ -189,6 +210,7 @@ impl BlockRangesParams {
 }
 
 async fn request_block_ranges<M2>(
+    chain_id: u64,
     event: BlockEvent,
     l2_middleware: M2,
 ) -> Result<Option<BlockRangesParams>>
@@ -204,9 +226,7 @@ where
                 .get_l1_batch_block_range(event.batch_number.as_u64() as u32)
                 .await?
             {
-                WATCHER_METRICS
-                    .l2_last_committed_block
-                    .set(range_end.as_u64() as i64);
+                WATCHER_METRICS.l2_last_committed_block[&chain_id].set(range_end.as_u64() as i64);
                 Ok(Some(BlockRangesParams::Commit {
                     range_begin: range_begin.as_u64(),
                     range_end: range_end.as_u64(),
@@ -233,7 +253,7 @@ where
                 .map(|range| range.1.as_u64());
 
             if let (Some(range_begin), Some(range_end)) = (range_begin, range_end) {
-                WATCHER_METRICS.l2_last_verified_block.set(range_end as i64);
+                WATCHER_METRICS.l2_last_verified_block[&chain_id].set(range_end as i64);
                 Ok(Some(BlockRangesParams::Verify {
                     range_begin,
                     range_end,
@@ -254,9 +274,7 @@ where
                 .get_l1_batch_block_range(event.batch_number.as_u64() as u32)
                 .await?
             {
-                WATCHER_METRICS
-                    .l2_last_executed_block
-                    .set(range_end.as_u64() as i64);
+                WATCHER_METRICS.l2_last_executed_block[&chain_id].set(range_end.as_u64() as i64);
                 Ok(Some(BlockRangesParams::Execute {
                     range_begin: range_begin.as_u64(),
                     range_end: range_end.as_u64(),
@@ -276,6 +294,7 @@ where
 
 async fn process_block_events<M2>(
     pool: &PgPool,
+    chain_id: u64,
     events: Vec<BlockEvent>,
     l2_middleware: M2,
 ) -> Result<()>
@@ -285,7 +304,7 @@ where
     let results: Result<Vec<_>> = futures::future::join_all(
         events
             .into_iter()
-            .map(|event| request_block_ranges(event, &l2_middleware)),
+            .map(|event| request_block_ranges(chain_id, event, &l2_middleware)),
     )
     .await
     .into_iter()
@@ -294,7 +313,7 @@ where
     let results = results?;
 
     for result in results.into_iter().flatten() {
-        result.write_to_storage(pool).await?;
+        result.write_to_storage(pool, chain_id).await?;
     }
 
     Ok(())
@@ -302,6 +321,7 @@ where
 
 async fn process_withdrawals_in_block(
     pool: &PgPool,
+    chain_id: u64,
     events: Vec<WithdrawalEvent>,
     withdrawals_meterer: &mut Option<WithdrawalsMeter>,
 ) -> Result<()> {
@@ -311,9 +331,7 @@ async fn process_withdrawals_in_block(
 
     for (_tx_hash, group) in group_by.into_iter() {
         for (index, event) in group.into_iter().enumerate() {
-            WATCHER_METRICS
-                .l2_last_seen_block
-                .set(event.block_number as i64);
+            WATCHER_METRICS.l2_last_seen_block[&chain_id].set(event.block_number as i64);
             tracing::info!("withdrawal {event:?} index in transaction is {index}");
 
             withdrawals_vec.push((event, index));
@@ -324,6 +342,7 @@ async fn process_withdrawals_in_block(
 
     for (event, index) in withdrawals_vec.into_iter() {
         stored_withdrawals.push(StoredWithdrawal {
+            chain_id,
             event,
             index_in_tx: index,
         });
@@ -342,7 +361,12 @@ async fn process_withdrawals_in_block(
     Ok(())
 }
 
-async fn run_l1_events_loop<BE, M2>(pool: PgPool, be: BE, l2_middleware: M2) -> Result<()>
+async fn run_l1_events_loop<BE, M2>(
+    pool: PgPool,
+    chain_id: u64,
+    be: BE,
+    l2_middleware: M2,
+) -> Result<()>
 where
     BE: Stream<Item = BlockEvent>,
     M2: ZksyncMiddleware,
@@ -363,6 +387,7 @@ where
 
             process_block_events(
                 &pool,
+                chain_id,
                 std::mem::take(&mut block_event_batch),
                 &l2_middleware,
             )
@@ -377,6 +402,7 @@ where
 
 async fn run_l2_events_loop<WE>(
     pool: PgPool,
+    chain_id: u64,
     we: WE,
     from_l2_block: u64,
     mut withdrawals_meterer: Option<WithdrawalsMeter>,
@@ -395,6 +421,7 @@ where
                 if event.block_number > curr_l2_block_number {
                     process_withdrawals_in_block(
                         &pool,
+                        chain_id,
                         std::mem::take(&mut in_block_events),
                         &mut withdrawals_meterer,
                     )
@@ -405,7 +432,7 @@ where
             }
             L2Event::L2TokenInitEvent(event) => {
                 tracing::debug!("l2 token init event {event:?}");
-                storage::add_token(&pool, &event).await?;
+                storage::add_token(&pool, chain_id, &event).await?;
             }
             L2Event::RestartedFromBlock(_block_number) => {
                 // The event producer has been restarted at a given
@@ -421,6 +448,7 @@ where
                 // will never be re-sent.
                 process_withdrawals_in_block(
                     &pool,
+                    chain_id,
                     std::mem::take(&mut in_block_events),
                     &mut withdrawals_meterer,
                 )
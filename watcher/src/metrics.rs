@@ -2,23 +2,27 @@
 
 #![allow(unexpected_cfgs)]
 
-use vise::{Gauge, Metrics};
+use vise::{Gauge, LabeledFamily, Metrics};
 
 /// Watcher metrics
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "watcher")]
 pub(super) struct WatcherMetrics {
     /// Block number of last seen block commit event.
-    pub l2_last_committed_block: Gauge,
+    #[metrics(labels = ["chain_id"])]
+    pub l2_last_committed_block: LabeledFamily<u64, Gauge>,
 
     /// Block number of last seen block verify event.
-    pub l2_last_verified_block: Gauge,
+    #[metrics(labels = ["chain_id"])]
+    pub l2_last_verified_block: LabeledFamily<u64, Gauge>,
 
     /// Block number of last seen block execute event.
-    pub l2_last_executed_block: Gauge,
+    #[metrics(labels = ["chain_id"])]
+    pub l2_last_executed_block: LabeledFamily<u64, Gauge>,
 
     /// Last seen L2 block number.
-    pub l2_last_seen_block: Gauge,
+    #[metrics(labels = ["chain_id"])]
+    pub l2_last_seen_block: LabeledFamily<u64, Gauge>,
 }
 
 #[vise::register]
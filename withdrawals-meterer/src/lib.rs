@@ -7,7 +7,7 @@
 
 use std::{collections::HashMap, str::FromStr};
 
-use client::{ETH_ADDRESS, ETH_TOKEN_ADDRESS};
+use client::{BaseToken, ETH_TOKEN_ADDRESS};
 use ethers::types::Address;
 use sqlx::PgPool;
 use storage::StoredWithdrawal;
@@ -20,8 +20,9 @@ pub use metrics::MeteringComponent;
 /// State of withdrawals volumes metering.
 pub struct WithdrawalsMeter {
     pool: PgPool,
-    /// A mapping from L2 address to L1 address and decimals of token.
-    tokens: HashMap<Address, (u32, Address)>,
+    /// A mapping from chain id and L2 address to L1 address and decimals of token.
+    tokens: HashMap<(u64, Address), (u32, Address)>,
+    base_token: BaseToken,
     metering_component: MeteringComponent,
 }
 
@@ -33,16 +34,14 @@ impl WithdrawalsMeter {
     /// * `pool`: DB connection pool
     /// * `component_name`: Name of the component that does metering, metric names will be
     ///    derived from it
-    pub fn new(pool: PgPool, metering_component: MeteringComponent) -> Self {
-        let mut token_decimals = HashMap::new();
-
-        token_decimals.insert(ETH_TOKEN_ADDRESS, (18_u32, ETH_ADDRESS));
-
+    /// * `base_token`: The base token of the chain withdrawals are metered of
+    pub fn new(pool: PgPool, metering_component: MeteringComponent, base_token: BaseToken) -> Self {
         WM_METRICS.token_decimals_stored[&metering_component].inc_by(1);
 
         Self {
             pool,
-            tokens: token_decimals,
+            tokens: HashMap::new(),
+            base_token,
             metering_component,
         }
     }
@@ -67,10 +66,18 @@ impl WithdrawalsMeter {
         withdrawals: &[StoredWithdrawal],
     ) -> Result<(), storage::Error> {
         for w in withdrawals {
-            let (decimals, l1_token_address) = match self.tokens.get(&w.event.token) {
+            let key = (w.chain_id, w.event.token);
+            let (decimals, l1_token_address) = match self.tokens.get(&key) {
+                None if w.event.token == ETH_TOKEN_ADDRESS => {
+                    (self.base_token.decimals, self.base_token.l1_address)
+                }
                 None => {
-                    let Some((decimals, address)) =
-                        storage::token_decimals_and_l1_address(&self.pool, w.event.token).await?
+                    let Some((decimals, address)) = storage::token_decimals_and_l1_address(
+                        &self.pool,
+                        w.chain_id,
+                        w.event.token,
+                    )
+                    .await?
                     else {
                         tracing::error!(
                             "Received withdrawal from unknown token {:?}",
@@ -79,7 +86,7 @@ impl WithdrawalsMeter {
                         continue;
                     };
 
-                    self.tokens.insert(w.event.token, (decimals, address));
+                    self.tokens.insert(key, (decimals, address));
 
                     WM_METRICS.token_decimals_stored[&self.metering_component].inc_by(1);
 